@@ -1,7 +1,11 @@
 use {Error, Result, NixPath};
 use errno::Errno;
 use libc::{self, c_int, c_uint, c_char, size_t, ssize_t};
-use sys::stat::Mode;
+use sys::stat::{fstat, Mode};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use sys::signal::SigNum;
+use unistd::{lseek, Pid, Whence};
+use std::mem;
 use std::os::unix::io::RawFd;
 use std::ffi::OsStr;
 use std::os::unix::ffi::OsStrExt;
@@ -241,6 +245,167 @@ pub fn renameat<P1: ?Sized + NixPath, P2: ?Sized + NixPath>(olddirfd: RawFd, old
     Errno::result(res).map(drop)
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod consts {
+    use libc::c_int;
+
+    // Not exported by libc. These values are shared by all architectures except alpha, parisc
+    // and sparc.
+    pub const F_SETSIG: c_int = 10;
+    pub const F_GETSIG: c_int = 11;
+    pub const F_SETOWN_EX: c_int = 15;
+    pub const F_GETOWN_EX: c_int = 16;
+
+    pub const F_DUPFD_QUERY: c_int = 1027;
+    pub const F_GET_RW_HINT: c_int = 1035;
+    pub const F_SET_RW_HINT: c_int = 1036;
+
+    pub const F_OWNER_TID: c_int = 0;
+    pub const F_OWNER_PID: c_int = 1;
+    pub const F_OWNER_PGRP: c_int = 2;
+
+    pub const DN_ACCESS: u32 = 0x0000_0001;
+    pub const DN_MODIFY: u32 = 0x0000_0002;
+    pub const DN_CREATE: u32 = 0x0000_0004;
+    pub const DN_DELETE: u32 = 0x0000_0008;
+    pub const DN_RENAME: u32 = 0x0000_0010;
+    pub const DN_ATTRIB: u32 = 0x0000_0020;
+    pub const DN_MULTISHOT: u32 = 0x8000_0000;
+
+    pub const RWH_WRITE_LIFE_NOT_SET: u64 = 0;
+    pub const RWH_WRITE_LIFE_NONE: u64 = 1;
+    pub const RWH_WRITE_LIFE_SHORT: u64 = 2;
+    pub const RWH_WRITE_LIFE_MEDIUM: u64 = 3;
+    pub const RWH_WRITE_LIFE_LONG: u64 = 4;
+    pub const RWH_WRITE_LIFE_EXTREME: u64 = 5;
+
+    #[repr(C)]
+    pub struct f_owner_ex {
+        pub type_: c_int,
+        pub pid: ::libc::pid_t,
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+bitflags!(
+    /// Events to be notified about with `F_NOTIFY`.
+    pub struct DnFlags: u32 {
+        /// A file in the directory was accessed.
+        const DN_ACCESS = consts::DN_ACCESS;
+        /// A file in the directory was modified.
+        const DN_MODIFY = consts::DN_MODIFY;
+        /// A file was created in the directory.
+        const DN_CREATE = consts::DN_CREATE;
+        /// A file was unlinked from the directory.
+        const DN_DELETE = consts::DN_DELETE;
+        /// A file in the directory was renamed.
+        const DN_RENAME = consts::DN_RENAME;
+        /// The attributes of a file in the directory were changed.
+        const DN_ATTRIB = consts::DN_ATTRIB;
+        /// Keep the notification active after the first event.
+        ///
+        /// Without this flag the notification has to be re-armed after every event.
+        const DN_MULTISHOT = consts::DN_MULTISHOT;
+    }
+);
+
+/// The process, process group or thread that receives `SIGIO` and `SIGURG` for a file
+/// descriptor (`F_SETOWN_EX`, `F_GETOWN_EX`).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FdOwner {
+    /// A single thread, identified by its thread id.
+    Thread(Pid),
+    /// A process.
+    Process(Pid),
+    /// A process group.
+    ProcessGroup(Pid),
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl FdOwner {
+    fn to_raw(self) -> consts::f_owner_ex {
+        let (type_, pid) = match self {
+            FdOwner::Thread(pid) => (consts::F_OWNER_TID, pid),
+            FdOwner::Process(pid) => (consts::F_OWNER_PID, pid),
+            FdOwner::ProcessGroup(pid) => (consts::F_OWNER_PGRP, pid),
+        };
+        consts::f_owner_ex { type_, pid: pid.into() }
+    }
+
+    fn from_raw(owner: &consts::f_owner_ex) -> Result<FdOwner> {
+        let pid = Pid::from_raw(owner.pid);
+        match owner.type_ {
+            consts::F_OWNER_TID => Ok(FdOwner::Thread(pid)),
+            consts::F_OWNER_PID => Ok(FdOwner::Process(pid)),
+            consts::F_OWNER_PGRP => Ok(FdOwner::ProcessGroup(pid)),
+            _ => Err(Error::invalid_argument()),
+        }
+    }
+}
+
+/// The kind of lease held on a file (`F_SETLEASE`, `F_GETLEASE`).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LeaseType {
+    /// A read lease. The holder is notified when another process opens the file for writing or
+    /// truncates it.
+    Read = libc::F_RDLCK as i32,
+    /// A write lease. The holder is notified when another process opens or truncates the file.
+    Write = libc::F_WRLCK as i32,
+    /// No lease. Used to remove a lease.
+    Unlock = libc::F_UNLCK as i32,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl LeaseType {
+    fn from_c_int(lease: c_int) -> Result<LeaseType> {
+        match lease {
+            x if x == libc::F_RDLCK as c_int => Ok(LeaseType::Read),
+            x if x == libc::F_WRLCK as c_int => Ok(LeaseType::Write),
+            x if x == libc::F_UNLCK as c_int => Ok(LeaseType::Unlock),
+            _ => Err(Error::invalid_argument()),
+        }
+    }
+}
+
+/// The expected lifetime of data written to a file (`F_SET_RW_HINT`, `F_GET_RW_HINT`).
+///
+/// Filesystems and devices can use this hint to place data with similar lifetimes together.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(u64)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RwHint {
+    /// No hint has been set.
+    NotSet = consts::RWH_WRITE_LIFE_NOT_SET,
+    /// The data has no particular lifetime.
+    None = consts::RWH_WRITE_LIFE_NONE,
+    /// The data is expected to have a short lifetime.
+    Short = consts::RWH_WRITE_LIFE_SHORT,
+    /// The data is expected to live longer than `Short` data.
+    Medium = consts::RWH_WRITE_LIFE_MEDIUM,
+    /// The data is expected to live longer than `Medium` data.
+    Long = consts::RWH_WRITE_LIFE_LONG,
+    /// The data is expected to live longer than `Long` data.
+    Extreme = consts::RWH_WRITE_LIFE_EXTREME,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl RwHint {
+    fn from_u64(hint: u64) -> Result<RwHint> {
+        match hint {
+            consts::RWH_WRITE_LIFE_NOT_SET => Ok(RwHint::NotSet),
+            consts::RWH_WRITE_LIFE_NONE => Ok(RwHint::None),
+            consts::RWH_WRITE_LIFE_SHORT => Ok(RwHint::Short),
+            consts::RWH_WRITE_LIFE_MEDIUM => Ok(RwHint::Medium),
+            consts::RWH_WRITE_LIFE_LONG => Ok(RwHint::Long),
+            consts::RWH_WRITE_LIFE_EXTREME => Ok(RwHint::Extreme),
+            _ => Err(Error::invalid_argument()),
+        }
+    }
+}
+
 #[allow(missing_debug_implementations)]
 pub enum FcntlArg<'a> {
     F_DUPFD(RawFd),
@@ -268,12 +433,41 @@ pub enum FcntlArg<'a> {
     F_GETPIPE_SZ,
     #[cfg(any(target_os = "linux", target_os = "android"))]
     F_SETPIPE_SZ(c_int),
-
-    // TODO: Rest of flags
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    F_SETOWN_EX(FdOwner),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    F_GETOWN_EX(&'a mut FdOwner),
+    /// Set the signal sent when I/O is possible. `None` restores the default of `SIGIO`.
+    ///
+    /// Only realtime signals are queued with an `si_fd` telling which file descriptor is ready.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    F_SETSIG(Option<SigNum>),
+    /// Get the signal sent when I/O is possible. 0 means `SIGIO`.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    F_GETSIG,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    F_NOTIFY(DnFlags),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    F_SETLEASE(LeaseType),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    F_GETLEASE,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    F_SET_RW_HINT(RwHint),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    F_GET_RW_HINT(&'a mut RwHint),
+    /// Check whether the file descriptor refers to the same open file description as the given
+    /// one. Returns 1 if it does and 0 otherwise.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    F_DUPFD_QUERY(RawFd),
 }
 pub use self::FcntlArg::*;
 
-// TODO: Figure out how to handle value fcntl returns
+/// Perform an operation on a file descriptor.
+///
+/// The meaning of the returned value depends on the command. The `get_*` functions in this
+/// module wrap the commands that return a value and convert it into the proper type.
+///
+/// For more information, see [the fcntl(2) man page.](http://man7.org/linux/man-pages/man2/fcntl.2.html)
 pub fn fcntl(fd: RawFd, arg: FcntlArg) -> Result<c_int> {
     let res = unsafe {
         match arg {
@@ -287,6 +481,12 @@ pub fn fcntl(fd: RawFd, arg: FcntlArg) -> Result<c_int> {
             F_SETLKW(flock) => libc::fcntl(fd, libc::F_SETLKW, flock),
             F_GETLK(flock) => libc::fcntl(fd, libc::F_GETLK, flock),
             #[cfg(any(target_os = "android", target_os = "linux"))]
+            F_OFD_SETLK(flock) => libc::fcntl(fd, libc::F_OFD_SETLK, flock),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            F_OFD_SETLKW(flock) => libc::fcntl(fd, libc::F_OFD_SETLKW, flock),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            F_OFD_GETLK(flock) => libc::fcntl(fd, libc::F_OFD_GETLK, flock),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
             F_ADD_SEALS(flag) => libc::fcntl(fd, libc::F_ADD_SEALS, flag.bits()),
             #[cfg(any(target_os = "android", target_os = "linux"))]
             F_GET_SEALS => libc::fcntl(fd, libc::F_GET_SEALS),
@@ -297,13 +497,366 @@ pub fn fcntl(fd: RawFd, arg: FcntlArg) -> Result<c_int> {
             #[cfg(any(target_os = "linux", target_os = "android"))]
             F_SETPIPE_SZ(size) => libc::fcntl(fd, libc::F_SETPIPE_SZ, size),
             #[cfg(any(target_os = "linux", target_os = "android"))]
-            _ => unimplemented!()
+            F_SETOWN_EX(owner) => libc::fcntl(fd, consts::F_SETOWN_EX, &owner.to_raw()),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            F_GETOWN_EX(owner) => {
+                let mut raw = consts::f_owner_ex { type_: 0, pid: 0 };
+                let res = libc::fcntl(fd, consts::F_GETOWN_EX, &mut raw);
+                if res != -1 {
+                    *owner = FdOwner::from_raw(&raw)?;
+                }
+                res
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            F_SETSIG(signal) => {
+                libc::fcntl(fd, consts::F_SETSIG, signal.map(c_int::from).unwrap_or(0))
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            F_GETSIG => libc::fcntl(fd, consts::F_GETSIG),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            F_NOTIFY(flags) => libc::fcntl(fd, libc::F_NOTIFY, flags.bits() as c_int),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            F_SETLEASE(lease) => libc::fcntl(fd, libc::F_SETLEASE, lease as c_int),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            F_GETLEASE => libc::fcntl(fd, libc::F_GETLEASE),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            F_SET_RW_HINT(hint) => libc::fcntl(fd, consts::F_SET_RW_HINT, &(hint as u64)),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            F_GET_RW_HINT(hint) => {
+                let mut raw = 0u64;
+                let res = libc::fcntl(fd, consts::F_GET_RW_HINT, &mut raw);
+                if res != -1 {
+                    *hint = RwHint::from_u64(raw)?;
+                }
+                res
+            },
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            F_DUPFD_QUERY(rawfd) => libc::fcntl(fd, consts::F_DUPFD_QUERY, rawfd),
         }
     };
 
     Errno::result(res)
 }
 
+/// Get the file descriptor flags (`F_GETFD`).
+pub fn get_fd_flags(fd: RawFd) -> Result<FdFlag> {
+    fcntl(fd, F_GETFD).map(FdFlag::from_bits_truncate)
+}
+
+/// Get the file access mode and the file status flags (`F_GETFL`).
+pub fn get_status_flags(fd: RawFd) -> Result<OFlag> {
+    fcntl(fd, F_GETFL).map(OFlag::from_bits_truncate)
+}
+
+/// Get the seals of a file created with `memfd_create` (`F_GET_SEALS`).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn get_seals(fd: RawFd) -> Result<SealFlag> {
+    fcntl(fd, F_GET_SEALS).map(SealFlag::from_bits_truncate)
+}
+
+/// Get the owner of a file descriptor (`F_GETOWN_EX`).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn get_owner_ex(fd: RawFd) -> Result<FdOwner> {
+    let mut owner = FdOwner::Process(Pid::from_raw(0));
+    fcntl(fd, F_GETOWN_EX(&mut owner))?;
+    Ok(owner)
+}
+
+/// Get the signal sent when I/O is possible on a file descriptor (`F_GETSIG`).
+///
+/// Returns `None` if the default of `SIGIO` is used.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn get_sig(fd: RawFd) -> Result<Option<SigNum>> {
+    match fcntl(fd, F_GETSIG)? {
+        0 => Ok(None),
+        signum => SigNum::from_c_int(signum).map(Some),
+    }
+}
+
+/// Get the kind of lease held on a file (`F_GETLEASE`).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn get_lease(fd: RawFd) -> Result<LeaseType> {
+    fcntl(fd, F_GETLEASE).and_then(LeaseType::from_c_int)
+}
+
+/// Get the write lifetime hint of the inode of a file (`F_GET_RW_HINT`).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn get_rw_hint(fd: RawFd) -> Result<RwHint> {
+    let mut hint = RwHint::NotSet;
+    fcntl(fd, F_GET_RW_HINT(&mut hint))?;
+    Ok(hint)
+}
+
+/// Check whether two file descriptors refer to the same open file description
+/// (`F_DUPFD_QUERY`).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn dupfd_query(fd: RawFd, other: RawFd) -> Result<bool> {
+    fcntl(fd, F_DUPFD_QUERY(other)).map(|res| res == 1)
+}
+
+/// The kind of a `RecordLock`.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LockType {
+    /// A shared lock. Any number of processes may hold a read lock on the same range.
+    Read = libc::F_RDLCK as i32,
+    /// An exclusive lock. Only one process may hold a write lock on a range.
+    Write = libc::F_WRLCK as i32,
+    /// Remove a lock.
+    Unlock = libc::F_UNLCK as i32,
+}
+
+impl LockType {
+    fn from_c_short(lock_type: libc::c_short) -> Result<LockType> {
+        match lock_type as c_int {
+            x if x == libc::F_RDLCK as c_int => Ok(LockType::Read),
+            x if x == libc::F_WRLCK as c_int => Ok(LockType::Write),
+            x if x == libc::F_UNLCK as c_int => Ok(LockType::Unlock),
+            _ => Err(Error::invalid_argument()),
+        }
+    }
+}
+
+/// A POSIX record lock on a byte range of a file.
+///
+/// The range starts at `start` bytes relative to `whence` and covers `len` bytes. A `len` of 0
+/// extends the range to the end of the file, however large the file grows, and a negative
+/// `len` covers the bytes before `start`.
+///
+/// Traditional locks (`set`, `set_wait`, `get`) are owned by the process. They are released
+/// when the process closes *any* file descriptor for the file and are not inherited by
+/// `fork`. Open file description locks (`set_ofd`, `set_ofd_wait`, `get_ofd`) are owned by the
+/// open file description instead, so they are shared by duplicated file descriptors and are
+/// only released when the last of them is closed.
+///
+/// For more information, see [the fcntl(2) man page.](http://man7.org/linux/man-pages/man2/fcntl.2.html)
+#[derive(Clone, Copy, Debug)]
+pub struct RecordLock {
+    lock_type: LockType,
+    whence: Whence,
+    start: libc::off_t,
+    len: libc::off_t,
+    pid: Option<Pid>,
+}
+
+impl RecordLock {
+    /// Describe a lock of the given type on a byte range.
+    pub fn new(lock_type: LockType, whence: Whence, start: libc::off_t, len: libc::off_t)
+        -> RecordLock
+    {
+        RecordLock { lock_type, whence, start, len, pid: None }
+    }
+
+    /// The type of the lock.
+    pub fn lock_type(&self) -> LockType {
+        self.lock_type
+    }
+
+    /// What `start` is relative to.
+    pub fn whence(&self) -> Whence {
+        self.whence
+    }
+
+    /// The start of the locked range.
+    pub fn start(&self) -> libc::off_t {
+        self.start
+    }
+
+    /// The length of the locked range.
+    pub fn length(&self) -> libc::off_t {
+        self.len
+    }
+
+    /// The process holding the lock.
+    ///
+    /// This is only set on locks returned by `get`. Open file description locks are not held
+    /// by any particular process, so it is always `None` for locks returned by `get_ofd`.
+    pub fn pid(&self) -> Option<Pid> {
+        self.pid
+    }
+
+    fn to_flock(self) -> libc::flock {
+        let mut flock: libc::flock = unsafe { mem::zeroed() };
+        flock.l_type = self.lock_type as libc::c_short;
+        flock.l_whence = self.whence as libc::c_short;
+        flock.l_start = self.start;
+        flock.l_len = self.len;
+        flock
+    }
+
+    fn from_flock(flock: &libc::flock) -> Result<RecordLock> {
+        let whence = match flock.l_whence as c_int {
+            libc::SEEK_SET => Whence::SeekSet,
+            libc::SEEK_CUR => Whence::SeekCur,
+            libc::SEEK_END => Whence::SeekEnd,
+            _ => return Err(Error::invalid_argument()),
+        };
+        Ok(RecordLock {
+            lock_type: LockType::from_c_short(flock.l_type)?,
+            whence,
+            start: flock.l_start,
+            len: flock.l_len,
+            pid: if flock.l_pid > 0 { Some(Pid::from_raw(flock.l_pid)) } else { None },
+        })
+    }
+
+    fn conflicting(flock: &libc::flock) -> Result<Option<RecordLock>> {
+        if flock.l_type as c_int == libc::F_UNLCK as c_int {
+            Ok(None)
+        } else {
+            RecordLock::from_flock(flock).map(Some)
+        }
+    }
+
+    /// Acquire or release the lock, failing with `EACCES` or `EAGAIN` if a conflicting lock is
+    /// held by another process (`F_SETLK`).
+    pub fn set(&self, fd: RawFd) -> Result<()> {
+        fcntl(fd, F_SETLK(&self.to_flock())).map(drop)
+    }
+
+    /// Acquire or release the lock, waiting for conflicting locks to be released (`F_SETLKW`).
+    pub fn set_wait(&self, fd: RawFd) -> Result<()> {
+        fcntl(fd, F_SETLKW(&self.to_flock())).map(drop)
+    }
+
+    /// Find a lock that would prevent this one from being acquired (`F_GETLK`).
+    ///
+    /// Returns `None` if the lock could be acquired.
+    pub fn get(&self, fd: RawFd) -> Result<Option<RecordLock>> {
+        let mut flock = self.to_flock();
+        fcntl(fd, F_GETLK(&mut flock))?;
+        RecordLock::conflicting(&flock)
+    }
+
+    /// Acquire or release the lock as an open file description lock, failing with `EAGAIN` if a
+    /// conflicting lock is held (`F_OFD_SETLK`).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_ofd(&self, fd: RawFd) -> Result<()> {
+        fcntl(fd, F_OFD_SETLK(&self.to_flock())).map(drop)
+    }
+
+    /// Acquire or release the lock as an open file description lock, waiting for conflicting
+    /// locks to be released (`F_OFD_SETLKW`).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_ofd_wait(&self, fd: RawFd) -> Result<()> {
+        fcntl(fd, F_OFD_SETLKW(&self.to_flock())).map(drop)
+    }
+
+    /// Find a lock that would prevent this one from being acquired as an open file description
+    /// lock (`F_OFD_GETLK`).
+    ///
+    /// Returns `None` if the lock could be acquired.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn get_ofd(&self, fd: RawFd) -> Result<Option<RecordLock>> {
+        let mut flock = self.to_flock();
+        fcntl(fd, F_OFD_GETLK(&mut flock))?;
+        RecordLock::conflicting(&flock)
+    }
+
+    // Makes `start` relative to the start of the file, so that the range doesn't move when the
+    // file offset or the file size changes.
+    fn to_seek_set(self, fd: RawFd) -> Result<RecordLock> {
+        let base = match self.whence {
+            Whence::SeekCur => lseek(fd, 0, Whence::SeekCur)?,
+            Whence::SeekEnd => fstat(fd)?.st_size as libc::off_t,
+            _ => return Ok(self),
+        };
+        let start = base.checked_add(self.start).ok_or(Error::Sys(Errno::EOVERFLOW))?;
+        Ok(RecordLock { whence: Whence::SeekSet, start, ..self })
+    }
+
+    fn guard(self, fd: RawFd, ofd: bool, wait: bool) -> Result<RecordLockGuard> {
+        if self.lock_type == LockType::Unlock {
+            return Err(Error::invalid_argument());
+        }
+        let lock = self.to_seek_set(fd)?;
+        let flock = lock.to_flock();
+        let arg = match (ofd, wait) {
+            (false, false) => F_SETLK(&flock),
+            (false, true) => F_SETLKW(&flock),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (true, false) => F_OFD_SETLK(&flock),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (true, true) => F_OFD_SETLKW(&flock),
+            #[cfg(not(any(target_os = "android", target_os = "linux")))]
+            (true, _) => unreachable!(),
+        };
+        fcntl(fd, arg)?;
+        Ok(RecordLockGuard { fd, lock, ofd })
+    }
+
+    /// Acquire the lock, waiting for conflicting locks to be released, and release it again
+    /// when the returned guard is dropped.
+    pub fn lock(self, fd: RawFd) -> Result<RecordLockGuard> {
+        self.guard(fd, false, true)
+    }
+
+    /// Acquire the lock if no conflicting lock is held, and release it again when the returned
+    /// guard is dropped.
+    pub fn try_lock(self, fd: RawFd) -> Result<RecordLockGuard> {
+        self.guard(fd, false, false)
+    }
+
+    /// Acquire the lock as an open file description lock, waiting for conflicting locks to be
+    /// released, and release it again when the returned guard is dropped.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn lock_ofd(self, fd: RawFd) -> Result<RecordLockGuard> {
+        self.guard(fd, true, true)
+    }
+
+    /// Acquire the lock as an open file description lock if no conflicting lock is held, and
+    /// release it again when the returned guard is dropped.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn try_lock_ofd(self, fd: RawFd) -> Result<RecordLockGuard> {
+        self.guard(fd, true, false)
+    }
+}
+
+/// A held `RecordLock`, which is released when the guard is dropped.
+///
+/// The guard does not own the file descriptor, which has to stay open for as long as the guard
+/// exists.
+#[derive(Debug)]
+pub struct RecordLockGuard {
+    fd: RawFd,
+    lock: RecordLock,
+    ofd: bool,
+}
+
+impl RecordLockGuard {
+    /// The lock held by this guard.
+    ///
+    /// A range relative to the file offset or the end of the file is resolved when the lock is
+    /// acquired, so its `whence` is always `SeekSet`.
+    pub fn lock(&self) -> &RecordLock {
+        &self.lock
+    }
+
+    fn release(&self) -> Result<()> {
+        let unlock = RecordLock { lock_type: LockType::Unlock, ..self.lock };
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            if self.ofd {
+                return unlock.set_ofd(self.fd);
+            }
+        }
+        unlock.set(self.fd)
+    }
+
+    /// Release the lock, reporting any error that occurs.
+    pub fn unlock(self) -> Result<()> {
+        let res = self.release();
+        mem::forget(self);
+        res
+    }
+}
+
+impl Drop for RecordLockGuard {
+    fn drop(&mut self) {
+        let _ = self.release();
+    }
+}
+
 #[derive(Clone, Copy)]
 #[allow(missing_debug_implementations)]
 pub enum FlockArg {
@@ -419,3 +972,259 @@ pub fn fallocate(fd: RawFd, mode: FallocateFlags, offset: libc::off_t, len: libc
     let res = unsafe { libc::fallocate(fd, mode.bits(), offset, len) };
     Errno::result(res)
 }
+
+/// Copy a range of data from one file to another, entirely inside the kernel.
+///
+/// Up to `len` bytes are copied from `fd_in` to `fd_out`. If `off_in` or `off_out` is `None`,
+/// the corresponding file offset is used and updated. If it is `Some`, the given offset is used
+/// instead and is updated to point past the copied data, while the file offset is left untouched.
+///
+/// Returns the number of bytes copied, which may be less than `len`. A return value of 0 means
+/// that `off_in` was at or past the end of the input file.
+///
+/// For more information, see [the copy_file_range(2) man page.](http://man7.org/linux/man-pages/man2/copy_file_range.2.html)
+#[cfg(target_os = "linux")]
+pub fn copy_file_range(fd_in: RawFd, off_in: Option<&mut libc::loff_t>,
+                       fd_out: RawFd, off_out: Option<&mut libc::loff_t>,
+                       len: usize) -> Result<usize> {
+    use std::ptr;
+    let off_in = off_in.map(|offset| offset as *mut _).unwrap_or(ptr::null_mut());
+    let off_out = off_out.map(|offset| offset as *mut _).unwrap_or(ptr::null_mut());
+
+    let ret = unsafe { libc::copy_file_range(fd_in, off_in, fd_out, off_out, len, 0) };
+    Errno::result(ret).map(|r| r as usize)
+}
+
+#[cfg(target_os = "linux")]
+mod ficlone {
+    // The structures and ioctls are defined in `linux/fs.h`.
+
+    #[repr(C)]
+    pub struct file_clone_range {
+        pub src_fd: i64,
+        pub src_offset: u64,
+        pub src_length: u64,
+        pub dest_offset: u64,
+    }
+
+    #[repr(C)]
+    pub struct file_dedupe_range {
+        pub src_offset: u64,
+        pub src_length: u64,
+        pub dest_count: u16,
+        pub reserved1: u16,
+        pub reserved2: u32,
+        // Followed by `dest_count` instances of `file_dedupe_range_info`.
+    }
+
+    #[repr(C)]
+    pub struct file_dedupe_range_info {
+        pub dest_fd: i64,
+        pub dest_offset: u64,
+        pub bytes_deduped: u64,
+        pub status: i32,
+        pub reserved: u32,
+    }
+
+    pub const FILE_DEDUPE_RANGE_SAME: i32 = 0;
+    pub const FILE_DEDUPE_RANGE_DIFFERS: i32 = 1;
+
+    const FS_IOC_MAGIC: u8 = 0x94;
+
+    ioctl_write_int!(ficlone, FS_IOC_MAGIC, 9);
+    ioctl_write_ptr!(ficlonerange, FS_IOC_MAGIC, 13, file_clone_range);
+    ioctl_readwrite!(fideduperange, FS_IOC_MAGIC, 54, file_dedupe_range);
+}
+
+/// Make `dest_fd` share all of the data of `src_fd` (`FICLONE`).
+///
+/// This creates a reflink: both files refer to the same extents on disk until one of them is
+/// modified. Any previous content of `dest_fd` is replaced. Both files must live on the same
+/// filesystem and that filesystem must support reflinks (e.g. btrfs, XFS), otherwise this
+/// fails with `EXDEV` or `EOPNOTSUPP`.
+///
+/// For more information, see [the ioctl_ficlone(2) man page.](http://man7.org/linux/man-pages/man2/ioctl_ficlone.2.html)
+#[cfg(target_os = "linux")]
+pub fn ficlone(dest_fd: RawFd, src_fd: RawFd) -> Result<()> {
+    unsafe { ficlone::ficlone(dest_fd, src_fd as ::sys::ioctl::ioctl_param_type) }.map(drop)
+}
+
+/// Make a range of `dest_fd` share the data of a range of `src_fd` (`FICLONERANGE`).
+///
+/// `src_length` bytes starting at `src_offset` in `src_fd` are reflinked into `dest_fd` at
+/// `dest_offset`. A `src_length` of 0 clones everything up to the end of `src_fd`. Offsets and
+/// length usually have to be aligned to the filesystem block size, except for a range that
+/// ends at the end of the source file.
+///
+/// For more information, see [the ioctl_ficlonerange(2) man page.](http://man7.org/linux/man-pages/man2/ioctl_ficlonerange.2.html)
+#[cfg(target_os = "linux")]
+pub fn ficlonerange(dest_fd: RawFd, src_fd: RawFd, src_offset: u64, src_length: u64,
+                    dest_offset: u64) -> Result<()> {
+    let range = ficlone::file_clone_range {
+        src_fd: src_fd as i64,
+        src_offset,
+        src_length,
+        dest_offset,
+    };
+    unsafe { ficlone::ficlonerange(dest_fd, &range) }.map(drop)
+}
+
+/// The outcome of deduplicating a range into one destination with `fideduperange`.
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DedupeResult {
+    /// The data was identical and this many bytes now share storage with the source.
+    Same(u64),
+    /// The data differs from the source, so nothing was deduplicated.
+    Differs,
+    /// Deduplicating into this destination failed.
+    Failed(Errno),
+}
+
+/// Share identical data between `src_fd` and a list of destinations (`FIDEDUPERANGE`).
+///
+/// `src_length` bytes starting at `src_offset` in `src_fd` are compared with the same amount
+/// of data at each `(fd, offset)` in `dests`. Wherever the contents are identical the kernel
+/// makes the destination share the storage of the source. Unlike `ficlonerange` the contents
+/// of the destinations never change.
+///
+/// Returns one `DedupeResult` per destination, in the order of `dests`. An `Err` is only
+/// returned if the request as a whole was rejected.
+///
+/// For more information, see [the ioctl_fideduperange(2) man page.](http://man7.org/linux/man-pages/man2/ioctl_fideduperange.2.html)
+#[cfg(target_os = "linux")]
+pub fn fideduperange(src_fd: RawFd, src_offset: u64, src_length: u64,
+                     dests: &[(RawFd, u64)]) -> Result<Vec<DedupeResult>> {
+    use std::mem;
+    use self::ficlone::{file_dedupe_range, file_dedupe_range_info};
+
+    if dests.len() > u16::max_value() as usize {
+        return Err(Error::invalid_argument());
+    }
+
+    // The request is a header immediately followed by an array of per-destination records.
+    // Allocate it as `u64`s so that both parts are suitably aligned.
+    let header_size = mem::size_of::<file_dedupe_range>();
+    let info_size = mem::size_of::<file_dedupe_range_info>();
+    let size = header_size + dests.len() * info_size;
+    let mut buf = vec![0u64; (size + 7) / 8];
+    let header = buf.as_mut_ptr() as *mut file_dedupe_range;
+    let infos = unsafe {
+        (header as *mut u8).offset(header_size as isize) as *mut file_dedupe_range_info
+    };
+
+    unsafe {
+        (*header).src_offset = src_offset;
+        (*header).src_length = src_length;
+        (*header).dest_count = dests.len() as u16;
+        for (i, &(fd, offset)) in dests.iter().enumerate() {
+            let info = &mut *infos.offset(i as isize);
+            info.dest_fd = fd as i64;
+            info.dest_offset = offset;
+        }
+
+        ficlone::fideduperange(src_fd, header)?;
+    }
+
+    let results = (0..dests.len()).map(|i| {
+        let info = unsafe { &*infos.offset(i as isize) };
+        match info.status {
+            ficlone::FILE_DEDUPE_RANGE_SAME => DedupeResult::Same(info.bytes_deduped),
+            ficlone::FILE_DEDUPE_RANGE_DIFFERS => DedupeResult::Differs,
+            errno => DedupeResult::Failed(Errno::from_i32(-errno)),
+        }
+    }).collect();
+    Ok(results)
+}
+
+/// Copy everything from `fd_in` to `fd_out`, using the fastest mechanism that works.
+///
+/// Data is read from the current offset of `fd_in` until its end and written at the current
+/// offset of `fd_out`. Both offsets are advanced past the copied data. The following methods
+/// are tried in order, falling back to the next one if the kernel or filesystem does not
+/// support it for these two file descriptors:
+///
+/// 1. a reflink with `ficlonerange`, which shares the data instead of copying it,
+/// 2. `copy_file_range`, which copies inside the kernel and may be offloaded by the filesystem,
+/// 3. `sendfile`, which copies inside the kernel through the page cache,
+/// 4. a plain `read`/`write` loop.
+///
+/// Returns the number of bytes copied.
+#[cfg(target_os = "linux")]
+pub fn copy_fd_to_fd(fd_in: RawFd, fd_out: RawFd) -> Result<u64> {
+    use sys::sendfile::sendfile;
+    use sys::stat::{fstat, SFlag};
+    use unistd::{lseek64, read, write, Whence};
+
+    // The largest amount of data Linux transfers in a single call.
+    const MAX_CHUNK: usize = 0x7fff_f000;
+
+    // Whether an error just means that a method can't be used for these file descriptors.
+    fn unsupported(err: Error) -> bool {
+        match err {
+            Error::Sys(Errno::EINVAL) | Error::Sys(Errno::ENOSYS) |
+            Error::Sys(Errno::EOPNOTSUPP) | Error::Sys(Errno::ENOTTY) |
+            Error::Sys(Errno::EXDEV) | Error::Sys(Errno::EBADF) |
+            Error::Sys(Errno::ETXTBSY) | Error::Sys(Errno::EISDIR) => true,
+            _ => false,
+        }
+    }
+
+    let mut copied = 0u64;
+
+    // Reflinks only make sense between seekable regular files.
+    let offsets = (lseek64(fd_in, 0, Whence::SeekCur), lseek64(fd_out, 0, Whence::SeekCur));
+    if let (Ok(off_in), Ok(off_out)) = offsets {
+        let stat = fstat(fd_in)?;
+        let is_reg = SFlag::from_bits_truncate(stat.st_mode) & SFlag::S_IFMT == SFlag::S_IFREG;
+        if is_reg && stat.st_size > off_in {
+            match ficlonerange(fd_out, fd_in, off_in as u64, 0, off_out as u64) {
+                Ok(()) => {
+                    let len = stat.st_size - off_in;
+                    lseek64(fd_in, len, Whence::SeekCur)?;
+                    lseek64(fd_out, len, Whence::SeekCur)?;
+                    return Ok(len as u64);
+                },
+                Err(err) if unsupported(err) => (),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    loop {
+        match copy_file_range(fd_in, None, fd_out, None, MAX_CHUNK) {
+            Ok(0) => return Ok(copied),
+            Ok(n) => copied += n as u64,
+            Err(err) if unsupported(err) => break,
+            Err(err) => return Err(err),
+        }
+    }
+
+    loop {
+        match sendfile(fd_out, fd_in, None, MAX_CHUNK) {
+            Ok(0) => return Ok(copied),
+            Ok(n) => copied += n as u64,
+            Err(err) if unsupported(err) => break,
+            Err(err) => return Err(err),
+        }
+    }
+
+    let mut buf = vec![0u8; 128 * 1024];
+    loop {
+        let n = match read(fd_in, &mut buf) {
+            Ok(0) => return Ok(copied),
+            Ok(n) => n,
+            Err(Error::Sys(Errno::EINTR)) => continue,
+            Err(err) => return Err(err),
+        };
+        let mut written = 0;
+        while written < n {
+            match write(fd_out, &buf[written..n]) {
+                Ok(m) => written += m,
+                Err(Error::Sys(Errno::EINTR)) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        copied += n as u64;
+    }
+}
@@ -3,6 +3,8 @@ pub use self::os::*;
 
 #[cfg(any(target_os = "linux", target_os = "android"))]
 mod os {
+    use std::{cmp, fmt};
+    use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
     use sys::utsname::uname;
 
     // Features:
@@ -10,87 +12,272 @@ mod os {
     // * pipe2: 2.6.27
     // * accept4: 2.6.28
 
-    static VERS_UNKNOWN: usize = 1;
-    static VERS_2_6_18:  usize = 2;
-    static VERS_2_6_27:  usize = 3;
-    static VERS_2_6_28:  usize = 4;
-    static VERS_3:       usize = 5;
-
-    #[inline]
-    fn digit(dst: &mut usize, b: u8) {
-        *dst *= 10;
-        *dst += (b - b'0') as usize;
+    /// A Linux kernel version, as reported by
+    /// [uname(2)](http://man7.org/linux/man-pages/man2/uname.2.html).
+    ///
+    /// Versions compare by `major`, then `minor`, then `patch`.  Vendor
+    /// kernels backport features, so prefer [`is_supported`](fn.is_supported.html)
+    /// where a probe exists.
+    #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    pub struct KernelVersion {
+        /// The major version, `4` in `4.19.0`
+        pub major: u32,
+        /// The minor version, `19` in `4.19.0`
+        pub minor: u32,
+        /// The patch level, `0` in `4.19.0`
+        pub patch: u32,
     }
 
-    fn parse_kernel_version() -> usize {
-        let u = uname();
-
-        let mut curr:  usize = 0;
-        let mut major: usize = 0;
-        let mut minor: usize = 0;
-        let mut patch: usize = 0;
+    impl KernelVersion {
+        /// Creates a version from its components.
+        pub fn new(major: u32, minor: u32, patch: u32) -> KernelVersion {
+            KernelVersion { major, minor, patch }
+        }
 
-        for b in u.release().bytes() {
-            if curr >= 3 {
-                break;
-            }
+        /// Parses the leading `major.minor.patch` of a release string such as
+        /// `4.19.0-18-amd64`.  Missing components are 0.  Returns `None` if
+        /// the string does not start with a number.
+        pub fn parse(release: &str) -> Option<KernelVersion> {
+            let mut parts = [0u32; 3];
+            let mut curr = 0;
+            let mut seen_digit = false;
 
-            match b {
-                b'.' | b'-' => {
-                    curr += 1;
-                }
-                b'0'...b'9' => {
-                    match curr {
-                        0 => digit(&mut major, b),
-                        1 => digit(&mut minor, b),
-                        _ => digit(&mut patch, b),
+            for b in release.bytes() {
+                match b {
+                    b'0'...b'9' => {
+                        let part = &mut parts[curr];
+                        *part = part.saturating_mul(10).saturating_add(u32::from(b - b'0'));
+                        seen_digit = true;
                     }
+                    b'.' if seen_digit && curr < 2 => curr += 1,
+                    _ => break,
                 }
-                _ => break,
             }
-        }
 
-        if major >= 3 {
-            VERS_3
-        } else if major >= 2 {
-            if minor >= 7 {
-                VERS_UNKNOWN
-            } else if minor >= 6 {
-                if patch >= 28 {
-                    VERS_2_6_28
-                } else if patch >= 27 {
-                    VERS_2_6_27
-                } else {
-                    VERS_2_6_18
-                }
-            } else {
-                VERS_UNKNOWN
+            if !seen_digit {
+                return None;
             }
-        } else {
-            VERS_UNKNOWN
+            Some(KernelVersion::new(parts[0], parts[1], parts[2]))
         }
-    }
 
-    fn kernel_version() -> usize {
-        static mut KERNEL_VERS: usize = 0;
+        /// The version of the running kernel.  It is read once and cached.
+        ///
+        /// An unparsable release string yields `0.0.0`, so that version
+        /// checks fail safe.  Components are clamped to 127, 255 and 65535
+        /// respectively.
+        pub fn current() -> KernelVersion {
+            // The version is packed as `CACHED | major << 24 | minor << 16 |
+            // patch`, which fits a 32-bit `usize`.  0 means not read yet.
+            const CACHED: usize = 1 << 31;
+            static CURRENT: AtomicUsize = ATOMIC_USIZE_INIT;
 
-        unsafe {
-            if KERNEL_VERS == 0 {
-                KERNEL_VERS = parse_kernel_version();
+            let mut packed = CURRENT.load(Ordering::Relaxed);
+            if packed == 0 {
+                let vers = KernelVersion::parse(uname().release())
+                    .unwrap_or_else(|| KernelVersion::new(0, 0, 0));
+                packed = CACHED
+                    | (cmp::min(vers.major, 0x7f) as usize) << 24
+                    | (cmp::min(vers.minor, 0xff) as usize) << 16
+                    | cmp::min(vers.patch, 0xffff) as usize;
+                CURRENT.store(packed, Ordering::Relaxed);
             }
 
-            KERNEL_VERS
+            KernelVersion::new(((packed >> 24) & 0x7f) as u32,
+                               ((packed >> 16) & 0xff) as u32,
+                               (packed & 0xffff) as u32)
+        }
+    }
+
+    impl fmt::Display for KernelVersion {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
         }
     }
 
     /// Check if the OS supports atomic close-on-exec for sockets
     pub fn socket_atomic_cloexec() -> bool {
-        kernel_version() >= VERS_2_6_27
+        KernelVersion::current() >= KernelVersion::new(2, 6, 27)
+    }
+
+    /// Check if the OS supports `pipe2(2)`
+    pub fn pipe_atomic_cloexec() -> bool {
+        KernelVersion::current() >= KernelVersion::new(2, 6, 27)
+    }
+
+    /// Check if the OS supports `accept4(2)`
+    pub fn accept_atomic_cloexec() -> bool {
+        KernelVersion::current() >= KernelVersion::new(2, 6, 28)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub use self::probe::*;
+
+    #[cfg(target_os = "linux")]
+    mod probe {
+        use errno::Errno;
+        use libc::{self, c_long, c_uint};
+        use std::ptr;
+        use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+        use unistd::close;
+
+        /// Kernel interfaces that can be detected at runtime with
+        /// [`is_supported`](fn.is_supported.html).
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub enum Feature {
+            /// [openat2(2)](http://man7.org/linux/man-pages/man2/openat2.2.html),
+            /// Linux 5.6
+            Openat2,
+            /// [clone3(2)](http://man7.org/linux/man-pages/man2/clone3.2.html),
+            /// Linux 5.3
+            Clone3,
+            /// [pidfd_open(2)](http://man7.org/linux/man-pages/man2/pidfd_open.2.html),
+            /// Linux 5.3
+            PidfdOpen,
+            /// [statx(2)](http://man7.org/linux/man-pages/man2/statx.2.html),
+            /// Linux 4.11
+            Statx,
+            /// [memfd_create(2)](http://man7.org/linux/man-pages/man2/memfd_create.2.html),
+            /// Linux 3.17
+            MemfdCreate,
+            /// The `MFD_NOEXEC_SEAL` and `MFD_EXEC` flags of `memfd_create`,
+            /// Linux 6.3
+            MemfdNoexecSeal,
+            /// The file descriptor based mount API (`fsopen`, `fsmount`,
+            /// `open_tree`, `move_mount`, ...), Linux 5.2
+            MountApi,
+            /// The [Landlock](http://man7.org/linux/man-pages/man7/landlock.7.html)
+            /// LSM, Linux 5.13.  It also has to be enabled at boot.
+            Landlock,
+        }
+
+        // Two bits per feature: whether it was probed, and the result.
+        static PROBED: AtomicUsize = ATOMIC_USIZE_INIT;
+
+        /// Check if the running kernel supports `feature`.
+        ///
+        /// Syscalls are probed by issuing them with invalid arguments: any
+        /// error other than `ENOSYS` means the kernel knows the syscall.
+        /// `EPERM` also counts as unsupported, since seccomp filters in
+        /// container runtimes commonly use it to block syscalls they do not
+        /// know.  The result is cached.
+        pub fn is_supported(feature: Feature) -> bool {
+            let probed_bit = 1 << (2 * feature as usize);
+            let supported_bit = probed_bit << 1;
+
+            let cache = PROBED.load(Ordering::Relaxed);
+            if cache & probed_bit != 0 {
+                return cache & supported_bit != 0;
+            }
+
+            let supported = probe(feature);
+            let bits = if supported { probed_bit | supported_bit } else { probed_bit };
+            PROBED.fetch_or(bits, Ordering::Relaxed);
+            supported
+        }
+
+        fn probe(feature: Feature) -> bool {
+            let null = ptr::null::<libc::c_void>();
+            match feature {
+                Feature::Openat2 => syscall_known(unsafe {
+                    // A zero sized open_how fails with EINVAL
+                    libc::syscall(libc::SYS_openat2, -1, null, null, 0)
+                }),
+                Feature::Clone3 => syscall_known(unsafe {
+                    // A zero sized clone_args fails with EINVAL
+                    libc::syscall(libc::SYS_clone3, null, 0)
+                }),
+                Feature::PidfdOpen => syscall_known(unsafe {
+                    libc::syscall(libc::SYS_pidfd_open, 0, 0)
+                }),
+                Feature::Statx => syscall_known(unsafe {
+                    libc::syscall(libc::SYS_statx, -1, null, -1, 0, null)
+                }),
+                Feature::MemfdCreate => syscall_known(unsafe {
+                    libc::syscall(libc::SYS_memfd_create, null, 0)
+                }),
+                Feature::MemfdNoexecSeal => {
+                    // Older kernels reject unknown flags with EINVAL
+                    let flags: c_uint = libc::MFD_CLOEXEC | libc::MFD_NOEXEC_SEAL;
+                    let res = unsafe {
+                        libc::syscall(libc::SYS_memfd_create, b"nix-probe\0".as_ptr(), flags)
+                    };
+                    owned_fd_created(res)
+                },
+                Feature::MountApi => syscall_known(unsafe {
+                    // Unknown flags are rejected before any permission check
+                    libc::syscall(libc::SYS_open_tree, -1, null, !0 as c_uint)
+                }),
+                Feature::Landlock => {
+                    const LANDLOCK_CREATE_RULESET_VERSION: c_uint = 1;
+                    // Returns the ABI version, or EOPNOTSUPP if disabled at boot
+                    let res = unsafe {
+                        libc::syscall(libc::SYS_landlock_create_ruleset, null, 0,
+                                      LANDLOCK_CREATE_RULESET_VERSION)
+                    };
+                    res >= 0
+                },
+            }
+        }
+
+        fn syscall_known(res: c_long) -> bool {
+            if res >= 0 {
+                return true;
+            }
+            let errno = Errno::last();
+            errno != Errno::ENOSYS && errno != Errno::EPERM
+        }
+
+        fn owned_fd_created(res: c_long) -> bool {
+            if res < 0 {
+                return false;
+            }
+            let _ = close(res as i32);
+            true
+        }
+
+        #[test]
+        fn test_probes_are_cached() {
+            let features = [Feature::Openat2, Feature::Clone3, Feature::PidfdOpen,
+                            Feature::Statx, Feature::MemfdCreate, Feature::MemfdNoexecSeal,
+                            Feature::MountApi, Feature::Landlock];
+            for &feature in &features {
+                assert_eq!(is_supported(feature), probe(feature));
+                assert_eq!(is_supported(feature), probe(feature));
+            }
+        }
     }
 
     #[test]
     pub fn test_parsing_kernel_version() {
-        assert!(kernel_version() > 0);
+        assert!(KernelVersion::current() > KernelVersion::new(0, 0, 0));
+    }
+
+    #[test]
+    fn test_parse_release_strings() {
+        let parse = KernelVersion::parse;
+        assert_eq!(parse("4.19.0-18-amd64"), Some(KernelVersion::new(4, 19, 0)));
+        assert_eq!(parse("2.6.32.71-grsec"), Some(KernelVersion::new(2, 6, 32)));
+        assert_eq!(parse("6.18.44-fc-v139"), Some(KernelVersion::new(6, 18, 44)));
+        assert_eq!(parse("5.10"), Some(KernelVersion::new(5, 10, 0)));
+        assert_eq!(parse("3.10.0+"), Some(KernelVersion::new(3, 10, 0)));
+        assert_eq!(parse("rc1"), None);
+        assert_eq!(parse(""), None);
+
+        assert!(KernelVersion::new(2, 6, 28) > KernelVersion::new(2, 6, 27));
+        assert!(KernelVersion::new(3, 0, 0) > KernelVersion::new(2, 6, 39));
+        assert!(KernelVersion::new(4, 9, 337) < KernelVersion::new(4, 10, 0));
+        assert_eq!(KernelVersion::new(5, 4, 1).to_string(), "5.4.1");
+    }
+
+    #[test]
+    fn test_kernel_version_cached_across_threads() {
+        use std::thread;
+
+        let vers = KernelVersion::current();
+        let threads: Vec<_> = (0..8).map(|_| thread::spawn(KernelVersion::current)).collect();
+        for t in threads {
+            assert_eq!(t.join().unwrap(), vers);
+        }
     }
 }
 
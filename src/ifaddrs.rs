@@ -1,18 +1,24 @@
 //! Query network interface addresses
 //!
 //! Uses the Linux and/or BSD specific function `getifaddrs` to query the list
-//! of interfaces and their associated addresses.
+//! of interfaces and their associated addresses.  On Linux, `interfaces` and
+//! `interfaces_in_netns` query rtnetlink instead and also report the index,
+//! MTU, hardware address and counters of every interface.
 
 use std::ffi;
 use std::iter::Iterator;
 use std::mem;
 use std::option::Option;
+#[cfg(target_os = "linux")]
+use std::os::unix::io::AsRawFd;
 
 use libc;
 
 use {Result, Errno};
 use sys::socket::SockAddr;
 use net::if_::*;
+#[cfg(target_os = "linux")]
+use net::rtnetlink::{Interface, RtNetlink};
 
 /// Describes a single address for an interface as returned by `getifaddrs`.
 #[derive(Clone, Eq, Hash, PartialEq, Debug)]
@@ -134,6 +140,41 @@ pub fn getifaddrs() -> Result<InterfaceAddressIterator> {
     })
 }
 
+/// Lists the network interfaces of the current network namespace with their
+/// addresses, using rtnetlink.
+///
+/// Unlike `getifaddrs`, every interface is reported exactly once, with its
+/// index, MTU, hardware address and traffic counters, and each address
+/// carries its prefix length and scope.
+///
+/// # Example
+/// ```
+/// use nix::ifaddrs::interfaces;
+///
+/// for interface in interfaces().unwrap() {
+///     println!("{}: {} (mtu {:?})", interface.link.index, interface.link.name,
+///              interface.link.mtu);
+///     for address in interface.addresses {
+///         println!("    {}/{}", address.address, address.prefix_len);
+///     }
+/// }
+/// ```
+#[cfg(target_os = "linux")]
+pub fn interfaces() -> Result<Vec<Interface>> {
+    RtNetlink::new()?.interfaces()
+}
+
+/// Lists the network interfaces of the network namespace referred to by
+/// `netns`, such as an open `/proc/<pid>/ns/net`.
+///
+/// The calling thread only enters the namespace long enough to open a
+/// netlink socket there; see
+/// [`RtNetlink::in_netns`](../net/rtnetlink/struct.RtNetlink.html#method.in_netns).
+#[cfg(target_os = "linux")]
+pub fn interfaces_in_netns<F: AsRawFd>(netns: &F) -> Result<Vec<Interface>> {
+    RtNetlink::in_netns(netns)?.interfaces()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
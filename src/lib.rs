@@ -28,6 +28,11 @@ pub extern crate libc;
 // Private internal modules
 #[macro_use] mod macros;
 
+// `sys` is declared ahead of the other modules so that the `ioctl_*!` macros
+// are usable from all of them.
+#[macro_use]
+pub mod sys;
+
 pub mod dirent;
 
 // Public crates
@@ -60,7 +65,6 @@ pub mod poll;
 pub mod pty;
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub mod sched;
-pub mod sys;
 // This can be implemented for other platforms as soon as libc
 // provides bindings for them.
 #[cfg(all(target_os = "linux",
@@ -256,9 +256,10 @@ macro_rules! libc_enum {
 }
 
 /// A Rust version of the familiar C `offset_of` macro.  It returns the byte
-/// offset of `field` within struct `ty`
+/// offset of `field` within struct `ty`, which must be valid when zeroed.
 macro_rules! offset_of {
-    ($ty:ty, $field:ident) => {
-        &(*(0 as *const $ty)).$field as *const _ as usize
-    }
+    ($ty:ty, $field:ident) => {{
+        let base: $ty = ::std::mem::zeroed();
+        &base.$field as *const _ as usize - &base as *const $ty as usize
+    }}
 }
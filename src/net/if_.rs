@@ -1,11 +1,21 @@
 //! Network interface name resolution.
 //!
 //! Uses Linux and/or POSIX functions to resolve interface names like "eth0"
-//! or "socan1" into device numbers.
+//! or "socan1" into device numbers, and on Linux and Android to query and
+//! configure interfaces with the `ifreq` ioctls of
+//! [`netdevice(7)`](http://man7.org/linux/man-pages/man7/netdevice.7.html).
 
 use libc;
 use libc::c_uint;
 use {Result, Error, NixPath};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::{ffi, mem};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::os::unix::io::RawFd;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use errno::Errno;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use sys::socket::SockAddr;
 
 /// Resolve an interface into a interface number.
 pub fn if_nametoindex<P: ?Sized + NixPath>(name: &P) -> Result<c_uint> {
@@ -266,3 +276,197 @@ libc_bitflags!(
         IFF_IPMP;
     }
 );
+
+/// Resolve an interface number into its name.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn if_indextoname(index: c_uint) -> Result<String> {
+    let mut buf = [0 as libc::c_char; libc::IFNAMSIZ];
+    let res = unsafe { libc::if_indextoname(index, buf.as_mut_ptr()) };
+    if res.is_null() {
+        return Err(Error::last());
+    }
+    Ok(unsafe { ffi::CStr::from_ptr(buf.as_ptr()) }.to_string_lossy().into_owned())
+}
+
+/// List the numbers and names of all interfaces.
+///
+/// See also [`if_nameindex(3)`](http://man7.org/linux/man-pages/man3/if_nameindex.3.html).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn if_nameindex() -> Result<Vec<(c_uint, String)>> {
+    let base = unsafe { libc::if_nameindex() };
+    if base.is_null() {
+        return Err(Error::last());
+    }
+    let mut interfaces = Vec::new();
+    let mut next = base;
+    unsafe {
+        while (*next).if_index != 0 {
+            let name = ffi::CStr::from_ptr((*next).if_name);
+            interfaces.push(((*next).if_index, name.to_string_lossy().into_owned()));
+            next = next.offset(1);
+        }
+        libc::if_freenameindex(base);
+    }
+    Ok(interfaces)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod ifreq {
+    use libc::{ifreq, SIOCGIFADDR, SIOCGIFFLAGS, SIOCGIFHWADDR, SIOCGIFINDEX, SIOCGIFMTU,
+               SIOCSIFFLAGS, SIOCSIFMTU, SIOCSIFNAME};
+
+    ioctl_read_bad!(siocgifaddr, SIOCGIFADDR, ifreq);
+    ioctl_read_bad!(siocgifflags, SIOCGIFFLAGS, ifreq);
+    ioctl_write_ptr_bad!(siocsifflags, SIOCSIFFLAGS, ifreq);
+    ioctl_read_bad!(siocgifmtu, SIOCGIFMTU, ifreq);
+    ioctl_write_ptr_bad!(siocsifmtu, SIOCSIFMTU, ifreq);
+    ioctl_write_ptr_bad!(siocsifname, SIOCSIFNAME, ifreq);
+    ioctl_read_bad!(siocgifhwaddr, SIOCGIFHWADDR, ifreq);
+    ioctl_read_bad!(siocgifindex, SIOCGIFINDEX, ifreq);
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn copy_ifname<P: ?Sized + NixPath>(name: &P, dst: &mut [libc::c_char; libc::IFNAMSIZ])
+    -> Result<()>
+{
+    name.with_nix_path(|name| {
+        let bytes = name.to_bytes();
+        if bytes.len() >= libc::IFNAMSIZ {
+            return Err(Error::Sys(Errno::ENAMETOOLONG));
+        }
+        for (dst, &src) in dst.iter_mut().zip(bytes) {
+            *dst = src as libc::c_char;
+        }
+        Ok(())
+    })?
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn ifreq_for<P: ?Sized + NixPath>(name: &P) -> Result<libc::ifreq> {
+    let mut req: libc::ifreq = unsafe { mem::zeroed() };
+    copy_ifname(name, &mut req.ifr_name)?;
+    Ok(req)
+}
+
+/// A hardware address, as returned by [`if_hwaddr`](fn.if_hwaddr.html).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct HardwareAddress {
+    /// The ARP hardware type of the interface, such as `ARPHRD_ETHER`.
+    pub hw_type: u16,
+    /// The address, padded with zeros.  Ethernet addresses use the first six
+    /// bytes.
+    pub address: [u8; 14],
+}
+
+/// Get the flags of the interface `name` (`SIOCGIFFLAGS`).
+///
+/// `fd` may be any socket, such as an `AF_INET` datagram socket.  The
+/// interfaces of the network namespace the socket was created in are used.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn if_flags<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<InterfaceFlags> {
+    let mut req = ifreq_for(name)?;
+    let flags = unsafe {
+        ifreq::siocgifflags(fd, &mut req)?;
+        req.ifr_ifru.ifru_flags
+    };
+    Ok(InterfaceFlags::from_bits_truncate(flags as libc::c_int & 0xffff))
+}
+
+/// Set the flags of the interface `name` (`SIOCSIFFLAGS`).
+///
+/// Only the lower 16 bits of the flags can be changed this way.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn if_set_flags<P: ?Sized + NixPath>(fd: RawFd, name: &P, flags: InterfaceFlags) -> Result<()> {
+    let mut req = ifreq_for(name)?;
+    req.ifr_ifru.ifru_flags = flags.bits() as libc::c_short;
+    unsafe { ifreq::siocsifflags(fd, &req) }.map(drop)
+}
+
+/// Bring the interface `name` up or down, leaving its other flags alone.
+///
+/// # Examples
+///
+/// ```no_run
+/// use nix::net::if_::if_set_up;
+/// use nix::sys::socket::{socket, AddressFamily, SockFlag, SockType};
+///
+/// let sock = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::SOCK_CLOEXEC, None)
+///     .unwrap();
+/// if_set_up(sock, "lo", true).unwrap();
+/// ```
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn if_set_up<P: ?Sized + NixPath>(fd: RawFd, name: &P, up: bool) -> Result<()> {
+    let mut flags = if_flags(fd, name)?;
+    flags.set(InterfaceFlags::IFF_UP, up);
+    if_set_flags(fd, name, flags)
+}
+
+/// Get the maximum transmission unit of the interface `name` (`SIOCGIFMTU`).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn if_mtu<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<u32> {
+    let mut req = ifreq_for(name)?;
+    let mtu = unsafe {
+        ifreq::siocgifmtu(fd, &mut req)?;
+        req.ifr_ifru.ifru_mtu
+    };
+    Ok(mtu as u32)
+}
+
+/// Set the maximum transmission unit of the interface `name` (`SIOCSIFMTU`).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn if_set_mtu<P: ?Sized + NixPath>(fd: RawFd, name: &P, mtu: u32) -> Result<()> {
+    let mut req = ifreq_for(name)?;
+    req.ifr_ifru.ifru_mtu = mtu as libc::c_int;
+    unsafe { ifreq::siocsifmtu(fd, &req) }.map(drop)
+}
+
+/// Get the hardware address of the interface `name` (`SIOCGIFHWADDR`).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn if_hwaddr<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<HardwareAddress> {
+    let mut req = ifreq_for(name)?;
+    let sa = unsafe {
+        ifreq::siocgifhwaddr(fd, &mut req)?;
+        req.ifr_ifru.ifru_hwaddr
+    };
+    let mut address = [0u8; 14];
+    for (dst, &src) in address.iter_mut().zip(sa.sa_data.iter()) {
+        *dst = src as u8;
+    }
+    Ok(HardwareAddress { hw_type: sa.sa_family, address })
+}
+
+/// Get the index of the interface `name` (`SIOCGIFINDEX`).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn if_index<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<c_uint> {
+    let mut req = ifreq_for(name)?;
+    let index = unsafe {
+        ifreq::siocgifindex(fd, &mut req)?;
+        req.ifr_ifru.ifru_ifindex
+    };
+    Ok(index as c_uint)
+}
+
+/// Rename the interface `name` to `new_name` (`SIOCSIFNAME`).  Most
+/// interfaces must be down to be renamed.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn if_set_name<P1, P2>(fd: RawFd, name: &P1, new_name: &P2) -> Result<()>
+    where P1: ?Sized + NixPath, P2: ?Sized + NixPath
+{
+    let mut req = ifreq_for(name)?;
+    let mut ifr_newname = [0 as libc::c_char; libc::IFNAMSIZ];
+    copy_ifname(new_name, &mut ifr_newname)?;
+    req.ifr_ifru.ifru_newname = ifr_newname;
+    unsafe { ifreq::siocsifname(fd, &req) }.map(drop)
+}
+
+/// Get the primary IPv4 address of the interface `name` (`SIOCGIFADDR`).
+/// Fails with `EADDRNOTAVAIL` if it has none.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn if_addr<P: ?Sized + NixPath>(fd: RawFd, name: &P) -> Result<SockAddr> {
+    let mut req = ifreq_for(name)?;
+    unsafe {
+        ifreq::siocgifaddr(fd, &mut req)?;
+        SockAddr::from_libc_sockaddr(&req.ifr_ifru.ifru_addr)
+    }.ok_or(Error::Sys(Errno::EAFNOSUPPORT))
+}
@@ -0,0 +1,674 @@
+//! Managing links, addresses and routes over rtnetlink.
+//!
+//! The socket of an [`RtNetlink`](struct.RtNetlink.html) handle stays bound
+//! to the network namespace it was opened in, so a handle opened after
+//! entering a namespace with `sched::setns` or `sched::unshare` keeps
+//! configuring that namespace.  [`RtNetlink::in_netns`](struct.RtNetlink.html#method.in_netns)
+//! opens a handle in another namespace without moving the calling thread.
+//!
+//! [Further reading](http://man7.org/linux/man-pages/man7/rtnetlink.7.html)
+use {Error, Result};
+use errno::Errno;
+use libc::{self, c_int};
+use std::{mem, process, ptr};
+use std::os::unix::io::{AsRawFd, RawFd};
+use fcntl::{self, OFlag};
+use net::if_::InterfaceFlags;
+use sched::{self, CloneFlags};
+use sys::stat::Mode;
+use sys::socket::{AddressFamily, IpAddr, Ipv4Addr, Ipv6Addr, SockProtocol};
+use sys::socket::netlink::{NetlinkSocket, NlMsg, NlMsgBuilder, NlmFlags};
+use unistd::{self, Pid};
+use libc::{RTM_DELADDR, RTM_DELLINK, RTM_DELROUTE, RTM_GETADDR, RTM_GETLINK, RTM_GETROUTE,
+           RTM_NEWADDR, RTM_NEWLINK, RTM_NEWROUTE, RTA_DST, RTA_GATEWAY, RTA_OIF, RTA_TABLE,
+           RTN_UNICAST, RTPROT_BOOT, RT_SCOPE_NOWHERE};
+pub use libc::{RT_SCOPE_HOST, RT_SCOPE_LINK, RT_SCOPE_UNIVERSE, RT_TABLE_MAIN};
+
+// libc lacks the following attributes and message headers on Linux.
+
+// Link attributes, from linux/if_link.h
+const IFLA_ADDRESS: u16 = 1;
+const IFLA_IFNAME: u16 = 3;
+const IFLA_MTU: u16 = 4;
+const IFLA_LINKINFO: u16 = 18;
+const IFLA_NET_NS_PID: u16 = 19;
+const IFLA_STATS64: u16 = 23;
+const IFLA_NET_NS_FD: u16 = 28;
+const IFLA_INFO_KIND: u16 = 1;
+const IFLA_INFO_DATA: u16 = 2;
+const VETH_INFO_PEER: u16 = 1;
+
+// Address attributes, from linux/if_addr.h
+const IFA_ADDRESS: u16 = 1;
+const IFA_LOCAL: u16 = 2;
+const IFA_LABEL: u16 = 3;
+
+fn ifinfomsg(index: u32) -> libc::ifinfomsg {
+    let mut msg: libc::ifinfomsg = unsafe { mem::zeroed() };
+    msg.ifi_family = libc::AF_UNSPEC as u8;
+    msg.ifi_index = index as c_int;
+    msg
+}
+
+// struct ifaddrmsg, from linux/if_addr.h
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct IfAddrMsg {
+    ifa_family: u8,
+    ifa_prefixlen: u8,
+    ifa_flags: u8,
+    ifa_scope: u8,
+    ifa_index: u32,
+}
+
+// struct rtmsg, from linux/rtnetlink.h
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RtMsg {
+    rtm_family: u8,
+    rtm_dst_len: u8,
+    rtm_src_len: u8,
+    rtm_tos: u8,
+    rtm_table: u8,
+    rtm_protocol: u8,
+    rtm_scope: u8,
+    rtm_type: u8,
+    rtm_flags: u32,
+}
+
+/// A network interface, as reported by `RTM_GETLINK`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Link {
+    /// The interface index.
+    pub index: u32,
+    /// The interface name.
+    pub name: String,
+    /// The interface flags, such as `IFF_UP`.
+    pub flags: InterfaceFlags,
+    /// The ARP hardware type, such as `ARPHRD_ETHER`.
+    pub link_type: u16,
+    /// The maximum transmission unit.
+    pub mtu: Option<u32>,
+    /// The hardware address, such as the MAC address of an Ethernet device.
+    pub address: Option<Vec<u8>>,
+    /// The kind of virtual device, such as `"veth"` or `"bridge"`.
+    pub kind: Option<String>,
+    /// The traffic counters of the interface.
+    pub stats: Option<LinkStats>,
+}
+
+/// The traffic counters of an interface, from `IFLA_STATS64`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct LinkStats {
+    /// Packets received.
+    pub rx_packets: u64,
+    /// Packets transmitted.
+    pub tx_packets: u64,
+    /// Bytes received.
+    pub rx_bytes: u64,
+    /// Bytes transmitted.
+    pub tx_bytes: u64,
+    /// Bad packets received.
+    pub rx_errors: u64,
+    /// Packets that could not be transmitted.
+    pub tx_errors: u64,
+    /// Received packets dropped, for example for lack of buffer space.
+    pub rx_dropped: u64,
+    /// Packets dropped before transmission.
+    pub tx_dropped: u64,
+    /// Multicast packets received.
+    pub multicast: u64,
+    /// Collisions detected on the medium.
+    pub collisions: u64,
+}
+
+impl LinkStats {
+    // The attribute holds a `struct rtnl_link_stats64`, which has grown over
+    // time; only its leading, stable counters are decoded.
+    fn from_bytes(bytes: &[u8]) -> Option<LinkStats> {
+        if bytes.len() < 10 * mem::size_of::<u64>() {
+            return None;
+        }
+        let counter = |i: usize| unsafe {
+            ptr::read_unaligned(bytes[i * mem::size_of::<u64>()..].as_ptr() as *const u64)
+        };
+        Some(LinkStats {
+            rx_packets: counter(0),
+            tx_packets: counter(1),
+            rx_bytes: counter(2),
+            tx_bytes: counter(3),
+            rx_errors: counter(4),
+            tx_errors: counter(5),
+            rx_dropped: counter(6),
+            tx_dropped: counter(7),
+            multicast: counter(8),
+            collisions: counter(9),
+        })
+    }
+}
+
+impl Link {
+    fn from_msg(msg: &NlMsg) -> Option<Link> {
+        let info: libc::ifinfomsg = match msg.header() {
+            Some(info) => info,
+            None => return None,
+        };
+        let mut link = Link {
+            index: info.ifi_index as u32,
+            name: String::new(),
+            flags: InterfaceFlags::from_bits_truncate(info.ifi_flags as c_int),
+            link_type: info.ifi_type,
+            mtu: None,
+            address: None,
+            kind: None,
+            stats: None,
+        };
+        for attr in msg.attrs::<libc::ifinfomsg>() {
+            match attr.ty() {
+                IFLA_IFNAME => link.name = attr.as_str().unwrap_or("").to_owned(),
+                IFLA_MTU => link.mtu = attr.as_u32(),
+                IFLA_ADDRESS => link.address = Some(attr.data().to_vec()),
+                IFLA_STATS64 => link.stats = LinkStats::from_bytes(attr.data()),
+                IFLA_LINKINFO => {
+                    link.kind = attr.nested().find_attr(IFLA_INFO_KIND)
+                        .and_then(|kind| kind.as_str())
+                        .map(|kind| kind.to_owned());
+                },
+                _ => (),
+            }
+        }
+        Some(link)
+    }
+}
+
+/// An address assigned to an interface, as reported by `RTM_GETADDR`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Address {
+    /// The index of the interface the address is assigned to.
+    pub index: u32,
+    /// The address itself.
+    pub address: IpAddr,
+    /// The length of the network prefix, in bits.
+    pub prefix_len: u8,
+    /// The scope of the address, such as `RT_SCOPE_UNIVERSE`.
+    pub scope: u8,
+    /// The label of an IPv4 address, which defaults to the interface name.
+    pub label: Option<String>,
+}
+
+impl Address {
+    fn from_msg(msg: &NlMsg) -> Option<Address> {
+        let info: IfAddrMsg = match msg.header() {
+            Some(info) => info,
+            None => return None,
+        };
+        let mut local = None;
+        let mut address = None;
+        let mut label = None;
+        for attr in msg.attrs::<IfAddrMsg>() {
+            match attr.ty() {
+                IFA_LOCAL => local = ip_from_bytes(attr.data()),
+                IFA_ADDRESS => address = ip_from_bytes(attr.data()),
+                IFA_LABEL => label = attr.as_str().map(|label| label.to_owned()),
+                _ => (),
+            }
+        }
+        // For point-to-point links IFA_ADDRESS is the peer, so prefer IFA_LOCAL.
+        local.or(address).map(|address| Address {
+            index: info.ifa_index,
+            address,
+            prefix_len: info.ifa_prefixlen,
+            scope: info.ifa_scope,
+            label,
+        })
+    }
+}
+
+/// A network interface together with all of its addresses, as returned by
+/// [`RtNetlink::interfaces`](struct.RtNetlink.html#method.interfaces).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Interface {
+    /// The interface itself, with its index, MTU, hardware address and
+    /// counters.
+    pub link: Link,
+    /// The IPv4 and IPv6 addresses assigned to the interface.
+    pub addresses: Vec<Address>,
+}
+
+/// A route, as reported by `RTM_GETROUTE` or passed to
+/// [`RtNetlink::add_route`](struct.RtNetlink.html#method.add_route).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Route {
+    /// The address family, `AddressFamily::Inet` or `AddressFamily::Inet6`.
+    pub family: AddressFamily,
+    /// The destination network, or `None` for the default route.
+    pub destination: Option<IpAddr>,
+    /// The length of the destination prefix, in bits.
+    pub prefix_len: u8,
+    /// The next hop, for routes that are not directly attached.
+    pub gateway: Option<IpAddr>,
+    /// The index of the outgoing interface.
+    pub index: Option<u32>,
+    /// The routing table, usually `RT_TABLE_MAIN`.
+    pub table: u32,
+    /// The scope of the route, such as `RT_SCOPE_LINK`.
+    pub scope: u8,
+}
+
+impl Route {
+    /// A route to the network `destination/prefix_len` in the main table.
+    pub fn new(destination: IpAddr, prefix_len: u8) -> Route {
+        Route {
+            family: ip_family(&destination),
+            destination: Some(destination),
+            prefix_len,
+            gateway: None,
+            index: None,
+            table: u32::from(RT_TABLE_MAIN),
+            scope: RT_SCOPE_LINK,
+        }
+    }
+
+    /// A default route through `gateway` in the main table.
+    pub fn default_via(gateway: IpAddr) -> Route {
+        Route {
+            family: ip_family(&gateway),
+            destination: None,
+            prefix_len: 0,
+            gateway: Some(gateway),
+            index: None,
+            table: u32::from(RT_TABLE_MAIN),
+            scope: RT_SCOPE_UNIVERSE,
+        }
+    }
+
+    /// Routes through `gateway` instead of directly over the link.
+    pub fn via(mut self, gateway: IpAddr) -> Route {
+        self.gateway = Some(gateway);
+        self.scope = RT_SCOPE_UNIVERSE;
+        self
+    }
+
+    /// Sends the traffic out of the interface with the given index.
+    pub fn dev(mut self, index: u32) -> Route {
+        self.index = Some(index);
+        self
+    }
+
+    fn from_msg(msg: &NlMsg) -> Option<Route> {
+        let info: RtMsg = match msg.header() {
+            Some(info) => info,
+            None => return None,
+        };
+        let family = match info.rtm_family as c_int {
+            libc::AF_INET => AddressFamily::Inet,
+            libc::AF_INET6 => AddressFamily::Inet6,
+            _ => return None,
+        };
+        let mut route = Route {
+            family,
+            destination: None,
+            prefix_len: info.rtm_dst_len,
+            gateway: None,
+            index: None,
+            table: info.rtm_table as u32,
+            scope: info.rtm_scope,
+        };
+        for attr in msg.attrs::<RtMsg>() {
+            match attr.ty() {
+                RTA_DST => route.destination = ip_from_bytes(attr.data()),
+                RTA_GATEWAY => route.gateway = ip_from_bytes(attr.data()),
+                RTA_OIF => route.index = attr.as_u32(),
+                RTA_TABLE => route.table = attr.as_u32().unwrap_or(route.table),
+                _ => (),
+            }
+        }
+        Some(route)
+    }
+
+    fn build_msg(&self, ty: u16, flags: NlmFlags) -> NlMsgBuilder {
+        let deleting = ty == RTM_DELROUTE;
+        let header = RtMsg {
+            rtm_family: self.family as u8,
+            rtm_dst_len: self.prefix_len,
+            rtm_src_len: 0,
+            rtm_tos: 0,
+            rtm_table: if self.table < 256 { self.table as u8 } else { 0 },
+            rtm_protocol: if deleting { 0 } else { RTPROT_BOOT },
+            rtm_scope: if deleting { RT_SCOPE_NOWHERE } else { self.scope },
+            rtm_type: if deleting { 0 } else { RTN_UNICAST },
+            rtm_flags: 0,
+        };
+        let mut msg = NlMsgBuilder::new(ty, flags);
+        msg.push_header(&header);
+        if self.table >= 256 {
+            msg.attr_u32(RTA_TABLE, self.table);
+        }
+        if let Some(ref destination) = self.destination {
+            msg.attr(RTA_DST, &ip_to_bytes(destination));
+        }
+        if let Some(ref gateway) = self.gateway {
+            msg.attr(RTA_GATEWAY, &ip_to_bytes(gateway));
+        }
+        if let Some(index) = self.index {
+            msg.attr_u32(RTA_OIF, index);
+        }
+        msg
+    }
+}
+
+fn ip_family(ip: &IpAddr) -> AddressFamily {
+    match *ip {
+        IpAddr::V4(_) => AddressFamily::Inet,
+        IpAddr::V6(_) => AddressFamily::Inet6,
+    }
+}
+
+// The address in network byte order, as carried in netlink attributes.
+fn ip_to_bytes(ip: &IpAddr) -> Vec<u8> {
+    match *ip {
+        IpAddr::V4(Ipv4Addr(ref addr)) => {
+            let s_addr = addr.s_addr;
+            unsafe {
+                ::std::slice::from_raw_parts(&s_addr as *const u32 as *const u8, 4).to_vec()
+            }
+        },
+        IpAddr::V6(Ipv6Addr(ref addr)) => addr.s6_addr.to_vec(),
+    }
+}
+
+fn ip_from_bytes(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => {
+            let mut addr: libc::in_addr = unsafe { mem::zeroed() };
+            addr.s_addr = unsafe { ptr::read_unaligned(bytes.as_ptr() as *const u32) };
+            Some(IpAddr::V4(Ipv4Addr(addr)))
+        },
+        16 => {
+            let mut addr: libc::in6_addr = unsafe { mem::zeroed() };
+            addr.s6_addr.copy_from_slice(bytes);
+            Some(IpAddr::V6(Ipv6Addr(addr)))
+        },
+        _ => None,
+    }
+}
+
+/// A handle for querying and changing the network configuration of a
+/// network namespace.
+///
+/// # Examples
+///
+/// ```
+/// use nix::net::rtnetlink::RtNetlink;
+///
+/// let mut rtnl = RtNetlink::new().unwrap();
+/// let lo = rtnl.link_by_name("lo").unwrap();
+/// assert_eq!(lo.index, 1);
+/// ```
+#[derive(Debug)]
+pub struct RtNetlink {
+    sock: NetlinkSocket,
+}
+
+impl RtNetlink {
+    /// Opens an rtnetlink socket in the current network namespace.
+    pub fn new() -> Result<RtNetlink> {
+        NetlinkSocket::new(SockProtocol::NetlinkRoute).map(|sock| RtNetlink { sock })
+    }
+
+    /// Opens an rtnetlink socket in the network namespace referred to by
+    /// `netns`, such as an open `/proc/<pid>/ns/net`.
+    ///
+    /// The calling thread enters the namespace with `setns` just long enough
+    /// to open the socket and then returns to its original namespace.  If it
+    /// cannot return, the process is aborted rather than left running in the
+    /// foreign namespace.
+    ///
+    /// Both switches require `CAP_SYS_ADMIN` in the caller's user namespace
+    /// and in the user namespaces that own `netns` and the original network
+    /// namespace.
+    pub fn in_netns<F: AsRawFd>(netns: &F) -> Result<RtNetlink> {
+        let path = format!("/proc/self/task/{}/ns/net", unistd::gettid());
+        let current = fcntl::open(path.as_str(), OFlag::O_RDONLY | OFlag::O_CLOEXEC,
+                                  Mode::empty())?;
+        let rtnl = sched::setns(netns.as_raw_fd(), CloneFlags::CLONE_NEWNET)
+            .map(|_| {
+                let rtnl = RtNetlink::new();
+                if sched::setns(current, CloneFlags::CLONE_NEWNET).is_err() {
+                    process::abort();
+                }
+                rtnl
+            });
+        let _ = unistd::close(current);
+        rtnl?
+    }
+
+    /// Gives access to the underlying netlink socket, for requests this type
+    /// does not cover.
+    pub fn socket(&mut self) -> &mut NetlinkSocket {
+        &mut self.sock
+    }
+
+    /// Lists all network interfaces.
+    pub fn links(&mut self) -> Result<Vec<Link>> {
+        let mut msg = NlMsgBuilder::new(RTM_GETLINK, NlmFlags::NLM_F_DUMP);
+        msg.push_header(&ifinfomsg(0));
+        let mut links = Vec::new();
+        self.sock.request(msg, |reply| {
+            if reply.ty() == RTM_NEWLINK {
+                links.extend(Link::from_msg(reply));
+            }
+            Ok(())
+        })?;
+        Ok(links)
+    }
+
+    /// Lists all network interfaces along with their addresses, sorted by
+    /// interface index.
+    pub fn interfaces(&mut self) -> Result<Vec<Interface>> {
+        let mut interfaces: Vec<Interface> = self.links()?.into_iter()
+            .map(|link| Interface { link, addresses: Vec::new() })
+            .collect();
+        interfaces.sort_by_key(|interface| interface.link.index);
+        for address in self.addresses()? {
+            if let Ok(i) = interfaces.binary_search_by_key(&address.index,
+                                                           |interface| interface.link.index) {
+                interfaces[i].addresses.push(address);
+            }
+        }
+        Ok(interfaces)
+    }
+
+    /// Looks up the interface with the given name.  Fails with `ENODEV` if
+    /// there is none.
+    pub fn link_by_name(&mut self, name: &str) -> Result<Link> {
+        let mut msg = NlMsgBuilder::new(RTM_GETLINK, NlmFlags::empty());
+        msg.push_header(&ifinfomsg(0))
+           .attr_str(IFLA_IFNAME, name);
+        self.get_link(msg)
+    }
+
+    /// Looks up the interface with the given index.  Fails with `ENODEV` if
+    /// there is none.
+    pub fn link_by_index(&mut self, index: u32) -> Result<Link> {
+        let mut msg = NlMsgBuilder::new(RTM_GETLINK, NlmFlags::empty());
+        msg.push_header(&ifinfomsg(index));
+        self.get_link(msg)
+    }
+
+    fn get_link(&mut self, msg: NlMsgBuilder) -> Result<Link> {
+        let mut link = None;
+        self.sock.request(msg, |reply| {
+            if reply.ty() == RTM_NEWLINK {
+                link = Link::from_msg(reply);
+            }
+            Ok(())
+        })?;
+        link.ok_or(Error::Sys(Errno::ENODEV))
+    }
+
+    /// Creates a pair of connected virtual Ethernet devices.
+    pub fn add_veth(&mut self, name: &str, peer_name: &str) -> Result<()> {
+        let mut msg = NlMsgBuilder::new(RTM_NEWLINK,
+                                        NlmFlags::NLM_F_CREATE | NlmFlags::NLM_F_EXCL);
+        msg.push_header(&ifinfomsg(0))
+           .attr_str(IFLA_IFNAME, name)
+           .begin_nested(IFLA_LINKINFO)
+               .attr_str(IFLA_INFO_KIND, "veth")
+               .begin_nested(IFLA_INFO_DATA)
+                   .begin_nested(VETH_INFO_PEER)
+                       .push_header(&ifinfomsg(0))
+                       .attr_str(IFLA_IFNAME, peer_name)
+                   .end_nested()
+               .end_nested()
+           .end_nested();
+        self.sock.ack(msg)
+    }
+
+    /// Deletes a network interface.  Deleting one end of a veth pair deletes
+    /// both.
+    pub fn delete_link(&mut self, index: u32) -> Result<()> {
+        let mut msg = NlMsgBuilder::new(RTM_DELLINK, NlmFlags::empty());
+        msg.push_header(&ifinfomsg(index));
+        self.sock.ack(msg)
+    }
+
+    /// Brings an interface up or down.
+    pub fn set_link_up(&mut self, index: u32, up: bool) -> Result<()> {
+        let mut header = ifinfomsg(index);
+        header.ifi_change = InterfaceFlags::IFF_UP.bits() as u32;
+        if up {
+            header.ifi_flags = header.ifi_change;
+        }
+        let mut msg = NlMsgBuilder::new(RTM_NEWLINK, NlmFlags::empty());
+        msg.push_header(&header);
+        self.sock.ack(msg)
+    }
+
+    /// Renames an interface.  Most devices must be down to be renamed.
+    pub fn set_link_name(&mut self, index: u32, name: &str) -> Result<()> {
+        self.set_link_attr(index, |msg| { msg.attr_str(IFLA_IFNAME, name); })
+    }
+
+    /// Sets the maximum transmission unit of an interface.
+    pub fn set_link_mtu(&mut self, index: u32, mtu: u32) -> Result<()> {
+        self.set_link_attr(index, |msg| { msg.attr_u32(IFLA_MTU, mtu); })
+    }
+
+    /// Sets the hardware address of an interface, such as its MAC address.
+    pub fn set_link_address(&mut self, index: u32, address: &[u8]) -> Result<()> {
+        self.set_link_attr(index, |msg| { msg.attr(IFLA_ADDRESS, address); })
+    }
+
+    /// Moves an interface into the network namespace of the process `pid`.
+    pub fn set_link_netns_pid(&mut self, index: u32, pid: Pid) -> Result<()> {
+        let pid: libc::pid_t = pid.into();
+        self.set_link_attr(index, |msg| { msg.attr_u32(IFLA_NET_NS_PID, pid as u32); })
+    }
+
+    /// Moves an interface into the network namespace referred to by `fd`,
+    /// such as an open `/proc/<pid>/ns/net`.
+    pub fn set_link_netns_fd<F: AsRawFd>(&mut self, index: u32, fd: &F) -> Result<()> {
+        let fd: RawFd = fd.as_raw_fd();
+        self.set_link_attr(index, |msg| { msg.attr_u32(IFLA_NET_NS_FD, fd as u32); })
+    }
+
+    fn set_link_attr<F: FnOnce(&mut NlMsgBuilder)>(&mut self, index: u32, f: F) -> Result<()> {
+        let mut msg = NlMsgBuilder::new(RTM_NEWLINK, NlmFlags::empty());
+        msg.push_header(&ifinfomsg(index));
+        f(&mut msg);
+        self.sock.ack(msg)
+    }
+
+    /// Lists the addresses of all interfaces.
+    pub fn addresses(&mut self) -> Result<Vec<Address>> {
+        let mut msg = NlMsgBuilder::new(RTM_GETADDR, NlmFlags::NLM_F_DUMP);
+        msg.push_header(&IfAddrMsg {
+            ifa_family: libc::AF_UNSPEC as u8,
+            ifa_prefixlen: 0,
+            ifa_flags: 0,
+            ifa_scope: 0,
+            ifa_index: 0,
+        });
+        let mut addresses = Vec::new();
+        self.sock.request(msg, |reply| {
+            if reply.ty() == RTM_NEWADDR {
+                addresses.extend(Address::from_msg(reply));
+            }
+            Ok(())
+        })?;
+        Ok(addresses)
+    }
+
+    /// Assigns `address/prefix_len` to an interface.
+    pub fn add_address(&mut self, index: u32, address: IpAddr, prefix_len: u8) -> Result<()> {
+        let msg = Self::address_msg(RTM_NEWADDR, NlmFlags::NLM_F_CREATE | NlmFlags::NLM_F_EXCL,
+                                    index, &address, prefix_len);
+        self.sock.ack(msg)
+    }
+
+    /// Removes `address/prefix_len` from an interface.
+    pub fn delete_address(&mut self, index: u32, address: IpAddr, prefix_len: u8) -> Result<()> {
+        let msg = Self::address_msg(RTM_DELADDR, NlmFlags::empty(), index, &address, prefix_len);
+        self.sock.ack(msg)
+    }
+
+    fn address_msg(ty: u16, flags: NlmFlags, index: u32, address: &IpAddr,
+                   prefix_len: u8) -> NlMsgBuilder {
+        let bytes = ip_to_bytes(address);
+        let mut msg = NlMsgBuilder::new(ty, flags);
+        msg.push_header(&IfAddrMsg {
+            ifa_family: ip_family(address) as u8,
+            ifa_prefixlen: prefix_len,
+            ifa_flags: 0,
+            ifa_scope: RT_SCOPE_UNIVERSE,
+            ifa_index: index,
+        })
+           .attr(IFA_LOCAL, &bytes)
+           .attr(IFA_ADDRESS, &bytes);
+        msg
+    }
+
+    /// Lists the routes of all routing tables.
+    pub fn routes(&mut self) -> Result<Vec<Route>> {
+        let mut msg = NlMsgBuilder::new(RTM_GETROUTE, NlmFlags::NLM_F_DUMP);
+        msg.push_header(&RtMsg {
+            rtm_family: libc::AF_UNSPEC as u8,
+            rtm_dst_len: 0,
+            rtm_src_len: 0,
+            rtm_tos: 0,
+            rtm_table: 0,
+            rtm_protocol: 0,
+            rtm_scope: 0,
+            rtm_type: 0,
+            rtm_flags: 0,
+        });
+        let mut routes = Vec::new();
+        self.sock.request(msg, |reply| {
+            if reply.ty() == RTM_NEWROUTE {
+                routes.extend(Route::from_msg(reply));
+            }
+            Ok(())
+        })?;
+        Ok(routes)
+    }
+
+    /// Adds a route.  Fails with `EEXIST` if an identical route exists.
+    pub fn add_route(&mut self, route: &Route) -> Result<()> {
+        let msg = route.build_msg(RTM_NEWROUTE, NlmFlags::NLM_F_CREATE | NlmFlags::NLM_F_EXCL);
+        self.sock.ack(msg)
+    }
+
+    /// Deletes a route.  Fails with `ESRCH` if there is no matching route.
+    pub fn delete_route(&mut self, route: &Route) -> Result<()> {
+        let msg = route.build_msg(RTM_DELROUTE, NlmFlags::empty());
+        self.sock.ack(msg)
+    }
+}
+
+impl AsRawFd for RtNetlink {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sock.as_raw_fd()
+    }
+}
+
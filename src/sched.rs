@@ -4,7 +4,10 @@ use std::option::Option;
 use libc::{self, c_int, c_void};
 use {Error, Result};
 use errno::Errno;
-use ::unistd::Pid;
+use fcntl::{self, OFlag};
+use sys::stat::Mode;
+use sys::time::{TimeSpec, TimeValLike};
+use ::unistd::{self, Pid};
 
 // For some functions taking with a parameter of type CloneFlags,
 // only a subset of these flags have an effect.
@@ -33,6 +36,9 @@ libc_bitflags!{
         CLONE_NEWPID;
         CLONE_NEWNET;
         CLONE_IO;
+        /// Create the process in a new time namespace. Since Linux 5.6.
+        #[cfg(all(target_os = "linux", any(target_env = "gnu", target_env = "musl")))]
+        CLONE_NEWTIME;
     }
 }
 
@@ -119,3 +125,127 @@ pub fn setns(fd: RawFd, nstype: CloneFlags) -> Result<()> {
 
     Errno::result(res).map(drop)
 }
+
+/// The clock offsets of a time namespace, relative to the initial time namespace.
+///
+/// The offsets of a new time namespace can only be written by the process that created it,
+/// with `CLONE_NEWTIME`, before any process has entered the namespace.
+///
+/// For more documentation, please read [time_namespaces(7)](http://man7.org/linux/man-pages/man7/time_namespaces.7.html).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TimeNsOffsets {
+    /// The offset of `CLOCK_MONOTONIC` and its variants.
+    pub monotonic: TimeSpec,
+    /// The offset of `CLOCK_BOOTTIME` and its variants.
+    pub boottime: TimeSpec,
+}
+
+impl TimeNsOffsets {
+    /// Creates offsets for the `CLOCK_MONOTONIC` and `CLOCK_BOOTTIME` clocks.
+    pub fn new(monotonic: TimeSpec, boottime: TimeSpec) -> TimeNsOffsets {
+        TimeNsOffsets { monotonic, boottime }
+    }
+
+    /// Reads the offsets of the time namespace of the process `pid` from
+    /// `/proc/<pid>/timens_offsets`.
+    pub fn read(pid: Pid) -> Result<TimeNsOffsets> {
+        let fd = fcntl::open(&timens_offsets_path(pid)[..],
+                             OFlag::O_RDONLY | OFlag::O_CLOEXEC, Mode::empty())?;
+        let mut buf = [0u8; 256];
+        let res = unistd::read(fd, &mut buf);
+        let _ = unistd::close(fd);
+        let len = res?;
+
+        let content = ::std::str::from_utf8(&buf[..len]).map_err(|_| Error::InvalidUtf8)?;
+        TimeNsOffsets::parse(content).ok_or_else(Error::invalid_argument)
+    }
+
+    /// Writes the offsets to `/proc/<pid>/timens_offsets`, where `pid` is a process that
+    /// created, but did not enter, a new time namespace.
+    pub fn write(&self, pid: Pid) -> Result<()> {
+        let content = self.to_string();
+        let fd = fcntl::open(&timens_offsets_path(pid)[..],
+                             OFlag::O_WRONLY | OFlag::O_CLOEXEC, Mode::empty())?;
+        // The kernel expects all offsets in a single write.
+        let res = unistd::write(fd, content.as_bytes());
+        let _ = unistd::close(fd);
+
+        match res {
+            Ok(n) if n == content.len() => Ok(()),
+            Ok(_) => Err(Error::Sys(Errno::EIO)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parses the contents of a `timens_offsets` file.
+    ///
+    /// Each line consists of a clock name (`monotonic` or `boottime`) or its numeric ID,
+    /// followed by the seconds and nanoseconds of its offset. Clocks that are not mentioned have
+    /// an offset of 0.
+    pub fn parse(content: &str) -> Option<TimeNsOffsets> {
+        let mut offsets = TimeNsOffsets::default();
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 3 {
+                return None;
+            }
+            let (clock, secs, nsecs) = match (fields[1].parse(), fields[2].parse()) {
+                (Ok(secs), Ok(nsecs)) if nsecs >= 0 && nsecs < 1_000_000_000 => {
+                    (fields[0], secs, nsecs)
+                }
+                _ => return None,
+            };
+            let offset = TimeSpec::from(libc::timespec { tv_sec: secs, tv_nsec: nsecs });
+            match clock {
+                "monotonic" => offsets.monotonic = offset,
+                "boottime" => offsets.boottime = offset,
+                _ => match clock.parse::<libc::clockid_t>() {
+                    Ok(libc::CLOCK_MONOTONIC) => offsets.monotonic = offset,
+                    Ok(libc::CLOCK_BOOTTIME) => offsets.boottime = offset,
+                    _ => return None,
+                },
+            }
+        }
+        Some(offsets)
+    }
+}
+
+impl Default for TimeNsOffsets {
+    fn default() -> TimeNsOffsets {
+        TimeNsOffsets::new(TimeSpec::zero(), TimeSpec::zero())
+    }
+}
+
+impl ::std::fmt::Display for TimeNsOffsets {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        writeln!(f, "monotonic {} {}", self.monotonic.tv_sec(), self.monotonic.tv_nsec())?;
+        writeln!(f, "boottime {} {}", self.boottime.tv_sec(), self.boottime.tv_nsec())
+    }
+}
+
+fn timens_offsets_path(pid: Pid) -> String {
+    format!("/proc/{}/timens_offsets", pid)
+}
+
+#[cfg(test)]
+mod test {
+    use super::TimeNsOffsets;
+    use sys::time::{TimeSpec, TimeValLike};
+
+    #[test]
+    fn test_timens_offsets_parse() {
+        let content = "monotonic           5   1000000\nboottime           -1 999999999\n";
+        let offsets = TimeNsOffsets::parse(content).unwrap();
+        assert_eq!(offsets.monotonic, TimeSpec::nanoseconds(5_001_000_000));
+        assert_eq!(offsets.boottime, TimeSpec::nanoseconds(-1));
+        assert_eq!(TimeNsOffsets::parse(&offsets.to_string()), Some(offsets));
+
+        let offsets = TimeNsOffsets::parse("1 3 0\n").unwrap();
+        assert_eq!(offsets.monotonic, TimeSpec::seconds(3));
+        assert_eq!(offsets.boottime, TimeSpec::zero());
+
+        assert_eq!(TimeNsOffsets::parse("realtime 1 0\n"), None);
+        assert_eq!(TimeNsOffsets::parse("monotonic 1 1000000000\n"), None);
+        assert_eq!(TimeNsOffsets::parse("monotonic 1\n"), None);
+    }
+}
@@ -1,9 +1,14 @@
 use Result;
 use errno::Errno;
 use libc::{self, c_int};
-use std::os::unix::io::RawFd;
+use std::cmp;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::ptr;
 use std::mem;
+use std::time::Duration;
+use sys::signal::SigSet;
+use sys::time::TimeSpec;
+use unistd::close;
 use ::Error;
 
 libc_bitflags!(
@@ -108,3 +113,110 @@ pub fn epoll_wait(epfd: RawFd, events: &mut [EpollEvent], timeout_ms: isize) ->
 
     Errno::result(res).map(|r| r as usize)
 }
+
+/// An epoll instance. It is closed when dropped.
+///
+/// For more documentation, please read [epoll(7)](http://man7.org/linux/man-pages/man7/epoll.7.html).
+#[derive(Debug, Eq, Hash, PartialEq)]
+pub struct Epoll(RawFd);
+
+impl Epoll {
+    /// Creates a new epoll instance.
+    pub fn new(flags: EpollCreateFlags) -> Result<Epoll> {
+        epoll_create1(flags).map(Epoll)
+    }
+
+    /// Registers `fd` with the interest list, using the events and data of `event`.
+    ///
+    /// `EPOLLEXCLUSIVE` may only be used here; `modify` rejects it with `EINVAL`.
+    pub fn add<F: AsRawFd>(&self, fd: &F, mut event: EpollEvent) -> Result<()> {
+        epoll_ctl(self.0, EpollOp::EpollCtlAdd, fd.as_raw_fd(), &mut event)
+    }
+
+    /// Changes the events and data associated with the already registered `fd`.
+    pub fn modify<F: AsRawFd>(&self, fd: &F, mut event: EpollEvent) -> Result<()> {
+        epoll_ctl(self.0, EpollOp::EpollCtlMod, fd.as_raw_fd(), &mut event)
+    }
+
+    /// Removes `fd` from the interest list.
+    pub fn delete<F: AsRawFd>(&self, fd: &F) -> Result<()> {
+        epoll_ctl(self.0, EpollOp::EpollCtlDel, fd.as_raw_fd(), None)
+    }
+
+    /// Waits for events and stores them in `events`.
+    ///
+    /// Returns the number of events stored, which is 0 if `timeout` expired. `None` waits
+    /// indefinitely. The timeout is rounded up to whole milliseconds; use `pwait2` for a finer
+    /// resolution.
+    pub fn wait(&self, events: &mut [EpollEvent], timeout: Option<Duration>) -> Result<usize> {
+        let timeout_ms = match timeout {
+            None => -1,
+            Some(timeout) => {
+                let ms = timeout.as_secs()
+                    .saturating_mul(1000)
+                    .saturating_add((u64::from(timeout.subsec_nanos()) + 999_999) / 1_000_000);
+                cmp::min(ms, c_int::max_value() as u64) as isize
+            }
+        };
+        epoll_wait(self.0, events, timeout_ms)
+    }
+
+    /// Waits for events like `wait`, but with a nanosecond resolution timeout and optionally
+    /// replacing the signal mask for the duration of the call.
+    ///
+    /// Requires Linux 5.11 or newer; older kernels fail with `ENOSYS`.
+    ///
+    /// For more documentation, please read [epoll_pwait2(2)](http://man7.org/linux/man-pages/man2/epoll_wait.2.html).
+    pub fn pwait2(&self, events: &mut [EpollEvent], timeout: Option<TimeSpec>,
+                  sigmask: Option<&SigSet>) -> Result<usize> {
+        epoll_pwait2(self.0, events, timeout, sigmask)
+    }
+}
+
+impl Drop for Epoll {
+    fn drop(&mut self) {
+        let _ = close(self.0);
+    }
+}
+
+impl AsRawFd for Epoll {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+/// The size of the kernel's signal set, which is smaller than glibc's `sigset_t`.
+#[cfg(any(target_arch = "mips", target_arch = "mips64"))]
+const KERNEL_SIGSET_SIZE: usize = 16;
+#[cfg(not(any(target_arch = "mips", target_arch = "mips64")))]
+const KERNEL_SIGSET_SIZE: usize = 8;
+
+/// The kernel's `struct __kernel_timespec`, which is 64-bit on all architectures.
+#[repr(C)]
+struct KernelTimespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+/// Waits for events on the epoll instance `epfd` with a `TimeSpec` timeout.
+///
+/// `None` waits indefinitely. If `sigmask` is given, it replaces the signal mask of the calling
+/// thread while waiting. Requires Linux 5.11 or newer.
+///
+/// For more documentation, please read [epoll_pwait2(2)](http://man7.org/linux/man-pages/man2/epoll_wait.2.html).
+pub fn epoll_pwait2(epfd: RawFd, events: &mut [EpollEvent], timeout: Option<TimeSpec>,
+                    sigmask: Option<&SigSet>) -> Result<usize> {
+    let timeout = timeout.map(|t| KernelTimespec {
+        tv_sec: t.as_ref().tv_sec as i64,
+        tv_nsec: t.as_ref().tv_nsec as i64,
+    });
+    let timeout_ptr = timeout.as_ref().map_or(ptr::null(), |t| t as *const KernelTimespec);
+    let sigmask_ptr = sigmask.map_or(ptr::null(), |s| s.as_ref() as *const libc::sigset_t);
+    let res = unsafe {
+        libc::syscall(libc::SYS_epoll_pwait2, epfd,
+                      events.as_mut_ptr() as *mut libc::epoll_event, events.len() as c_int,
+                      timeout_ptr, sigmask_ptr, KERNEL_SIGSET_SIZE)
+    };
+
+    Errno::result(res).map(|r| r as usize)
+}
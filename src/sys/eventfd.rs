@@ -1,6 +1,7 @@
 use libc;
-use std::os::unix::io::RawFd;
-use Result;
+use std::os::unix::io::{AsRawFd, RawFd};
+use unistd::{close, read, write};
+use {Error, Result};
 use errno::Errno;
 
 libc_bitflags! {
@@ -16,3 +17,76 @@ pub fn eventfd(initval: libc::c_uint, flags: EfdFlags) -> Result<RawFd> {
 
     Errno::result(res).map(|r| r as RawFd)
 }
+
+/// An event notification file descriptor. It is closed when dropped.
+///
+/// The kernel keeps a 64-bit counter for each `EventFd`. Writing adds to the counter and reading
+/// returns it, blocking while it is zero. In semaphore mode (`EFD_SEMAPHORE`) each read
+/// decrements the counter by one and returns 1 instead of resetting it to zero.
+///
+/// For more documentation, please read [eventfd(2)](http://man7.org/linux/man-pages/man2/eventfd.2.html).
+#[derive(Debug, Eq, Hash, PartialEq)]
+pub struct EventFd(RawFd);
+
+impl EventFd {
+    /// Creates a new `EventFd` with a counter of 0 and the `EFD_CLOEXEC` flag set.
+    pub fn new() -> Result<EventFd> {
+        EventFd::from_value_and_flags(0, EfdFlags::EFD_CLOEXEC)
+    }
+
+    /// Creates a new `EventFd` in semaphore mode with the given initial counter value and the
+    /// `EFD_CLOEXEC` flag set.
+    pub fn semaphore(initval: libc::c_uint) -> Result<EventFd> {
+        EventFd::from_value_and_flags(initval, EfdFlags::EFD_CLOEXEC | EfdFlags::EFD_SEMAPHORE)
+    }
+
+    /// Creates a new `EventFd` with the given initial counter value and flags.
+    pub fn from_value_and_flags(initval: libc::c_uint, flags: EfdFlags) -> Result<EventFd> {
+        eventfd(initval, flags).map(EventFd)
+    }
+
+    /// Adds `value` to the counter.
+    ///
+    /// Blocks if the counter would overflow, or fails with `EAGAIN` if the `EventFd` is
+    /// non-blocking. A value of `u64::MAX` is rejected with `EINVAL`.
+    pub fn write(&self, value: u64) -> Result<()> {
+        let buf = unsafe { ::std::slice::from_raw_parts(&value as *const u64 as *const u8, 8) };
+        loop {
+            match write(self.0, buf) {
+                Ok(_) => return Ok(()),
+                Err(Error::Sys(Errno::EINTR)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Reads the counter.
+    ///
+    /// Returns the counter and resets it to 0, or returns 1 and decrements it in semaphore mode.
+    /// Blocks while the counter is 0, or fails with `EAGAIN` if the `EventFd` is non-blocking.
+    pub fn read(&self) -> Result<u64> {
+        let mut buf = [0u8; 8];
+        loop {
+            match read(self.0, &mut buf) {
+                Ok(8) => {
+                    return Ok(unsafe { ::std::ptr::read_unaligned(buf.as_ptr() as *const u64) });
+                }
+                Ok(_) => return Err(Error::Sys(Errno::EIO)),
+                Err(Error::Sys(Errno::EINTR)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for EventFd {
+    fn drop(&mut self) {
+        let _ = close(self.0);
+    }
+}
+
+impl AsRawFd for EventFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
@@ -0,0 +1,486 @@
+//! Monitoring API for filesystem events, including permission decisions.
+//!
+//! Unlike inotify, fanotify can watch whole mounts and filesystems, and it can ask a listener
+//! to allow or deny accesses before they happen. Most operations require `CAP_SYS_ADMIN`.
+//!
+//! For more documentation, please read [fanotify(7)](http://man7.org/linux/man-pages/man7/fanotify.7.html).
+//!
+//! # Examples
+//!
+//! Report every file that is modified on the filesystem containing `/var/lib`:
+//! ```no_run
+//! # use nix::fcntl::OFlag;
+//! # use nix::sys::fanotify::{Fanotify, InitFlags, MarkFlags, MaskFlags};
+//! let fan = Fanotify::init(InitFlags::FAN_CLASS_NOTIF | InitFlags::FAN_REPORT_DFID_NAME,
+//!                          OFlag::O_RDONLY).unwrap();
+//! fan.mark(MarkFlags::FAN_MARK_ADD | MarkFlags::FAN_MARK_FILESYSTEM,
+//!          MaskFlags::FAN_MODIFY, None, Some("/var/lib")).unwrap();
+//!
+//! loop {
+//!     for event in fan.read_events().unwrap() {
+//!         for fid in event.fids() {
+//!             println!("{:?} modified", fid.name);
+//!         }
+//!     }
+//! }
+//! ```
+
+use libc::{self, c_uint};
+use std::ffi::{OsStr, OsString};
+use std::mem::size_of;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::ptr;
+use fcntl::OFlag;
+use unistd::{close, read, write, Pid};
+use {Error, NixPath, Result};
+use errno::Errno;
+
+libc_bitflags! {
+    /// Configuration options for [`Fanotify::init`](struct.Fanotify.html#method.init).
+    pub struct InitFlags: c_uint {
+        /// Set the `FD_CLOEXEC` flag on the file descriptor.
+        FAN_CLOEXEC;
+        /// Set the `O_NONBLOCK` flag on the file descriptor.
+        FAN_NONBLOCK;
+        /// Only receive notifications after files were accessed.
+        FAN_CLASS_NOTIF;
+        /// Also receive permission events, after the file content is available.
+        FAN_CLASS_CONTENT;
+        /// Also receive permission events, before the file content is available.
+        FAN_CLASS_PRE_CONTENT;
+        /// Don't limit the number of queued events.
+        FAN_UNLIMITED_QUEUE;
+        /// Don't limit the number of marks.
+        FAN_UNLIMITED_MARKS;
+        /// Allow permission responses to request an audit record.
+        FAN_ENABLE_AUDIT;
+        /// Report thread ids instead of process ids.
+        FAN_REPORT_TID;
+        /// Identify objects by file handle instead of opening a file descriptor.
+        FAN_REPORT_FID;
+        /// Identify the directory containing the object by file handle.
+        FAN_REPORT_DIR_FID;
+        /// Also report the name of the object within its directory.
+        ///
+        /// Requires `FAN_REPORT_DIR_FID`.
+        FAN_REPORT_NAME;
+        /// Combination of `FAN_REPORT_DIR_FID` and `FAN_REPORT_NAME`.
+        FAN_REPORT_DFID_NAME;
+    }
+}
+
+libc_bitflags! {
+    /// Configuration options for [`Fanotify::mark`](struct.Fanotify.html#method.mark).
+    pub struct MarkFlags: c_uint {
+        /// Add the events in the mask to the mark.
+        FAN_MARK_ADD;
+        /// Remove the events in the mask from the mark.
+        FAN_MARK_REMOVE;
+        /// Remove all marks of the kind given by `FAN_MARK_MOUNT` or `FAN_MARK_FILESYSTEM`, or
+        /// all inode marks if neither is given.
+        FAN_MARK_FLUSH;
+        /// Don't follow symlinks.
+        FAN_MARK_DONT_FOLLOW;
+        /// Fail if the path is not a directory.
+        FAN_MARK_ONLYDIR;
+        /// Add the events to the ignore mask instead of the event mask.
+        FAN_MARK_IGNORED_MASK;
+        /// Keep the ignore mask when the file is modified.
+        FAN_MARK_IGNORED_SURV_MODIFY;
+        /// Mark the inode at the path. This is the default.
+        FAN_MARK_INODE;
+        /// Mark the whole mount containing the path.
+        FAN_MARK_MOUNT;
+        /// Mark the whole filesystem containing the path.
+        FAN_MARK_FILESYSTEM;
+    }
+}
+
+libc_bitflags! {
+    /// The events to mark, and the events reported by [`FanotifyEvent`](struct.FanotifyEvent.html).
+    pub struct MaskFlags: u64 {
+        /// A file or directory was accessed.
+        FAN_ACCESS;
+        /// A file was modified.
+        FAN_MODIFY;
+        /// Metadata of a file or directory changed. Requires a FID reporting mode.
+        FAN_ATTRIB;
+        /// A writable file was closed.
+        FAN_CLOSE_WRITE;
+        /// A read-only file or a directory was closed.
+        FAN_CLOSE_NOWRITE;
+        /// A file or directory was opened.
+        FAN_OPEN;
+        /// A file or directory was moved out of a marked directory. Requires a FID reporting
+        /// mode.
+        FAN_MOVED_FROM;
+        /// A file or directory was moved into a marked directory. Requires a FID reporting mode.
+        FAN_MOVED_TO;
+        /// A file or directory was created in a marked directory. Requires a FID reporting mode.
+        FAN_CREATE;
+        /// A file or directory was deleted from a marked directory. Requires a FID reporting
+        /// mode.
+        FAN_DELETE;
+        /// A marked file or directory was deleted. Requires a FID reporting mode.
+        FAN_DELETE_SELF;
+        /// A marked file or directory was moved. Requires a FID reporting mode.
+        FAN_MOVE_SELF;
+        /// A file was opened for execution.
+        FAN_OPEN_EXEC;
+        /// The event queue overflowed.
+        FAN_Q_OVERFLOW;
+        /// Permission to open a file or directory is requested.
+        FAN_OPEN_PERM;
+        /// Permission to read a file or directory is requested.
+        FAN_ACCESS_PERM;
+        /// Permission to open a file for execution is requested.
+        FAN_OPEN_EXEC_PERM;
+        /// The event is about a directory.
+        FAN_ONDIR;
+        /// Also report events for the direct children of a marked directory.
+        FAN_EVENT_ON_CHILD;
+        /// Combination of `FAN_CLOSE_WRITE` and `FAN_CLOSE_NOWRITE`.
+        FAN_CLOSE;
+        /// Combination of `FAN_MOVED_FROM` and `FAN_MOVED_TO`.
+        FAN_MOVE;
+    }
+}
+
+/// What an information record of a [`FanotifyEvent`](struct.FanotifyEvent.html) identifies.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FidInfoType {
+    /// The object the event is about (`FAN_EVENT_INFO_TYPE_FID`).
+    Fid,
+    /// The directory containing the object, and the object's name
+    /// (`FAN_EVENT_INFO_TYPE_DFID_NAME`).
+    DirFidName,
+    /// The directory containing the object (`FAN_EVENT_INFO_TYPE_DFID`).
+    DirFid,
+}
+
+/// A file handle reported by a fanotify instance in a FID reporting mode.
+///
+/// The handle can be opened with `open_by_handle_at(2)` on any file descriptor from the
+/// filesystem identified by `fsid`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FanotifyFid {
+    /// What this handle identifies.
+    pub info_type: FidInfoType,
+    /// The id of the filesystem containing the object, as returned by `statfs(2)`.
+    pub fsid: [i32; 2],
+    /// The type of the file handle.
+    pub handle_type: i32,
+    /// The opaque file handle.
+    pub handle: Vec<u8>,
+    /// The name of the object within the directory, for `FidInfoType::DirFidName` records.
+    pub name: Option<OsString>,
+}
+
+/// A single fanotify event.
+///
+/// If the event carries a file descriptor, it is closed when the event is dropped.
+#[derive(Debug)]
+pub struct FanotifyEvent {
+    mask: MaskFlags,
+    fd: Option<RawFd>,
+    pid: Pid,
+    fids: Vec<FanotifyFid>,
+}
+
+impl FanotifyEvent {
+    /// The events that occurred.
+    pub fn mask(&self) -> MaskFlags {
+        self.mask
+    }
+
+    /// An open file descriptor for the object the event is about.
+    ///
+    /// This is `None` in FID reporting modes and for queue overflow events.
+    pub fn fd(&self) -> Option<RawFd> {
+        self.fd
+    }
+
+    /// The process (or thread, with `FAN_REPORT_TID`) that caused the event.
+    pub fn pid(&self) -> Pid {
+        self.pid
+    }
+
+    /// The file handles reported in FID reporting modes.
+    pub fn fids(&self) -> &[FanotifyFid] {
+        &self.fids
+    }
+
+    // Parse the events contained in a buffer filled by reading from a fanotify file
+    // descriptor.
+    //
+    // The returned events own the file descriptors contained in the buffer, so the buffer must
+    // come from the kernel. An event of an unknown metadata version, or a truncated event at the
+    // end of the buffer, ends parsing; the file descriptors of the remaining events are closed.
+    fn parse_buffer(buffer: &[u8]) -> Vec<FanotifyEvent> {
+        let metadata_size = size_of::<libc::fanotify_event_metadata>();
+        let mut events = Vec::new();
+        let mut offset = 0;
+
+        while buffer.len() - offset >= metadata_size {
+            let metadata = unsafe {
+                ptr::read_unaligned(buffer[offset..].as_ptr() as *const libc::fanotify_event_metadata)
+            };
+            let event_len = metadata.event_len as usize;
+            let metadata_len = metadata.metadata_len as usize;
+            if metadata.vers != libc::FANOTIFY_METADATA_VERSION ||
+               event_len < metadata_len || metadata_len < metadata_size ||
+               buffer.len() - offset < event_len {
+                close_event_fds(&buffer[offset..]);
+                break;
+            }
+
+            let info = &buffer[offset + metadata_len..offset + event_len];
+            events.push(FanotifyEvent {
+                mask: MaskFlags::from_bits_truncate(metadata.mask),
+                fd: if metadata.fd >= 0 { Some(metadata.fd) } else { None },
+                pid: Pid::from_raw(metadata.pid),
+                fids: parse_fids(info),
+            });
+
+            offset += event_len;
+        }
+
+        events
+    }
+}
+
+impl Drop for FanotifyEvent {
+    fn drop(&mut self) {
+        if let Some(fd) = self.fd {
+            let _ = close(fd);
+        }
+    }
+}
+
+// Close the file descriptors of events that can't be parsed, so that they don't leak. The walk
+// stops at the first event whose length doesn't make sense.
+fn close_event_fds(mut buffer: &[u8]) {
+    let metadata_size = size_of::<libc::fanotify_event_metadata>();
+
+    while buffer.len() >= metadata_size {
+        let metadata = unsafe {
+            ptr::read_unaligned(buffer.as_ptr() as *const libc::fanotify_event_metadata)
+        };
+        if metadata.metadata_len as usize >= metadata_size && metadata.fd >= 0 {
+            let _ = close(metadata.fd);
+        }
+        let event_len = metadata.event_len as usize;
+        if event_len < metadata_size || event_len > buffer.len() {
+            break;
+        }
+        buffer = &buffer[event_len..];
+    }
+}
+
+// Parse the information records following the metadata of an event. Records of unknown types
+// are skipped.
+fn parse_fids(mut info: &[u8]) -> Vec<FanotifyFid> {
+    let header_size = size_of::<libc::fanotify_event_info_header>();
+    let fid_size = size_of::<libc::fanotify_event_info_fid>();
+    // struct file_handle { unsigned int handle_bytes; int handle_type; unsigned char f_handle[]; }
+    let file_handle_size = 8;
+    let mut fids = Vec::new();
+
+    while info.len() >= header_size {
+        let header = unsafe {
+            ptr::read_unaligned(info.as_ptr() as *const libc::fanotify_event_info_header)
+        };
+        let len = header.len as usize;
+        if len < header_size || len > info.len() {
+            break;
+        }
+        let record = &info[..len];
+        info = &info[len..];
+
+        let info_type = match header.info_type {
+            libc::FAN_EVENT_INFO_TYPE_FID => FidInfoType::Fid,
+            libc::FAN_EVENT_INFO_TYPE_DFID_NAME => FidInfoType::DirFidName,
+            libc::FAN_EVENT_INFO_TYPE_DFID => FidInfoType::DirFid,
+            _ => continue,
+        };
+        if record.len() < fid_size + file_handle_size {
+            continue;
+        }
+
+        let fid = unsafe {
+            ptr::read_unaligned(record.as_ptr() as *const libc::fanotify_event_info_fid)
+        };
+        let handle = &record[fid_size..];
+        let (handle_bytes, handle_type) = unsafe {
+            (ptr::read_unaligned(handle.as_ptr() as *const u32) as usize,
+             ptr::read_unaligned(handle[4..].as_ptr() as *const i32))
+        };
+        if handle.len() < file_handle_size + handle_bytes {
+            continue;
+        }
+        let f_handle = &handle[file_handle_size..file_handle_size + handle_bytes];
+
+        let name = if info_type == FidInfoType::DirFidName {
+            let raw = &handle[file_handle_size + handle_bytes..];
+            let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+            Some(OsStr::from_bytes(&raw[..end]).to_owned())
+        } else {
+            None
+        };
+
+        fids.push(FanotifyFid {
+            info_type,
+            fsid: fid.fsid.val,
+            handle_type,
+            handle: f_handle.to_vec(),
+            name,
+        });
+    }
+
+    fids
+}
+
+/// The decision for a permission event.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Response {
+    /// Allow the access.
+    Allow,
+    /// Deny the access. The process trying to access the file gets `EPERM`.
+    Deny,
+}
+
+/// A fanotify instance. It is closed when dropped.
+#[derive(Debug, Eq, Hash, PartialEq)]
+pub struct Fanotify(RawFd);
+
+impl Fanotify {
+    /// Initialize a new fanotify instance.
+    ///
+    /// `event_f_flags` are the status flags of the file descriptors opened for events, such as
+    /// `O_RDONLY`, `O_LARGEFILE` or `O_CLOEXEC`.
+    ///
+    /// For more information see, [fanotify_init(2)](http://man7.org/linux/man-pages/man2/fanotify_init.2.html).
+    pub fn init(flags: InitFlags, event_f_flags: OFlag) -> Result<Fanotify> {
+        let res = unsafe { libc::fanotify_init(flags.bits(), event_f_flags.bits() as c_uint) };
+
+        Errno::result(res).map(Fanotify)
+    }
+
+    /// Add, remove or modify a mark.
+    ///
+    /// The object to mark is `path` relative to `dirfd`, which defaults to the current
+    /// working directory. If `path` is `None`, `dirfd` itself is marked.
+    ///
+    /// For more information see, [fanotify_mark(2)](http://man7.org/linux/man-pages/man2/fanotify_mark.2.html).
+    pub fn mark<P: ?Sized + NixPath>(&self, flags: MarkFlags, mask: MaskFlags,
+                                     dirfd: Option<RawFd>, path: Option<&P>) -> Result<()> {
+        let dirfd = dirfd.unwrap_or(libc::AT_FDCWD);
+        let res = match path {
+            Some(path) => path.with_nix_path(|cstr| unsafe {
+                libc::fanotify_mark(self.0, flags.bits(), mask.bits(), dirfd, cstr.as_ptr())
+            })?,
+            None => unsafe {
+                libc::fanotify_mark(self.0, flags.bits(), mask.bits(), dirfd, ptr::null())
+            },
+        };
+
+        Errno::result(res).map(drop)
+    }
+
+    /// Reads a collection of events from the fanotify file descriptor. This call can either be
+    /// blocking or non blocking depending on whether `FAN_NONBLOCK` was set at initialization.
+    ///
+    /// Returns as many events as available. If the call was non blocking and no events could be
+    /// read then the `EAGAIN` error is returned.
+    pub fn read_events(&self) -> Result<Vec<FanotifyEvent>> {
+        // The buffer is made of `u64`s so that the event metadata is properly aligned.
+        const BUFSIZ: usize = 4096;
+        let mut buffer = [0u64; BUFSIZ / 8];
+        let buffer = unsafe {
+            ::std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, BUFSIZ)
+        };
+
+        let nread = read(self.0, buffer)?;
+
+        Ok(FanotifyEvent::parse_buffer(&buffer[..nread]))
+    }
+
+    /// Allow or deny the access that caused a permission event.
+    pub fn respond(&self, event: &FanotifyEvent, response: Response) -> Result<()> {
+        let fd = event.fd.ok_or_else(Error::invalid_argument)?;
+        let response = libc::fanotify_response {
+            fd,
+            response: match response {
+                Response::Allow => libc::FAN_ALLOW,
+                Response::Deny => libc::FAN_DENY,
+            },
+        };
+        let bytes = unsafe {
+            ::std::slice::from_raw_parts(&response as *const _ as *const u8,
+                                         size_of::<libc::fanotify_response>())
+        };
+
+        write(self.0, bytes).map(drop)
+    }
+}
+
+impl Drop for Fanotify {
+    fn drop(&mut self) {
+        let _ = close(self.0);
+    }
+}
+
+impl AsRawFd for Fanotify {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unistd::pipe2;
+
+    fn metadata(vers: u8, fd: RawFd) -> libc::fanotify_event_metadata {
+        let size = size_of::<libc::fanotify_event_metadata>();
+        libc::fanotify_event_metadata {
+            event_len: size as u32,
+            vers,
+            reserved: 0,
+            metadata_len: size as u16,
+            mask: libc::FAN_OPEN,
+            fd,
+            pid: 1,
+        }
+    }
+
+    #[test]
+    fn parse_buffer_closes_unparsed_fds() {
+        let size = size_of::<libc::fanotify_event_metadata>();
+        let (rd1, wr1) = pipe2(OFlag::O_NONBLOCK).unwrap();
+        let (rd2, wr2) = pipe2(OFlag::O_NONBLOCK).unwrap();
+
+        let mut buffer = vec![0u8; 3 * size];
+        let version = libc::FANOTIFY_METADATA_VERSION;
+        let events = [metadata(version, libc::FAN_NOFD), metadata(version + 1, wr1),
+                      metadata(version, wr2)];
+        for (i, event) in events.iter().enumerate() {
+            unsafe {
+                ptr::write_unaligned(buffer[i * size..].as_mut_ptr()
+                                     as *mut libc::fanotify_event_metadata, *event);
+            }
+        }
+
+        let events = FanotifyEvent::parse_buffer(&buffer);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].fd(), None);
+
+        // Both write ends were closed, so the pipes report end of file.
+        let mut buf = [0u8; 1];
+        assert_eq!(read(rd1, &mut buf), Ok(0));
+        assert_eq!(read(rd2, &mut buf), Ok(0));
+        close(rd1).unwrap();
+        close(rd2).unwrap();
+    }
+}
@@ -0,0 +1,290 @@
+//! Monitoring API for filesystem events.
+//!
+//! Inotify is a Linux-only API to monitor filesystems events.
+//!
+//! For more documentation, please read [inotify(7)](http://man7.org/linux/man-pages/man7/inotify.7.html).
+//!
+//! # Examples
+//!
+//! Monitor all events happening in directory "test":
+//! ```no_run
+//! # use nix::sys::inotify::{AddWatchFlags,InitFlags,Inotify};
+//! #
+//! // We create a new inotify instance.
+//! let instance = Inotify::init(InitFlags::empty()).unwrap();
+//!
+//! // We add a new watch on directory "test" for all events.
+//! let wd = instance.add_watch("test", AddWatchFlags::IN_ALL_EVENTS).unwrap();
+//!
+//! loop {
+//!     // We read from our inotify instance for events.
+//!     let events = instance.read_events().unwrap();
+//!     println!("Events: {:?}", events);
+//! }
+//! ```
+
+use libc::{self, c_int};
+use std::ffi::{OsStr, OsString};
+use std::mem::size_of;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::ptr;
+use unistd::{close, read};
+use {NixPath, Result};
+use errno::Errno;
+
+libc_bitflags! {
+    /// Configuration options for [`Inotify::add_watch`](struct.Inotify.html#method.add_watch).
+    pub struct AddWatchFlags: u32 {
+        /// File was accessed.
+        IN_ACCESS;
+        /// File was modified.
+        IN_MODIFY;
+        /// Metadata changed.
+        IN_ATTRIB;
+        /// Writable file was closed.
+        IN_CLOSE_WRITE;
+        /// Nonwritable file was closed.
+        IN_CLOSE_NOWRITE;
+        /// File was opened.
+        IN_OPEN;
+        /// File was moved out of the watched directory.
+        IN_MOVED_FROM;
+        /// File was moved into the watched directory.
+        IN_MOVED_TO;
+        /// File or directory was created in the watched directory.
+        IN_CREATE;
+        /// File or directory was deleted from the watched directory.
+        IN_DELETE;
+        /// The watched file or directory was deleted.
+        IN_DELETE_SELF;
+        /// The watched file or directory was moved.
+        IN_MOVE_SELF;
+
+        /// The filesystem containing the watched object was unmounted.
+        IN_UNMOUNT;
+        /// The event queue overflowed.
+        IN_Q_OVERFLOW;
+        /// The watch was removed.
+        IN_IGNORED;
+
+        /// Combination of `IN_CLOSE_WRITE` and `IN_CLOSE_NOWRITE`.
+        IN_CLOSE;
+        /// Combination of `IN_MOVED_FROM` and `IN_MOVED_TO`.
+        IN_MOVE;
+
+        /// Only watch the path if it is a directory.
+        IN_ONLYDIR;
+        /// Don't follow symlinks.
+        IN_DONT_FOLLOW;
+        /// Don't report events for children after they were unlinked from the watched
+        /// directory.
+        IN_EXCL_UNLINK;
+
+        /// Fail with `EEXIST` if the path is already being watched.
+        IN_MASK_CREATE;
+        /// Add the events to an existing watch instead of replacing its mask.
+        IN_MASK_ADD;
+        /// The event is about a directory.
+        IN_ISDIR;
+        /// Only report the first event and remove the watch afterwards.
+        IN_ONESHOT;
+
+        /// All of the events.
+        IN_ALL_EVENTS;
+    }
+}
+
+libc_bitflags! {
+    /// Configuration options for [`Inotify::init`](struct.Inotify.html#method.init).
+    pub struct InitFlags: c_int {
+        /// Set the `FD_CLOEXEC` flag on the file descriptor.
+        IN_CLOEXEC;
+        /// Set the `O_NONBLOCK` flag on the open file description referred to by the new file
+        /// descriptor.
+        IN_NONBLOCK;
+    }
+}
+
+/// An inotify instance. It is closed when dropped.
+#[derive(Debug, Eq, Hash, PartialEq)]
+pub struct Inotify(RawFd);
+
+/// This object is returned when you create a new watch on an inotify instance. It is then
+/// returned as part of an event and can be used to remove the watch with `rm_watch`.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct WatchDescriptor(i32);
+
+/// A single inotify event.
+///
+/// For more documentation see, [inotify(7)](http://man7.org/linux/man-pages/man7/inotify.7.html).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InotifyEvent {
+    /// Watch descriptor. This field corresponds to the watch descriptor you were issued when
+    /// calling `add_watch`. It allows you to know which watch this event comes from.
+    pub wd: WatchDescriptor,
+    /// Event mask. This field is a bitfield describing the exact event that occured.
+    pub mask: AddWatchFlags,
+    /// This cookie is a number that allows you to connect related events. For now only
+    /// `IN_MOVED_FROM` and `IN_MOVED_TO` can be connected.
+    pub cookie: u32,
+    /// Filename. This field exists only if the event was triggered for a file inside the watched
+    /// directory.
+    pub name: Option<OsString>,
+}
+
+impl InotifyEvent {
+    // Parse the events contained in a buffer filled by reading from an inotify file
+    // descriptor.
+    //
+    // Each `struct inotify_event` in the buffer is followed by a NUL-padded name of `len`
+    // bytes. A truncated event at the end of the buffer is ignored.
+    fn parse_buffer(buffer: &[u8]) -> Vec<InotifyEvent> {
+        let header_size = size_of::<libc::inotify_event>();
+        let mut events = Vec::new();
+        let mut offset = 0;
+
+        while buffer.len() - offset >= header_size {
+            let event = unsafe {
+                ptr::read_unaligned(buffer[offset..].as_ptr() as *const libc::inotify_event)
+            };
+            let len = event.len as usize;
+            if buffer.len() - offset - header_size < len {
+                break;
+            }
+
+            let name = if len == 0 {
+                None
+            } else {
+                let raw = &buffer[offset + header_size..offset + header_size + len];
+                let end = raw.iter().position(|&b| b == 0).unwrap_or(len);
+                Some(OsStr::from_bytes(&raw[..end]).to_owned())
+            };
+
+            events.push(InotifyEvent {
+                wd: WatchDescriptor(event.wd),
+                mask: AddWatchFlags::from_bits_truncate(event.mask),
+                cookie: event.cookie,
+                name,
+            });
+
+            offset += header_size + len;
+        }
+
+        events
+    }
+}
+
+impl Inotify {
+    /// Initialize a new inotify instance.
+    ///
+    /// Returns a Result containing an inotify instance.
+    ///
+    /// For more information see, [inotify_init(2)](http://man7.org/linux/man-pages/man2/inotify_init.2.html).
+    pub fn init(flags: InitFlags) -> Result<Inotify> {
+        let res = Errno::result(unsafe { libc::inotify_init1(flags.bits()) });
+
+        res.map(Inotify)
+    }
+
+    /// Adds a new watch on the target file or directory.
+    ///
+    /// Returns a watch descriptor. This is not a File Descriptor!
+    ///
+    /// For more information see, [inotify_add_watch(2)](http://man7.org/linux/man-pages/man2/inotify_add_watch.2.html).
+    pub fn add_watch<P: ?Sized + NixPath>(&self, path: &P, mask: AddWatchFlags)
+        -> Result<WatchDescriptor>
+    {
+        let res = path.with_nix_path(|cstr| {
+            unsafe { libc::inotify_add_watch(self.0, cstr.as_ptr(), mask.bits()) }
+        })?;
+
+        Errno::result(res).map(WatchDescriptor)
+    }
+
+    /// Removes an existing watch using the watch descriptor returned by `add_watch`.
+    ///
+    /// Returns an `EINVAL` error if the watch descriptor is invalid.
+    ///
+    /// For more information see, [inotify_rm_watch(2)](http://man7.org/linux/man-pages/man2/inotify_rm_watch.2.html).
+    pub fn rm_watch(&self, wd: WatchDescriptor) -> Result<()> {
+        let res = unsafe { libc::inotify_rm_watch(self.0, wd.0 as _) };
+
+        Errno::result(res).map(drop)
+    }
+
+    /// Reads a collection of events from the inotify file descriptor. This call can either be
+    /// blocking or non blocking depending on whether `IN_NONBLOCK` was set at initialization.
+    ///
+    /// Returns as many events as available. If the call was non blocking and no events could be
+    /// read then the `EAGAIN` error is returned.
+    pub fn read_events(&self) -> Result<Vec<InotifyEvent>> {
+        // Large enough for at least one event with a name of `NAME_MAX` bytes. The buffer is
+        // made of `u32`s so that the event headers are properly aligned.
+        const BUFSIZ: usize = 4096;
+        let mut buffer = [0u32; BUFSIZ / 4];
+        let buffer = unsafe {
+            ::std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, BUFSIZ)
+        };
+
+        let nread = read(self.0, buffer)?;
+
+        Ok(InotifyEvent::parse_buffer(&buffer[..nread]))
+    }
+}
+
+impl Drop for Inotify {
+    fn drop(&mut self) {
+        let _ = close(self.0);
+    }
+}
+
+impl AsRawFd for Inotify {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+impl WatchDescriptor {
+    /// Create a `WatchDescriptor` from the raw value returned by `inotify_add_watch(2)`.
+    pub fn from_raw(wd: c_int) -> WatchDescriptor {
+        WatchDescriptor(wd)
+    }
+
+    /// The raw watch descriptor.
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_buffer() {
+        let header_size = size_of::<libc::inotify_event>();
+        let mut buffer = vec![0u8; 2 * header_size + 16];
+
+        let first = libc::inotify_event { wd: 1, mask: libc::IN_CREATE, cookie: 0, len: 16 };
+        let second = libc::inotify_event { wd: 2, mask: libc::IN_DELETE_SELF, cookie: 7, len: 0 };
+        unsafe {
+            ptr::write_unaligned(buffer.as_mut_ptr() as *mut libc::inotify_event, first);
+            ptr::write_unaligned(buffer.as_mut_ptr().offset((header_size + 16) as isize)
+                                 as *mut libc::inotify_event, second);
+        }
+        buffer[header_size..header_size + 3].copy_from_slice(b"foo");
+
+        let events = InotifyEvent::parse_buffer(&buffer);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].wd, WatchDescriptor(1));
+        assert_eq!(events[0].mask, AddWatchFlags::IN_CREATE);
+        assert_eq!(events[0].name, Some(OsString::from("foo")));
+        assert_eq!(events[1].wd, WatchDescriptor(2));
+        assert_eq!(events[1].cookie, 7);
+        assert_eq!(events[1].name, None);
+
+        // A truncated event is ignored.
+        assert_eq!(InotifyEvent::parse_buffer(&buffer[..header_size + 8]).len(), 0);
+    }
+}
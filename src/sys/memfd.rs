@@ -8,6 +8,13 @@ libc_bitflags!(
     pub struct MemFdCreateFlag: libc::c_uint {
         MFD_CLOEXEC;
         MFD_ALLOW_SEALING;
+        /// Back the file with huge pages (since Linux 4.14)
+        MFD_HUGETLB;
+        /// Make the file non-executable and seal that (since Linux 6.3).
+        /// See [`features::Feature::MemfdNoexecSeal`](../../features/enum.Feature.html).
+        MFD_NOEXEC_SEAL;
+        /// Allow the file to be executable (since Linux 6.3)
+        MFD_EXEC;
     }
 );
 
@@ -20,6 +20,12 @@ pub mod event;
 #[cfg(target_os = "linux")]
 pub mod eventfd;
 
+#[cfg(target_os = "linux")]
+pub mod fanotify;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod inotify;
+
 #[cfg(any(target_os = "android",
           target_os = "dragonfly",
           target_os = "freebsd",
@@ -83,6 +89,12 @@ pub mod termios;
 
 pub mod time;
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod timerfd;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod timer;
+
 pub mod uio;
 
 pub mod utsname;
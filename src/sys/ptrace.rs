@@ -5,7 +5,7 @@ use {Error, Result};
 use errno::Errno;
 use libc::{self, c_void, c_long, siginfo_t};
 use ::unistd::Pid;
-use sys::signal::Signal;
+use sys::signal::{SigInfo, Signal};
 
 
 cfg_if! {
@@ -234,6 +234,11 @@ pub fn getsiginfo(pid: Pid) -> Result<siginfo_t> {
     ptrace_get_data::<siginfo_t>(Request::PTRACE_GETSIGINFO, pid)
 }
 
+/// Get siginfo as with `ptrace(PTRACE_GETSIGINFO,...)`, decoded into a `SigInfo`
+pub fn getsiginfo_typed(pid: Pid) -> Result<SigInfo> {
+    getsiginfo(pid).and_then(|info| SigInfo::from_siginfo(&info))
+}
+
 /// Set siginfo as with `ptrace(PTRACE_SETSIGINFO,...)`
 pub fn setsiginfo(pid: Pid, sig: &siginfo_t) -> Result<()> {
     let ret = unsafe{
@@ -13,6 +13,12 @@ use std::ptr;
 
 #[cfg(not(target_os = "openbsd"))]
 pub use self::sigevent::*;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use self::altstack::*;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use self::realtime::*;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use self::siginfo::*;
 
 libc_enum!{
     // Currently there is only one definition of c_int in libc, as well as only one
@@ -442,6 +448,42 @@ pub unsafe fn sigaction(signal: Signal, sigaction: &SigAction) -> Result<SigActi
     Errno::result(res).map(|_| SigAction { sigaction: oldact })
 }
 
+/// A signal action that is installed for as long as the guard lives.
+///
+/// When the guard is dropped, the action that was installed before is restored.
+#[allow(missing_debug_implementations)]
+pub struct SigActionGuard {
+    signal: Signal,
+    previous: SigAction,
+}
+
+impl SigActionGuard {
+    /// Installs `action` for `signal` like `sigaction`.
+    ///
+    /// # Safety
+    ///
+    /// See `sigaction`.
+    pub unsafe fn new(signal: Signal, action: &SigAction) -> Result<SigActionGuard> {
+        sigaction(signal, action).map(|previous| SigActionGuard { signal, previous })
+    }
+
+    /// The signal whose action was replaced.
+    pub fn signal(&self) -> Signal {
+        self.signal
+    }
+
+    /// The action that will be restored when the guard is dropped.
+    pub fn previous(&self) -> &SigAction {
+        &self.previous
+    }
+}
+
+impl Drop for SigActionGuard {
+    fn drop(&mut self) {
+        let _ = unsafe { sigaction(self.signal, &self.previous) };
+    }
+}
+
 /// Manages the signal mask (set of blocked signals) for the calling thread.
 ///
 /// If the `set` parameter is `Some(..)`, then the signal mask will be updated with the signal set.
@@ -513,6 +555,20 @@ pub fn raise(signal: Signal) -> Result<()> {
     Errno::result(res).map(drop)
 }
 
+/// Suspends execution of the calling thread until one of the signals in `set` becomes pending,
+/// and returns information about the accepted signal.
+///
+/// The signals in `set` should be blocked beforehand.
+///
+/// For more information, see [sigwaitinfo(2)](http://man7.org/linux/man-pages/man2/sigwaitinfo.2.html).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn sigwaitinfo(set: &SigSet) -> Result<SigInfo> {
+    let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+    let res = unsafe { libc::sigwaitinfo(&set.sigset, &mut info) };
+
+    Errno::result(res).and_then(|_| SigInfo::from_siginfo(&info))
+}
+
 
 #[cfg(target_os = "freebsd")]
 pub type type_of_thread_id = libc::lwpid_t;
@@ -658,6 +714,783 @@ mod sigevent {
     }
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod altstack {
+    use libc::{self, c_int, c_void};
+    use std::cmp;
+    use std::marker::PhantomData;
+    use std::mem;
+    use std::os::unix::io::{AsRawFd, RawFd};
+    use std::ptr;
+    use std::sync::atomic::{AtomicIsize, Ordering, ATOMIC_ISIZE_INIT};
+    use fcntl::OFlag;
+    use sys::mman::{mmap, munmap, MapFlags, ProtFlags};
+    use unistd::{self, sysconf, SysconfVar};
+    use {Error, Result};
+    use errno::Errno;
+    use super::{SaFlags, SigAction, SigActionGuard, SigHandler, SigSet, Signal};
+
+    /// The smallest usable alternate signal stack, the traditional `SIGSTKSZ`.
+    const MIN_ALT_STACK_SIZE: usize = 8192;
+
+    fn page_size() -> usize {
+        match sysconf(SysconfVar::PAGE_SIZE) {
+            Ok(Some(size)) => size as usize,
+            _ => 4096,
+        }
+    }
+
+    /// An alternate stack for signal handlers, mapped with a guard page below it.
+    ///
+    /// Signal handlers only run on the stack if it is installed with `sigaltstack` and if they
+    /// were registered with `SA_ONSTACK`. This allows handling a `SIGSEGV` caused by a stack
+    /// overflow, and the guard page turns an overflow of the alternate stack itself into another
+    /// fault instead of silently corrupting memory. The stack is unmapped when dropped.
+    #[derive(Debug)]
+    pub struct AltStack {
+        /// The start of the mapping, which is the guard page.
+        base: *mut c_void,
+        /// The length of the mapping, including the guard page.
+        len: usize,
+        guard: usize,
+    }
+
+    impl AltStack {
+        /// Maps a new alternate signal stack of at least `size` bytes.
+        ///
+        /// The size is rounded up to whole pages and to at least 8 KiB.
+        pub fn new(size: usize) -> Result<AltStack> {
+            let page = page_size();
+            let size = (cmp::max(size, MIN_ALT_STACK_SIZE) + page - 1) / page * page;
+            let len = size + page;
+            let base = unsafe {
+                mmap(ptr::null_mut(), len, ProtFlags::PROT_NONE,
+                     MapFlags::MAP_PRIVATE | MapFlags::MAP_ANONYMOUS | MapFlags::MAP_STACK, -1, 0)?
+            };
+            let stack = AltStack { base, len, guard: page };
+            let res = unsafe {
+                libc::mprotect(stack.stack_ptr(), size, libc::PROT_READ | libc::PROT_WRITE)
+            };
+            Errno::result(res).map(|_| stack)
+        }
+
+        /// The usable size of the stack, without the guard page.
+        pub fn size(&self) -> usize {
+            self.len - self.guard
+        }
+
+        fn stack_ptr(&self) -> *mut c_void {
+            (self.base as usize + self.guard) as *mut c_void
+        }
+    }
+
+    // The mapping is owned by the `AltStack`, so it can be moved to the thread that installs it.
+    unsafe impl Send for AltStack {}
+
+    impl Drop for AltStack {
+        fn drop(&mut self) {
+            let _ = unsafe { munmap(self.base, self.len) };
+        }
+    }
+
+    /// An alternate signal stack that is installed for the calling thread for as long as the
+    /// guard lives.
+    ///
+    /// When the guard is dropped, the alternate stack that was installed before is restored. To
+    /// keep the stack installed for the lifetime of the thread, `mem::forget` both the guard and
+    /// the `AltStack`.
+    #[derive(Debug)]
+    pub struct AltStackGuard<'a> {
+        previous: libc::stack_t,
+        // The alternate stack is a property of the thread that installed it.
+        _stack: PhantomData<(&'a AltStack, *const ())>,
+    }
+
+    impl<'a> Drop for AltStackGuard<'a> {
+        fn drop(&mut self) {
+            unsafe { libc::sigaltstack(&self.previous, ptr::null_mut()) };
+        }
+    }
+
+    /// Installs `stack` as the alternate signal stack of the calling thread.
+    ///
+    /// Fails with `EPERM` if the calling thread is currently running on its alternate signal
+    /// stack.
+    ///
+    /// For more documentation, please read [sigaltstack(2)](http://man7.org/linux/man-pages/man2/sigaltstack.2.html).
+    pub fn sigaltstack<'a>(stack: &'a AltStack) -> Result<AltStackGuard<'a>> {
+        let new = libc::stack_t {
+            ss_sp: stack.stack_ptr(),
+            ss_flags: 0,
+            ss_size: stack.size(),
+        };
+        let mut previous: libc::stack_t = unsafe { mem::zeroed() };
+        let res = unsafe { libc::sigaltstack(&new, &mut previous) };
+
+        Errno::result(res).map(|_| AltStackGuard { previous, _stack: PhantomData })
+    }
+
+    /// The write end of the pipe of the current `SignalPipe`, plus one so that zero means none.
+    static SIGNAL_PIPE_FD: AtomicIsize = ATOMIC_ISIZE_INIT;
+
+    extern fn signal_pipe_handler(signum: c_int) {
+        let fd = SIGNAL_PIPE_FD.load(Ordering::SeqCst) - 1;
+        if fd < 0 {
+            return;
+        }
+        // Only async-signal-safe functions may be called here. If the pipe is full the signal
+        // is dropped, which is fine because the reader has not caught up with earlier ones yet.
+        unsafe {
+            let errno = errno_location();
+            let saved = *errno;
+            let byte = signum as u8;
+            libc::write(fd as RawFd, &byte as *const u8 as *const c_void, 1);
+            *errno = saved;
+        }
+    }
+
+    #[cfg(target_os = "android")]
+    unsafe fn errno_location() -> *mut c_int {
+        libc::__errno()
+    }
+
+    #[cfg(target_os = "linux")]
+    unsafe fn errno_location() -> *mut c_int {
+        libc::__errno_location()
+    }
+
+    /// Turns signals into readable events on a file descriptor.
+    ///
+    /// This installs a handler for the given signals that only writes the signal number into a
+    /// non-blocking pipe, which is async-signal-safe. The signals can then be read from the pipe
+    /// at leisure, for example after `poll` reported it as readable. When dropped, the previous
+    /// handlers are restored.
+    ///
+    /// Only one `SignalPipe` can exist at a time in a process. Unlike `SignalFd`, this does not
+    /// require the signals to be blocked in every thread.
+    #[allow(missing_debug_implementations)]
+    pub struct SignalPipe {
+        read: RawFd,
+        write: RawFd,
+        guards: Vec<SigActionGuard>,
+    }
+
+    impl SignalPipe {
+        /// Starts redirecting `signals` into a new pipe.
+        ///
+        /// Fails with `EBUSY` if another `SignalPipe` exists.
+        pub fn new(signals: &[Signal]) -> Result<SignalPipe> {
+            let (read, write) = unistd::pipe2(OFlag::O_CLOEXEC | OFlag::O_NONBLOCK)?;
+            if SIGNAL_PIPE_FD.compare_exchange(0, write as isize + 1, Ordering::SeqCst,
+                                               Ordering::SeqCst).is_err() {
+                let _ = unistd::close(read);
+                let _ = unistd::close(write);
+                return Err(Error::Sys(Errno::EBUSY));
+            }
+            let mut pipe = SignalPipe { read, write, guards: Vec::with_capacity(signals.len()) };
+
+            let action = SigAction::new(SigHandler::Handler(signal_pipe_handler),
+                                        SaFlags::SA_RESTART, SigSet::empty());
+            for &signal in signals {
+                // Safe because the handler is async-signal-safe.
+                pipe.guards.push(unsafe { SigActionGuard::new(signal, &action)? });
+            }
+            Ok(pipe)
+        }
+
+        /// Reads the next signal that was received.
+        ///
+        /// Returns `None` if no signal is pending.
+        pub fn read(&self) -> Result<Option<Signal>> {
+            let mut byte = [0u8];
+            match unistd::read(self.read, &mut byte) {
+                Ok(1) => Signal::from_c_int(c_int::from(byte[0])).map(Some),
+                Ok(_) => Err(Error::Sys(Errno::EIO)),
+                Err(Error::Sys(Errno::EAGAIN)) => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+    }
+
+    impl Drop for SignalPipe {
+        fn drop(&mut self) {
+            // Restore the previous handlers before the pipe goes away.
+            self.guards.clear();
+            SIGNAL_PIPE_FD.store(0, Ordering::SeqCst);
+            let _ = unistd::close(self.read);
+            let _ = unistd::close(self.write);
+        }
+    }
+
+    impl AsRawFd for SignalPipe {
+        /// The read end of the pipe.
+        fn as_raw_fd(&self) -> RawFd {
+            self.read
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod realtime {
+    use libc::{self, c_int, c_void};
+    use std::fmt;
+    use unistd::Pid;
+    use {Error, Result};
+    use errno::Errno;
+    use super::{SigSet, Signal};
+
+    /// A realtime signal, between `SIGRTMIN` and `SIGRTMAX`.
+    ///
+    /// The range of realtime signals is only known at runtime, because the C library reserves
+    /// some of them for its own use.
+    #[derive(Clone, Copy, Eq, Hash, PartialEq, Ord, PartialOrd)]
+    pub struct RealtimeSignal(c_int);
+
+    impl RealtimeSignal {
+        /// Returns the realtime signal `SIGRTMIN + n`.
+        ///
+        /// Fails with `EINVAL` if it is greater than `SIGRTMAX`.
+        pub fn new(n: c_int) -> Result<RealtimeSignal> {
+            if n < 0 {
+                return Err(Error::invalid_argument());
+            }
+            RealtimeSignal::from_c_int(libc::SIGRTMIN().saturating_add(n))
+        }
+
+        /// Converts a raw signal number into a realtime signal.
+        ///
+        /// Fails with `EINVAL` if it is not between `SIGRTMIN` and `SIGRTMAX`.
+        pub fn from_c_int(signum: c_int) -> Result<RealtimeSignal> {
+            if libc::SIGRTMIN() <= signum && signum <= libc::SIGRTMAX() {
+                Ok(RealtimeSignal(signum))
+            } else {
+                Err(Error::invalid_argument())
+            }
+        }
+
+        /// `SIGRTMIN`, the realtime signal with the highest priority.
+        pub fn min() -> RealtimeSignal {
+            RealtimeSignal(libc::SIGRTMIN())
+        }
+
+        /// `SIGRTMAX`, the realtime signal with the lowest priority.
+        pub fn max() -> RealtimeSignal {
+            RealtimeSignal(libc::SIGRTMAX())
+        }
+
+        /// The offset `n` of this signal in `SIGRTMIN + n`.
+        pub fn offset(self) -> c_int {
+            self.0 - libc::SIGRTMIN()
+        }
+    }
+
+    impl fmt::Debug for RealtimeSignal {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "SIGRTMIN+{}", self.offset())
+        }
+    }
+
+    impl From<RealtimeSignal> for c_int {
+        fn from(signal: RealtimeSignal) -> c_int {
+            signal.0
+        }
+    }
+
+    /// Any signal number: either one of the standard signals or a realtime signal.
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum SigNum {
+        /// One of the standard signals.
+        Standard(Signal),
+        /// A realtime signal.
+        Realtime(RealtimeSignal),
+    }
+
+    impl SigNum {
+        /// Converts a raw signal number.
+        ///
+        /// Fails with `EINVAL` if it is neither a standard nor a realtime signal.
+        pub fn from_c_int(signum: c_int) -> Result<SigNum> {
+            match Signal::from_c_int(signum) {
+                Ok(signal) => Ok(SigNum::Standard(signal)),
+                Err(_) => RealtimeSignal::from_c_int(signum).map(SigNum::Realtime),
+            }
+        }
+    }
+
+    impl From<Signal> for SigNum {
+        fn from(signal: Signal) -> SigNum {
+            SigNum::Standard(signal)
+        }
+    }
+
+    impl From<RealtimeSignal> for SigNum {
+        fn from(signal: RealtimeSignal) -> SigNum {
+            SigNum::Realtime(signal)
+        }
+    }
+
+    impl From<SigNum> for c_int {
+        fn from(signum: SigNum) -> c_int {
+            match signum {
+                SigNum::Standard(signal) => signal as c_int,
+                SigNum::Realtime(signal) => signal.0,
+            }
+        }
+    }
+
+    impl PartialEq<Signal> for SigNum {
+        fn eq(&self, other: &Signal) -> bool {
+            *self == SigNum::Standard(*other)
+        }
+    }
+
+    impl PartialEq<RealtimeSignal> for SigNum {
+        fn eq(&self, other: &RealtimeSignal) -> bool {
+            *self == SigNum::Realtime(*other)
+        }
+    }
+
+    /// The value that is sent along with a queued signal.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum SigVal {
+        /// An integer, which the receiver finds in `sival_int`.
+        Int(c_int),
+        /// A pointer, which the receiver finds in `sival_ptr`.
+        Ptr(*mut c_void),
+    }
+
+    /// The C `union sigval`, of which `libc` only exposes the pointer member.
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    #[allow(missing_debug_implementations)]
+    union RawSigVal {
+        int: c_int,
+        ptr: *mut c_void,
+    }
+
+    impl From<SigVal> for libc::sigval {
+        fn from(value: SigVal) -> libc::sigval {
+            let raw = match value {
+                SigVal::Int(int) => {
+                    let mut raw = RawSigVal { ptr: ::std::ptr::null_mut() };
+                    raw.int = int;
+                    raw
+                }
+                SigVal::Ptr(ptr) => RawSigVal { ptr },
+            };
+            libc::sigval { sival_ptr: unsafe { raw.ptr } }
+        }
+    }
+
+    impl SigSet {
+        /// Adds a realtime signal to the set.
+        pub fn add_realtime(&mut self, signal: RealtimeSignal) {
+            unsafe { libc::sigaddset(&mut self.sigset, signal.0) };
+        }
+
+        /// Removes a realtime signal from the set.
+        pub fn remove_realtime(&mut self, signal: RealtimeSignal) {
+            unsafe { libc::sigdelset(&mut self.sigset, signal.0) };
+        }
+
+        /// Returns whether the set contains a realtime signal.
+        pub fn contains_realtime(&self, signal: RealtimeSignal) -> bool {
+            unsafe { libc::sigismember(&self.sigset, signal.0) == 1 }
+        }
+    }
+
+    /// Queues a signal together with a value to the process `pid`.
+    ///
+    /// Unlike standard signals, multiple instances of a realtime signal are queued and
+    /// delivered in order.
+    ///
+    /// For more information, see [sigqueue(3)](http://man7.org/linux/man-pages/man3/sigqueue.3.html).
+    pub fn sigqueue<S: Into<SigNum>>(pid: Pid, signal: S, value: SigVal) -> Result<()> {
+        let res = unsafe {
+            libc::sigqueue(pid.into(), c_int::from(signal.into()), value.into())
+        };
+
+        Errno::result(res).map(drop)
+    }
+
+    /// Sends a signal to the thread `tid` of the thread group `tgid`.
+    ///
+    /// For more information, see [tgkill(2)](http://man7.org/linux/man-pages/man2/tgkill.2.html).
+    pub fn tgkill<S: Into<SigNum>>(tgid: Pid, tid: Pid, signal: S) -> Result<()> {
+        let res = unsafe {
+            libc::syscall(libc::SYS_tgkill, libc::pid_t::from(tgid), libc::pid_t::from(tid),
+                          c_int::from(signal.into()))
+        };
+
+        Errno::result(res).map(drop)
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod siginfo {
+    use libc::{self, c_int, c_long};
+    use std::mem;
+    use std::os::unix::io::RawFd;
+    use std::time::Duration;
+    use sys::wait::WaitStatus;
+    use unistd::{getpid, getuid, Pid, Uid};
+    use {Error, Result};
+    use errno::Errno;
+    use super::{SigNum, SigSet, SigVal, Signal};
+
+    /// `si_code` of `SIGSYS` when it was sent by a seccomp filter.
+    const SYS_SECCOMP: c_int = 1;
+
+    /// Information about how a signal was generated, decoded from `si_code`.
+    ///
+    /// For more documentation, please read [sigaction(2)](http://man7.org/linux/man-pages/man2/sigaction.2.html).
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum SigInfo {
+        /// Sent by `kill(2)` or `raise(3)` from the process `pid` of the user `uid`.
+        Kill { signal: SigNum, pid: Pid, uid: Uid },
+        /// Sent by `tkill(2)` or `tgkill(2)` from the process `pid` of the user `uid`.
+        Tkill { signal: SigNum, pid: Pid, uid: Uid },
+        /// Sent by `sigqueue(3)` or a POSIX message queue from the process `pid` of the user
+        /// `uid`, together with `value`.
+        Queue { signal: SigNum, pid: Pid, uid: Uid, value: libc::intptr_t },
+        /// Sent by the expiration of the POSIX timer `id`, which expired `overrun` more times
+        /// since the signal was generated.
+        Timer { signal: SigNum, id: c_int, overrun: c_int, value: libc::intptr_t },
+        /// `SIGCHLD` sent because the child `pid` of the user `uid` changed its state.
+        Child { pid: Pid, uid: Uid, status: WaitStatus },
+        /// A hardware fault or trap (`SIGSEGV`, `SIGBUS`, `SIGILL`, `SIGFPE` or `SIGTRAP`) at
+        /// the address `addr`, with `code` describing the fault, such as `SEGV_MAPERR`.
+        Fault { signal: Signal, code: c_int, addr: usize },
+        /// An I/O event on the file descriptor `fd`, with `band` holding the `poll(2)` events.
+        Poll { signal: SigNum, band: c_long, fd: RawFd },
+        /// `SIGSYS` sent by a seccomp filter because of the system call `syscall` with the audit
+        /// architecture `arch`, made by the instruction at `call_addr`.
+        Sys { syscall: c_int, arch: u32, call_addr: usize },
+        /// Sent by the kernel for any other reason, with the raw `code`.
+        Kernel { signal: SigNum, code: c_int },
+    }
+
+    /// The fields of `siginfo_t` and `signalfd_siginfo` that `SigInfo` is decoded from.
+    struct Fields {
+        signo: c_int,
+        code: c_int,
+        pid: libc::pid_t,
+        uid: libc::uid_t,
+        status: c_int,
+        value: libc::intptr_t,
+        timer_id: c_int,
+        overrun: c_int,
+        addr: usize,
+        band: c_long,
+        fd: RawFd,
+        syscall: c_int,
+        arch: u32,
+        call_addr: usize,
+    }
+
+    /// The members of the `_sifields` union of the kernel's `siginfo_t`. Each one is preceded by
+    /// the three `int`s `si_signo`, `si_errno` and `si_code` and aligned like a pointer.
+    #[repr(C)]
+    struct Union<T> {
+        head: [c_int; 3],
+        fields: T,
+    }
+
+    #[repr(C)]
+    struct Kill {
+        _align: [usize; 0],
+        pid: libc::pid_t,
+        uid: libc::uid_t,
+        value: usize,
+    }
+
+    #[repr(C)]
+    struct Timer {
+        _align: [usize; 0],
+        tid: c_int,
+        overrun: c_int,
+        value: usize,
+    }
+
+    #[repr(C)]
+    struct Child {
+        _align: [usize; 0],
+        pid: libc::pid_t,
+        uid: libc::uid_t,
+        status: c_int,
+    }
+
+    #[repr(C)]
+    struct Fault {
+        addr: usize,
+    }
+
+    #[repr(C)]
+    struct Poll {
+        band: c_long,
+        fd: c_int,
+    }
+
+    #[repr(C)]
+    struct Sys {
+        call_addr: usize,
+        syscall: c_int,
+        arch: u32,
+    }
+
+    /// Reads the union member `T` of `info`.
+    unsafe fn union_member<T>(info: &libc::siginfo_t) -> &T {
+        debug_assert!(mem::size_of::<Union<T>>() <= mem::size_of::<libc::siginfo_t>());
+        &(*(info as *const libc::siginfo_t as *const Union<T>)).fields
+    }
+
+    impl SigInfo {
+        /// Decodes a `siginfo_t` as passed to `SA_SIGINFO` signal handlers or returned by
+        /// `sigwaitinfo` and `ptrace`.
+        ///
+        /// Fails with `EINVAL` if the signal number is not a valid `Signal`.
+        pub fn from_siginfo(info: &libc::siginfo_t) -> Result<SigInfo> {
+            // Every member of the union is plain data, so reading one that wasn't written only
+            // yields meaningless values that `decode` ignores.
+            let (kill, timer, child, fault, poll, sys) = unsafe {
+                (union_member::<Kill>(info), union_member::<Timer>(info),
+                 union_member::<Child>(info), union_member::<Fault>(info),
+                 union_member::<Poll>(info), union_member::<Sys>(info))
+            };
+            SigInfo::decode(Fields {
+                signo: info.si_signo,
+                code: info.si_code,
+                pid: kill.pid,
+                uid: kill.uid,
+                status: child.status,
+                value: kill.value as libc::intptr_t,
+                timer_id: timer.tid,
+                overrun: timer.overrun,
+                addr: fault.addr,
+                band: poll.band,
+                fd: poll.fd,
+                syscall: sys.syscall,
+                arch: sys.arch,
+                call_addr: sys.call_addr,
+            })
+        }
+
+        /// Decodes a `signalfd_siginfo` as read from a `SignalFd`.
+        ///
+        /// Fails with `EINVAL` if the signal number is not a valid `Signal`.
+        pub fn from_signalfd_siginfo(info: &libc::signalfd_siginfo) -> Result<SigInfo> {
+            SigInfo::decode(Fields {
+                signo: info.ssi_signo as c_int,
+                code: info.ssi_code,
+                pid: info.ssi_pid as libc::pid_t,
+                uid: info.ssi_uid,
+                status: info.ssi_status,
+                value: info.ssi_ptr as libc::intptr_t,
+                timer_id: info.ssi_tid as c_int,
+                overrun: info.ssi_overrun as c_int,
+                addr: info.ssi_addr as usize,
+                band: info.ssi_band as c_long,
+                fd: info.ssi_fd,
+                syscall: info.ssi_syscall,
+                arch: info.ssi_arch,
+                call_addr: info.ssi_call_addr as usize,
+            })
+        }
+
+        fn decode(f: Fields) -> Result<SigInfo> {
+            let signal = SigNum::from_c_int(f.signo)?;
+            let pid = Pid::from_raw(f.pid);
+            let uid = Uid::from_raw(f.uid);
+
+            Ok(match f.code {
+                libc::SI_USER => SigInfo::Kill { signal, pid, uid },
+                libc::SI_TKILL => SigInfo::Tkill { signal, pid, uid },
+                libc::SI_QUEUE | libc::SI_MESGQ => {
+                    SigInfo::Queue { signal, pid, uid, value: f.value }
+                }
+                libc::SI_TIMER => {
+                    SigInfo::Timer { signal, id: f.timer_id, overrun: f.overrun, value: f.value }
+                }
+                code if code <= 0 || code == libc::SI_KERNEL => SigInfo::Kernel { signal, code },
+                code => match signal {
+                    SigNum::Standard(Signal::SIGCHLD) => {
+                        match child_status(pid, code, f.status)? {
+                            Some(status) => SigInfo::Child { pid, uid, status },
+                            None => SigInfo::Kernel { signal, code },
+                        }
+                    }
+                    SigNum::Standard(fault @ Signal::SIGSEGV) |
+                    SigNum::Standard(fault @ Signal::SIGBUS) |
+                    SigNum::Standard(fault @ Signal::SIGILL) |
+                    SigNum::Standard(fault @ Signal::SIGFPE) |
+                    SigNum::Standard(fault @ Signal::SIGTRAP) => {
+                        SigInfo::Fault { signal: fault, code, addr: f.addr }
+                    }
+                    // I/O events can be redirected to realtime signals with `F_SETSIG`.
+                    SigNum::Standard(Signal::SIGIO) | SigNum::Realtime(_) => {
+                        SigInfo::Poll { signal, band: f.band, fd: f.fd }
+                    }
+                    SigNum::Standard(Signal::SIGSYS) if code == SYS_SECCOMP => {
+                        SigInfo::Sys { syscall: f.syscall, arch: f.arch, call_addr: f.call_addr }
+                    }
+                    _ => SigInfo::Kernel { signal, code },
+                },
+            })
+        }
+
+        /// The signal that was generated.
+        pub fn signal(&self) -> SigNum {
+            match *self {
+                SigInfo::Kill { signal, .. } |
+                SigInfo::Tkill { signal, .. } |
+                SigInfo::Queue { signal, .. } |
+                SigInfo::Timer { signal, .. } |
+                SigInfo::Poll { signal, .. } |
+                SigInfo::Kernel { signal, .. } => signal,
+                SigInfo::Fault { signal, .. } => SigNum::Standard(signal),
+                SigInfo::Child { .. } => SigNum::Standard(Signal::SIGCHLD),
+                SigInfo::Sys { .. } => SigNum::Standard(Signal::SIGSYS),
+            }
+        }
+    }
+
+    impl SigSet {
+        /// Suspends execution of the calling thread until one of the signals in the set becomes
+        /// pending, and returns information about the accepted signal.
+        ///
+        /// Unlike `wait`, this also accepts realtime signals.
+        pub fn wait_info(&self) -> Result<SigInfo> {
+            super::sigwaitinfo(self)
+        }
+
+        /// Like `wait_info`, but gives up after `timeout` and returns `None`.
+        ///
+        /// For more information, see [sigtimedwait(2)](http://man7.org/linux/man-pages/man2/sigtimedwait.2.html).
+        pub fn timed_wait(&self, timeout: Duration) -> Result<Option<SigInfo>> {
+            let timeout = libc::timespec {
+                tv_sec: timeout.as_secs() as libc::time_t,
+                tv_nsec: timeout.subsec_nanos() as c_long,
+            };
+            let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+            let res = unsafe { libc::sigtimedwait(&self.sigset, &mut info, &timeout) };
+
+            match Errno::result(res) {
+                Ok(_) => SigInfo::from_siginfo(&info).map(Some),
+                Err(Error::Sys(Errno::EAGAIN)) => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+    }
+
+    /// Queues a signal together with a value to the thread `tid` of the thread group `tgid`.
+    ///
+    /// The receiver sees a `SigInfo::Queue` with the pid and uid of the calling process.
+    ///
+    /// For more information, see [rt_tgsigqueueinfo(2)](http://man7.org/linux/man-pages/man2/rt_tgsigqueueinfo.2.html).
+    pub fn rt_tgsigqueueinfo<S: Into<SigNum>>(tgid: Pid, tid: Pid, signal: S, value: SigVal)
+        -> Result<()>
+    {
+        let signum = c_int::from(signal.into());
+        let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+        info.si_signo = signum;
+        info.si_code = libc::SI_QUEUE;
+        unsafe {
+            (*(&mut info as *mut libc::siginfo_t as *mut Union<Kill>)).fields = Kill {
+                _align: [],
+                pid: getpid().into(),
+                uid: getuid().into(),
+                value: libc::sigval::from(value).sival_ptr as usize,
+            };
+        }
+        let res = unsafe {
+            libc::syscall(libc::SYS_rt_tgsigqueueinfo, libc::pid_t::from(tgid),
+                          libc::pid_t::from(tid), signum, &info)
+        };
+
+        Errno::result(res).map(drop)
+    }
+
+    /// Converts the `si_code` and `si_status` of a `SIGCHLD` into a `WaitStatus`.
+    fn child_status(pid: Pid, code: c_int, status: c_int) -> Result<Option<WaitStatus>> {
+        Ok(Some(match code {
+            libc::CLD_EXITED => WaitStatus::Exited(pid, status),
+            libc::CLD_KILLED => WaitStatus::Signaled(pid, Signal::from_c_int(status)?, false),
+            libc::CLD_DUMPED => WaitStatus::Signaled(pid, Signal::from_c_int(status)?, true),
+            libc::CLD_STOPPED | libc::CLD_TRAPPED => {
+                WaitStatus::Stopped(pid, Signal::from_c_int(status)?)
+            }
+            libc::CLD_CONTINUED => WaitStatus::Continued(pid),
+            _ => return Ok(None),
+        }))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn siginfo<T>(signo: c_int, code: c_int, fields: T) -> libc::siginfo_t {
+            let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+            info.si_signo = signo;
+            info.si_code = code;
+            unsafe {
+                (*(&mut info as *mut libc::siginfo_t as *mut Union<T>)).fields = fields;
+            }
+            info
+        }
+
+        #[test]
+        fn test_union_layout() {
+            let info = siginfo(libc::SIGCHLD, libc::CLD_EXITED,
+                               Child { _align: [], pid: 42, uid: 1000, status: 3 });
+            unsafe {
+                assert_eq!(info.si_pid(), 42);
+                assert_eq!(info.si_uid(), 1000);
+                assert_eq!(info.si_status(), 3);
+            }
+            assert_eq!(SigInfo::from_siginfo(&info).unwrap(), SigInfo::Child {
+                pid: Pid::from_raw(42),
+                uid: Uid::from_raw(1000),
+                status: WaitStatus::Exited(Pid::from_raw(42), 3),
+            });
+
+            let info = siginfo(libc::SIGSEGV, 1, Fault { addr: 0x1000 });
+            assert_eq!(unsafe { info.si_addr() } as usize, 0x1000);
+            assert_eq!(SigInfo::from_siginfo(&info).unwrap(),
+                       SigInfo::Fault { signal: Signal::SIGSEGV, code: 1, addr: 0x1000 });
+
+            let info = siginfo(libc::SIGUSR1, libc::SI_QUEUE,
+                               Kill { _align: [], pid: 1, uid: 0, value: 7 });
+            assert_eq!(unsafe { info.si_value() }.sival_ptr as usize, 7);
+            assert_eq!(SigInfo::from_siginfo(&info).unwrap().signal(), Signal::SIGUSR1);
+        }
+
+        #[test]
+        fn test_decode() {
+            let info = siginfo(libc::SIGALRM, libc::SI_TIMER,
+                               Timer { _align: [], tid: 2, overrun: 5, value: 9 });
+            assert_eq!(SigInfo::from_siginfo(&info).unwrap(),
+                       SigInfo::Timer { signal: SigNum::Standard(Signal::SIGALRM), id: 2,
+                                        overrun: 5, value: 9 });
+
+            let info = siginfo(libc::SIGSYS, SYS_SECCOMP,
+                               Sys { call_addr: 0x2000, syscall: 39, arch: 0xc000_003e });
+            assert_eq!(SigInfo::from_siginfo(&info).unwrap(),
+                       SigInfo::Sys { syscall: 39, arch: 0xc000_003e, call_addr: 0x2000 });
+
+            let info = siginfo(libc::SIGIO, 1, Poll { band: 1, fd: 4 });
+            assert_eq!(SigInfo::from_siginfo(&info).unwrap(),
+                       SigInfo::Poll { signal: SigNum::Standard(Signal::SIGIO), band: 1, fd: 4 });
+
+            let info = siginfo(libc::SIGTERM, libc::SI_KERNEL, Fault { addr: 0 });
+            assert_eq!(SigInfo::from_siginfo(&info).unwrap(),
+                       SigInfo::Kernel { signal: SigNum::Standard(Signal::SIGTERM),
+                                         code: libc::SI_KERNEL });
+
+            assert!(SigInfo::from_siginfo(&siginfo(0, libc::SI_USER, Fault { addr: 0 })).is_err());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
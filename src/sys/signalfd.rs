@@ -19,7 +19,7 @@ use libc;
 use unistd;
 use {Error, Result};
 use errno::Errno;
-pub use sys::signal::{self, SigSet};
+pub use sys::signal::{self, SigInfo, SigSet};
 pub use libc::signalfd_siginfo as siginfo;
 
 use std::os::unix::io::{RawFd, AsRawFd};
@@ -107,6 +107,16 @@ impl SignalFd {
             Err(error) => Err(error)
         }
     }
+
+    /// Reads the next pending signal like `read_signal`, and decodes it into a `SigInfo`.
+    ///
+    /// Returns `Ok(None)` if the `SignalFd` is non-blocking and no signal is pending.
+    pub fn read_siginfo(&mut self) -> Result<Option<SigInfo>> {
+        match self.read_signal()? {
+            Some(info) => SigInfo::from_signalfd_siginfo(&info).map(Some),
+            None => Ok(None),
+        }
+    }
 }
 
 impl Drop for SignalFd {
@@ -2,12 +2,20 @@ use super::sa_family_t;
 use {Error, Result, NixPath};
 use errno::Errno;
 use libc;
-use std::{fmt, hash, mem, net, ptr, slice};
+use std::{cmp, fmt, hash, mem, net, ptr, slice};
 use std::ffi::OsStr;
 use std::path::Path;
 use std::os::unix::ffi::OsStrExt;
 #[cfg(any(target_os = "android", target_os = "linux"))]
 use ::sys::socket::addr::netlink::NetlinkAddr;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use {fcntl, unistd};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use fcntl::OFlag;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use sys::stat::Mode;
+#[cfg(target_os = "linux")]
+use ::sys::socket::addr::vsock::VsockAddr;
 #[cfg(any(target_os = "ios", target_os = "macos"))]
 use std::os::unix::io::RawFd;
 #[cfg(any(target_os = "ios", target_os = "macos"))]
@@ -215,8 +223,8 @@ impl AddressFamily {
     /// Create a new `AddressFamily` from an integer value retrieved from `libc`, usually from
     /// the `sa_family` field of a `sockaddr`.
     ///
-    /// Currently only supports these address families: Unix, Inet (v4 & v6), Netlink, Link/Packet
-    /// and System. Returns None for unsupported or unknown address families.
+    /// Currently only supports these address families: Unix, Inet (v4 & v6), Netlink, Link/Packet,
+    /// Vsock and System. Returns None for unsupported or unknown address families.
     pub fn from_i32(family: i32) -> Option<AddressFamily> {
         match family {
             libc::AF_UNIX => Some(AddressFamily::Unix),
@@ -228,6 +236,8 @@ impl AddressFamily {
             libc::AF_SYSTEM => Some(AddressFamily::System),
             #[cfg(any(target_os = "android", target_os = "linux"))]
             libc::AF_PACKET => Some(AddressFamily::Packet),
+            #[cfg(target_os = "linux")]
+            libc::AF_VSOCK => Some(AddressFamily::Vsock),
             #[cfg(any(target_os = "dragonfly",
                       target_os = "freebsd",
                       target_os = "ios",
@@ -392,7 +402,7 @@ impl fmt::Debug for InetAddr {
  * ===== IpAddr =====
  *
  */
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
 pub enum IpAddr {
     V4(Ipv4Addr),
     V6(Ipv6Addr),
@@ -565,6 +575,21 @@ impl Ipv6Addr {
     }
 }
 
+impl PartialEq for Ipv6Addr {
+    fn eq(&self, other: &Ipv6Addr) -> bool {
+        self.0.s6_addr == other.0.s6_addr
+    }
+}
+
+impl Eq for Ipv6Addr {
+}
+
+impl hash::Hash for Ipv6Addr {
+    fn hash<H: hash::Hasher>(&self, s: &mut H) {
+        self.0.s6_addr.hash(s)
+    }
+}
+
 impl fmt::Display for Ipv6Addr {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         self.to_std().fmt(fmt)
@@ -594,8 +619,27 @@ impl fmt::Debug for Ipv6Addr {
 #[derive(Copy)]
 pub struct UnixAddr(pub libc::sockaddr_un, pub usize);
 
+/// The kind of address held by a [`UnixAddr`](struct.UnixAddr.html).
+///
+/// See [`unix(7)`](http://man7.org/linux/man-pages/man7/unix.7.html).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum UnixAddrKind<'a> {
+    /// A socket bound to a filesystem path.
+    Pathname(&'a Path),
+    /// A name in the Linux abstract namespace, without the leading null byte.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Abstract(&'a [u8]),
+    /// A socket without a name, such as an unbound socket or one created by
+    /// `socketpair`.
+    Unnamed,
+}
+
 impl UnixAddr {
     /// Create a new sockaddr_un representing a filesystem path.
+    ///
+    /// A path of exactly `sun_path.len()` bytes is accepted and stored
+    /// without a terminating null.  Longer paths fail with `ENAMETOOLONG`;
+    /// see [`with_long_path`](#method.with_long_path) for those.
     pub fn new<P: ?Sized + NixPath>(path: &P) -> Result<UnixAddr> {
         try!(path.with_nix_path(|cstr| {
             unsafe {
@@ -647,24 +691,110 @@ impl UnixAddr {
         }
     }
 
+    /// Create an unnamed address.  Binding a socket to it makes the kernel
+    /// pick a unique name in the abstract namespace ("autobind"), which can
+    /// then be read back with `getsockname`.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn new_autobind() -> UnixAddr {
+        let ret = libc::sockaddr_un {
+            sun_family: AddressFamily::Unix as sa_family_t,
+            .. unsafe { mem::zeroed() }
+        };
+        UnixAddr(ret, 0)
+    }
+
+    /// Create a `UnixAddr` from a `sockaddr_un` and the length of the whole
+    /// socket address, as returned by `accept`, `getsockname` or `recvfrom`.
+    ///
+    /// Linux includes the terminating null of a pathname in the length;
+    /// it is dropped here so that the address compares equal to one built
+    /// with [`new`](#method.new).
+    pub fn from_raw_parts(sun: libc::sockaddr_un, len: usize) -> UnixAddr {
+        let offset = unsafe { offset_of!(libc::sockaddr_un, sun_path) };
+        let mut pathlen = cmp::min(len.saturating_sub(offset), sun.sun_path.len());
+        if pathlen > 0 && sun.sun_path[0] != 0 {
+            pathlen = unsafe { libc::strnlen(sun.sun_path.as_ptr(), pathlen) };
+        }
+        UnixAddr(sun, pathlen)
+    }
+
+    /// Run `f` with an address for `path`, even if `path` is too long to fit
+    /// into `sun_path`.
+    ///
+    /// Long paths are reached through an `O_PATH` descriptor of their parent
+    /// directory: `f` then sees an address of the form
+    /// `/proc/self/fd/<fd>/<file name>`, which is only valid until `f`
+    /// returns.  Sockets bound this way report that address from
+    /// `getsockname`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use nix::sys::socket::*;
+    ///
+    /// let path = "/run/containers/some/very/long/path/api.sock";
+    /// let fd = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None).unwrap();
+    /// UnixAddr::with_long_path(path, |addr| bind(fd, &SockAddr::Unix(*addr))).unwrap();
+    /// ```
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn with_long_path<P, F, T>(path: &P, f: F) -> Result<T>
+        where P: ?Sized + AsRef<Path>,
+              F: FnOnce(&UnixAddr) -> Result<T>
+    {
+        let path = path.as_ref();
+        match UnixAddr::new(path) {
+            Err(Error::Sys(Errno::ENAMETOOLONG)) => (),
+            addr => return f(&addr?),
+        }
+
+        let name = match path.file_name() {
+            Some(name) => name,
+            None => return Err(Error::Sys(Errno::EINVAL)),
+        };
+        let parent = match path.parent() {
+            Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+            Some(parent) => parent,
+            None => return Err(Error::Sys(Errno::EINVAL)),
+        };
+        let dirfd = fcntl::open(parent,
+                                OFlag::O_PATH | OFlag::O_DIRECTORY | OFlag::O_CLOEXEC,
+                                Mode::empty())?;
+        let proc_path = Path::new("/proc/self/fd").join(dirfd.to_string()).join(name);
+        let res = UnixAddr::new(&proc_path).and_then(|addr| f(&addr));
+        let _ = unistd::close(dirfd);
+        res
+    }
+
+    /// Return the kind of this address, along with its path or name.
+    pub fn kind<'a>(&'a self) -> UnixAddrKind<'a> {
+        if self.1 == 0 {
+            return UnixAddrKind::Unnamed;
+        }
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            if self.0.sun_path[0] == 0 {
+                return UnixAddrKind::Abstract(&self.sun_path()[1..]);
+            }
+        }
+        // POSIX only requires that `sun_len` be at least long enough to
+        // contain the pathname, and it need not be null-terminated.  So we
+        // need to create a string that is the shorter of the
+        // null-terminated length or the full length.
+        let p = self.sun_path();
+        let reallen = p.iter().position(|&b| b == 0).unwrap_or(p.len());
+        UnixAddrKind::Pathname(Path::new(<OsStr as OsStrExt>::from_bytes(&p[..reallen])))
+    }
+
     fn sun_path(&self) -> &[u8] {
         unsafe { slice::from_raw_parts(self.0.sun_path.as_ptr() as *const u8, self.1) }
     }
 
     /// If this address represents a filesystem path, return that path.
     pub fn path(&self) -> Option<&Path> {
-        if self.1 == 0 || self.0.sun_path[0] == 0 {
+        match self.kind() {
+            UnixAddrKind::Pathname(path) => Some(path),
             // unnamed or abstract
-            None
-        } else {
-            let p = self.sun_path();
-            // POSIX only requires that `sun_len` be at least long enough to
-            // contain the pathname, and it need not be null-terminated.  So we
-            // need to create a string that is the shorter of the
-            // null-terminated length or the full length.
-            let ptr = &self.0.sun_path as *const libc::c_char;
-            let reallen = unsafe { libc::strnlen(ptr, p.len()) };
-            Some(Path::new(<OsStr as OsStrExt>::from_bytes(&p[..reallen])))
+            _ => None,
         }
     }
 
@@ -674,11 +804,10 @@ impl UnixAddr {
     /// leading null byte. `None` is returned for unnamed or path-backed sockets.
     #[cfg(any(target_os = "android", target_os = "linux"))]
     pub fn as_abstract(&self) -> Option<&[u8]> {
-        if self.1 >= 1 && self.0.sun_path[0] == 0 {
-            Some(&self.sun_path()[1..])
-        } else {
+        match self.kind() {
+            UnixAddrKind::Abstract(name) => Some(name),
             // unnamed or filesystem path
-            None
+            _ => None,
         }
     }
 }
@@ -706,13 +835,11 @@ impl Clone for UnixAddr {
 
 impl fmt::Display for UnixAddr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.1 == 0 {
-            f.write_str("<unbound UNIX socket>")
-        } else if let Some(path) = self.path() {
-            path.display().fmt(f)
-        } else {
-            let display = String::from_utf8_lossy(&self.sun_path()[1..]);
-            write!(f, "@{}", display)
+        match self.kind() {
+            UnixAddrKind::Unnamed => f.write_str("<unbound UNIX socket>"),
+            UnixAddrKind::Pathname(path) => path.display().fmt(f),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            UnixAddrKind::Abstract(name) => write!(f, "@{}", String::from_utf8_lossy(name)),
         }
     }
 }
@@ -747,7 +874,10 @@ pub enum SockAddr {
               target_os = "macos",
               target_os = "netbsd",
               target_os = "openbsd"))]
-    Link(LinkAddr)
+    Link(LinkAddr),
+    /// VM socket address (see [`vsock(7)`](http://man7.org/linux/man-pages/man7/vsock.7.html))
+    #[cfg(target_os = "linux")]
+    Vsock(VsockAddr),
 }
 
 impl SockAddr {
@@ -764,6 +894,11 @@ impl SockAddr {
         SockAddr::Netlink(NetlinkAddr::new(pid, groups))
     }
 
+    #[cfg(target_os = "linux")]
+    pub fn new_vsock(cid: u32, port: u32) -> SockAddr {
+        SockAddr::Vsock(VsockAddr::new(cid, port))
+    }
+
     #[cfg(any(target_os = "ios", target_os = "macos"))]
     pub fn new_sys_control(sockfd: RawFd, name: &str, unit: u32) -> Result<SockAddr> {
         SysControlAddr::from_name(sockfd, name, unit).map(|a| SockAddr::SysControl(a))
@@ -786,7 +921,9 @@ impl SockAddr {
                       target_os = "macos",
                       target_os = "netbsd",
                       target_os = "openbsd"))]
-            SockAddr::Link(..) => AddressFamily::Link
+            SockAddr::Link(..) => AddressFamily::Link,
+            #[cfg(target_os = "linux")]
+            SockAddr::Vsock(..) => AddressFamily::Vsock,
         }
     }
 
@@ -796,7 +933,8 @@ impl SockAddr {
 
     /// Creates a `SockAddr` struct from libc's sockaddr.
     ///
-    /// Supports only the following address families: Unix, Inet (v4 & v6), Netlink and System.
+    /// Supports only the following address families: Inet (v4 & v6), Netlink, Link/Packet,
+    /// Vsock and System.
     /// Returns None for unsupported families.
     pub unsafe fn from_libc_sockaddr(addr: *const libc::sockaddr) -> Option<SockAddr> {
         if addr.is_null() {
@@ -831,6 +969,9 @@ impl SockAddr {
                         Some(SockAddr::Link(ether_addr))
                     }
                 },
+                #[cfg(target_os = "linux")]
+                Some(AddressFamily::Vsock) => Some(SockAddr::Vsock(
+                    VsockAddr(*(addr as *const libc::sockaddr_vm)))),
                 // Other address families are currently not supported and simply yield a None
                 // entry instead of a proper conversion to a `SockAddr`.
                 Some(_) | None => None,
@@ -863,6 +1004,8 @@ impl SockAddr {
                       target_os = "netbsd",
                       target_os = "openbsd"))]
             SockAddr::Link(LinkAddr(ref ether_addr)) => (mem::transmute(ether_addr), mem::size_of::<libc::sockaddr_dl>() as libc::socklen_t),
+            #[cfg(target_os = "linux")]
+            SockAddr::Vsock(VsockAddr(ref sa)) => (mem::transmute(sa), mem::size_of::<libc::sockaddr_vm>() as libc::socklen_t),
         }
     }
 }
@@ -891,6 +1034,10 @@ impl PartialEq for SockAddr {
             (SockAddr::Link(ref a), SockAddr::Link(ref b)) => {
                 a == b
             }
+            #[cfg(target_os = "linux")]
+            (SockAddr::Vsock(ref a), SockAddr::Vsock(ref b)) => {
+                a == b
+            }
             _ => false,
         }
     }
@@ -916,7 +1063,9 @@ impl hash::Hash for SockAddr {
                       target_os = "macos",
                       target_os = "netbsd",
                       target_os = "openbsd"))]
-            SockAddr::Link(ref ether_addr) => ether_addr.hash(s)
+            SockAddr::Link(ref ether_addr) => ether_addr.hash(s),
+            #[cfg(target_os = "linux")]
+            SockAddr::Vsock(ref a) => a.hash(s),
         }
     }
 }
@@ -944,7 +1093,9 @@ impl fmt::Display for SockAddr {
                       target_os = "macos",
                       target_os = "netbsd",
                       target_os = "openbsd"))]
-            SockAddr::Link(ref ether_addr) => ether_addr.fmt(f)
+            SockAddr::Link(ref ether_addr) => ether_addr.fmt(f),
+            #[cfg(target_os = "linux")]
+            SockAddr::Vsock(ref svm) => svm.fmt(f),
         }
     }
 }
@@ -1010,6 +1161,72 @@ pub mod netlink {
     }
 }
 
+#[cfg(target_os = "linux")]
+pub mod vsock {
+    use ::sys::socket::addr::AddressFamily;
+    use libc::{sa_family_t, sockaddr_vm};
+    use std::{fmt, mem};
+    use std::hash::{Hash, Hasher};
+
+    pub use libc::{VMADDR_CID_ANY, VMADDR_CID_HOST, VMADDR_CID_HYPERVISOR, VMADDR_CID_LOCAL,
+                   VMADDR_PORT_ANY};
+
+    /// Address of an `AF_VSOCK` socket, made of a context ID that identifies
+    /// the virtual machine and a port.
+    #[derive(Copy, Clone)]
+    pub struct VsockAddr(pub sockaddr_vm);
+
+    impl PartialEq for VsockAddr {
+        fn eq(&self, other: &Self) -> bool {
+            let (inner, other) = (self.0, other.0);
+            (inner.svm_family, inner.svm_cid, inner.svm_port) ==
+            (other.svm_family, other.svm_cid, other.svm_port)
+        }
+    }
+
+    impl Eq for VsockAddr {}
+
+    impl Hash for VsockAddr {
+        fn hash<H: Hasher>(&self, s: &mut H) {
+            let inner = self.0;
+            (inner.svm_family, inner.svm_cid, inner.svm_port).hash(s);
+        }
+    }
+
+    impl VsockAddr {
+        pub fn new(cid: u32, port: u32) -> VsockAddr {
+            let mut addr: sockaddr_vm = unsafe { mem::zeroed() };
+            addr.svm_family = AddressFamily::Vsock as sa_family_t;
+            addr.svm_cid = cid;
+            addr.svm_port = port;
+
+            VsockAddr(addr)
+        }
+
+        /// Context ID
+        pub fn cid(&self) -> u32 {
+            self.0.svm_cid
+        }
+
+        /// Port number
+        pub fn port(&self) -> u32 {
+            self.0.svm_port
+        }
+    }
+
+    impl fmt::Display for VsockAddr {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "cid: {} port: {}", self.cid(), self.port())
+        }
+    }
+
+    impl fmt::Debug for VsockAddr {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            fmt::Display::fmt(self, f)
+        }
+    }
+}
+
 #[cfg(any(target_os = "ios", target_os = "macos"))]
 pub mod sys_control {
     use ::sys::socket::addr::AddressFamily;
@@ -1112,13 +1329,36 @@ pub mod sys_control {
 
 #[cfg(any(target_os = "android", target_os = "linux"))]
 mod datalink {
-    use super::{libc, hash, fmt, AddressFamily};
+    use super::{libc, hash, fmt, mem, AddressFamily};
 
     /// Hardware Address
     #[derive(Clone, Copy)]
     pub struct LinkAddr(pub libc::sockaddr_ll);
 
     impl LinkAddr {
+        /// Creates an address for binding or sending on an `AF_PACKET`
+        /// socket (see [`packet(7)`](http://man7.org/linux/man-pages/man7/packet.7.html)).
+        ///
+        /// `protocol` is an `ETH_P_*` value in network byte order, like
+        /// `sll_protocol`; for example `(libc::ETH_P_ALL as u16).to_be()`.
+        /// `addr` is the destination hardware address when sending.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `addr` is longer than 8 bytes.
+        pub fn new(protocol: u16, ifindex: usize, hatype: u16, pkttype: u8, addr: &[u8]) -> LinkAddr {
+            let mut sll: libc::sockaddr_ll = unsafe { mem::zeroed() };
+            assert!(addr.len() <= sll.sll_addr.len(), "hardware address too long");
+            sll.sll_family = libc::AF_PACKET as u16;
+            sll.sll_protocol = protocol;
+            sll.sll_ifindex = ifindex as i32;
+            sll.sll_hatype = hatype;
+            sll.sll_pkttype = pkttype;
+            sll.sll_halen = addr.len() as u8;
+            sll.sll_addr[..addr.len()].copy_from_slice(addr);
+            LinkAddr(sll)
+        }
+
         /// Always AF_PACKET
         pub fn family(&self) -> AddressFamily {
             assert_eq!(self.0.sll_family as i32, libc::AF_PACKET);
@@ -0,0 +1,230 @@
+//! Passing file descriptors and credentials between processes over a Unix
+//! socket.
+//!
+//! [Further reading](http://man7.org/linux/man-pages/man7/unix.7.html)
+use {Error, Result};
+use errno::Errno;
+use std::{mem, ptr};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use sys::uio::IoVec;
+use unistd::close;
+use super::{cmsg_space, recvmsg, sendmsg, setsockopt, socketpair, sockopt, AddressFamily,
+            CmsgBuffer, ControlMessage, MsgFlags, SockFlag, SockType, UnixCredentials};
+
+/// The maximum number of file descriptors Linux passes in one message
+/// (`SCM_MAX_FD`).
+pub const SCM_MAX_FD: usize = 253;
+
+/// A file descriptor received over a [`UnixChannel`](struct.UnixChannel.html).
+/// It is closed when dropped unless it is taken with `into_raw_fd`.
+#[derive(Debug, Eq, Hash, PartialEq)]
+pub struct ReceivedFd(RawFd);
+
+impl AsRawFd for ReceivedFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+impl IntoRawFd for ReceivedFd {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.0;
+        mem::forget(self);
+        fd
+    }
+}
+
+impl FromRawFd for ReceivedFd {
+    unsafe fn from_raw_fd(fd: RawFd) -> ReceivedFd {
+        ReceivedFd(fd)
+    }
+}
+
+impl Drop for ReceivedFd {
+    fn drop(&mut self) {
+        let _ = close(self.0);
+    }
+}
+
+/// A message received by [`UnixChannel::recv`](struct.UnixChannel.html#method.recv).
+#[derive(Debug)]
+pub struct ChannelMessage {
+    /// The number of payload bytes written to the receive buffer.
+    pub bytes: usize,
+    /// The file descriptors sent along with the payload.
+    pub fds: Vec<ReceivedFd>,
+    /// The credentials of the sender, if credential passing is enabled on
+    /// the receiving end.
+    pub credentials: Option<UnixCredentials>,
+}
+
+/// One end of a connected `SOCK_SEQPACKET` Unix socket pair that sends byte
+/// payloads together with file descriptors.
+///
+/// Every `send` arrives as exactly one message.  Received descriptors have
+/// `FD_CLOEXEC` set and are closed again if they are not taken out of the
+/// returned `ReceivedFd`s.
+///
+/// # Examples
+///
+/// ```
+/// use nix::sys::socket::UnixChannel;
+/// use nix::unistd::{pipe, write, read};
+/// use std::os::unix::io::AsRawFd;
+///
+/// let (mut parent, child) = UnixChannel::pair().unwrap();
+/// let (r, w) = pipe().unwrap();
+/// child.send(b"pipe", &[r]).unwrap();
+///
+/// let mut buf = [0u8; 16];
+/// let msg = parent.recv(&mut buf).unwrap();
+/// assert_eq!(&buf[..msg.bytes], b"pipe");
+/// write(w, b"x").unwrap();
+/// assert_eq!(read(msg.fds[0].as_raw_fd(), &mut buf), Ok(1));
+/// ```
+#[derive(Debug)]
+pub struct UnixChannel {
+    fd: RawFd,
+    max_fds: usize,
+    pass_credentials: bool,
+    cmsg_buffer: CmsgBuffer,
+}
+
+impl UnixChannel {
+    /// Creates a pair of connected channels, for example to share with a
+    /// child process.  Both ends are opened with `SOCK_CLOEXEC`.
+    pub fn pair() -> Result<(UnixChannel, UnixChannel)> {
+        let (a, b) = socketpair(AddressFamily::Unix, SockType::SeqPacket, None,
+                                SockFlag::SOCK_CLOEXEC)?;
+        unsafe { Ok((UnixChannel::from_raw_fd(a), UnixChannel::from_raw_fd(b))) }
+    }
+
+    /// Limits the number of file descriptors `recv` accepts per message.
+    /// The default is `SCM_MAX_FD`.
+    pub fn set_max_fds(&mut self, max_fds: usize) {
+        self.max_fds = max_fds;
+        self.resize_cmsg_buffer();
+    }
+
+    fn resize_cmsg_buffer(&mut self) {
+        let mut space = 0;
+        if self.max_fds > 0 {
+            space += cmsg_space(self.max_fds * mem::size_of::<RawFd>());
+        }
+        if self.pass_credentials {
+            space += cmsg_space(mem::size_of::<UnixCredentials>());
+        }
+        self.cmsg_buffer = CmsgBuffer::with_space(space);
+    }
+
+    /// Asks the kernel to attach the sender's credentials to every message
+    /// this end receives (`SO_PASSCRED`).
+    pub fn set_pass_credentials(&mut self, enable: bool) -> Result<()> {
+        setsockopt(self.fd, sockopt::PassCred, &enable)?;
+        self.pass_credentials = enable;
+        self.resize_cmsg_buffer();
+        Ok(())
+    }
+
+    /// Sends `payload` together with `fds`.  The descriptors stay open in the
+    /// sending process.
+    ///
+    /// A message without payload and descriptors cannot be told apart from
+    /// the peer closing the channel.
+    pub fn send(&self, payload: &[u8], fds: &[RawFd]) -> Result<usize> {
+        self.send_msg(payload, fds, None)
+    }
+
+    /// Like `send`, but also sends the credentials of the calling process.
+    /// The receiver only gets them if it enabled
+    /// [`set_pass_credentials`](#method.set_pass_credentials).
+    pub fn send_with_credentials(&self, payload: &[u8], fds: &[RawFd]) -> Result<usize> {
+        self.send_msg(payload, fds, Some(UnixCredentials::current()))
+    }
+
+    fn send_msg(&self, payload: &[u8], fds: &[RawFd],
+                credentials: Option<UnixCredentials>) -> Result<usize> {
+        let iov = [IoVec::from_slice(payload)];
+        let mut cmsgs = Vec::with_capacity(2);
+        if !fds.is_empty() {
+            cmsgs.push(ControlMessage::ScmRights(fds));
+        }
+        if let Some(ref credentials) = credentials {
+            cmsgs.push(ControlMessage::ScmCredentials(credentials));
+        }
+        sendmsg(self.fd, &iov, &cmsgs, MsgFlags::MSG_NOSIGNAL, None)
+    }
+
+    /// Receives one message into `buf`.
+    ///
+    /// Fails with `EMSGSIZE` if the payload did not fit into `buf`, and with
+    /// `ENOBUFS` if more file descriptors were sent than allowed by
+    /// [`set_max_fds`](#method.set_max_fds).  In both cases the message is
+    /// consumed and all descriptors that came with it are closed.
+    ///
+    /// A message with no bytes and no descriptors means that the peer closed
+    /// the channel.
+    pub fn recv(&mut self, buf: &mut [u8]) -> Result<ChannelMessage> {
+        let iov = [IoVec::from_mut_slice(buf)];
+        let msg = recvmsg(self.fd, &iov, Some(&mut self.cmsg_buffer),
+                          MsgFlags::MSG_CMSG_CLOEXEC)?;
+
+        let mut fds = Vec::new();
+        let mut credentials = None;
+        for cmsg in msg.cmsgs() {
+            match cmsg {
+                ControlMessage::ScmRights(received) => {
+                    fds.extend(received.iter().map(|&fd| ReceivedFd(fd)));
+                },
+                ControlMessage::ScmCredentials(creds) => credentials = Some(*creds),
+                _ => (),
+            }
+        }
+
+        if msg.flags.contains(MsgFlags::MSG_TRUNC) {
+            return Err(Error::Sys(Errno::EMSGSIZE));
+        }
+        if msg.flags.contains(MsgFlags::MSG_CTRUNC) {
+            return Err(Error::Sys(Errno::ENOBUFS));
+        }
+        Ok(ChannelMessage { bytes: msg.bytes, fds, credentials })
+    }
+}
+
+impl AsRawFd for UnixChannel {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl IntoRawFd for UnixChannel {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.fd;
+        // Only the socket must outlive `self`; the buffer is still freed.
+        let cmsg_buffer = unsafe { ptr::read(&self.cmsg_buffer) };
+        mem::forget(self);
+        drop(cmsg_buffer);
+        fd
+    }
+}
+
+impl FromRawFd for UnixChannel {
+    /// Wraps a connected `SOCK_SEQPACKET` Unix socket, such as one inherited
+    /// from a parent process.
+    unsafe fn from_raw_fd(fd: RawFd) -> UnixChannel {
+        let mut channel = UnixChannel {
+            fd,
+            max_fds: SCM_MAX_FD,
+            pass_credentials: false,
+            cmsg_buffer: CmsgBuffer::with_space(0),
+        };
+        channel.resize_cmsg_buffer();
+        channel
+    }
+}
+
+impl Drop for UnixChannel {
+    fn drop(&mut self) {
+        let _ = close(self.fd);
+    }
+}
@@ -3,13 +3,21 @@
 //! [Further reading](http://man7.org/linux/man-pages/man7/socket.7.html)
 use {Error, Result};
 use errno::Errno;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use features;
 use libc::{self, c_void, c_int, socklen_t, size_t};
-use std::{fmt, mem, ptr, slice};
+use std::{cmp, fmt, mem, ptr, slice};
 use std::os::unix::io::RawFd;
 use sys::time::TimeVal;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use sys::time::TimeSpec;
 use sys::uio::IoVec;
 
 mod addr;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod channel;
+#[cfg(target_os = "linux")]
+pub mod netlink;
 pub mod sockopt;
 
 /*
@@ -23,6 +31,7 @@ pub use self::addr::{
     SockAddr,
     InetAddr,
     UnixAddr,
+    UnixAddrKind,
     IpAddr,
     Ipv4Addr,
     Ipv6Addr,
@@ -30,6 +39,11 @@ pub use self::addr::{
 };
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub use ::sys::socket::addr::netlink::NetlinkAddr;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::channel::{ChannelMessage, ReceivedFd, UnixChannel, SCM_MAX_FD};
+#[cfg(target_os = "linux")]
+pub use ::sys::socket::addr::vsock::{VsockAddr, VMADDR_CID_ANY, VMADDR_CID_HOST,
+                                     VMADDR_CID_HYPERVISOR, VMADDR_CID_LOCAL, VMADDR_PORT_ANY};
 
 pub use libc::{
     cmsghdr,
@@ -83,6 +97,30 @@ pub enum SockProtocol {
     /// ([ref](https://developer.apple.com/library/content/documentation/Darwin/Conceptual/NKEConceptual/control/control.html))
     #[cfg(any(target_os = "ios", target_os = "macos"))]
     KextControl = libc::SYSPROTO_CONTROL,
+    /// Receives routing and link updates and may be used to modify the routing tables (both
+    /// IPv4 and IPv6), IP addresses, link parameters, neighbor setups, queueing disciplines,
+    /// traffic classes and packet classifiers
+    /// ([ref](http://man7.org/linux/man-pages/man7/rtnetlink.7.html))
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    NetlinkRoute = libc::NETLINK_ROUTE,
+    /// Netlink socket diagnostics
+    /// ([ref](http://man7.org/linux/man-pages/man7/sock_diag.7.html))
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    NetlinkSockDiag = libc::NETLINK_SOCK_DIAG,
+    /// Kernel auditing ([ref](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    NetlinkAudit = libc::NETLINK_AUDIT,
+    /// Netfilter subsystem ([ref](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    NetlinkNetFilter = libc::NETLINK_NETFILTER,
+    /// Kernel messages to userspace, such as uevents
+    /// ([ref](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    NetlinkKObjectUEvent = libc::NETLINK_KOBJECT_UEVENT,
+    /// Generic netlink family for simplified netlink usage
+    /// ([ref](http://man7.org/linux/man-pages/man7/netlink.7.html))
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    NetlinkGeneric = libc::NETLINK_GENERIC,
 }
 
 libc_bitflags!{
@@ -169,6 +207,21 @@ libc_bitflags!{
                   target_os = "netbsd",
                   target_os = "openbsd"))]
         MSG_CMSG_CLOEXEC;
+        /// Don't generate a `SIGPIPE` signal when sending on a stream-oriented
+        /// socket whose peer has closed the connection; `EPIPE` is still
+        /// returned.
+        #[cfg(any(target_os = "android",
+                  target_os = "dragonfly",
+                  target_os = "freebsd",
+                  target_os = "linux",
+                  target_os = "netbsd",
+                  target_os = "openbsd"))]
+        MSG_NOSIGNAL;
+        /// Makes [`recvmmsg`](fn.recvmmsg.html) return as soon as at least one
+        /// message has been received, as if `MSG_DONTWAIT` were set after the
+        /// first message.
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        MSG_WAITFORONE;
     }
 }
 
@@ -182,6 +235,16 @@ cfg_if! {
         pub struct UnixCredentials(libc::ucred);
 
         impl UnixCredentials {
+            /// Returns the credentials of the calling process, as sent in an
+            /// `SCM_CREDENTIALS` message.
+            pub fn current() -> Self {
+                UnixCredentials(libc::ucred {
+                    pid: ::unistd::getpid().into(),
+                    uid: ::unistd::getuid().into(),
+                    gid: ::unistd::getgid().into(),
+                })
+            }
+
             /// Returns the process identifier
             pub fn pid(&self) -> libc::pid_t {
                 self.0.pid
@@ -205,6 +268,18 @@ cfg_if! {
         }
         impl Eq for UnixCredentials {}
 
+        impl From<libc::ucred> for UnixCredentials {
+            fn from(cred: libc::ucred) -> Self {
+                UnixCredentials(cred)
+            }
+        }
+
+        impl From<UnixCredentials> for libc::ucred {
+            fn from(cred: UnixCredentials) -> Self {
+                cred.0
+            }
+        }
+
         impl fmt::Debug for UnixCredentials {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
                 f.debug_struct("UnixCredentials")
@@ -217,6 +292,27 @@ cfg_if! {
     }
 }
 
+/// The timestamps carried by an `SCM_TIMESTAMPING` control message.
+///
+/// Which of the fields are filled in depends on the flags passed to the
+/// `SO_TIMESTAMPING` socket option; unused fields are zero.
+/// [Further reading](https://www.kernel.org/doc/Documentation/networking/timestamping.txt)
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Timestamps {
+    /// The software timestamp.
+    pub system: TimeSpec,
+    /// Deprecated by the kernel; always zero.
+    pub hw_trans: TimeSpec,
+    /// The raw hardware timestamp.
+    pub hw_raw: TimeSpec,
+}
+
+// Not yet exported by libc.
+#[cfg(target_os = "linux")]
+const SCM_PIDFD: c_int = 0x04;
+
 /// Request for multicast socket operations
 ///
 /// This is a wrapper type around `ip_mreq`.
@@ -329,30 +425,110 @@ cfg_if! {
     }
 }
 
+/// Computes the buffer space needed to receive control messages carrying
+/// the given types, as `CMSG_SPACE` would, for use with
+/// [`CmsgBuffer::with_space`](sys/socket/struct.CmsgBuffer.html#method.with_space).
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate nix;
+/// # use std::os::unix::io::RawFd;
+/// # use nix::sys::socket::CmsgBuffer;
+/// # fn main() {
+/// // Room for two file descriptors in one `ScmRights` and a timestamp
+/// let buffer = CmsgBuffer::with_space(cmsg_space!([RawFd; 2], nix::sys::time::TimeVal));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cmsg_space {
+    ( $( $x:ty ),* ) => {
+        0usize $( + $crate::sys::socket::cmsg_space(::std::mem::size_of::<$x>()) )*
+    }
+}
+
+/// The value of `CMSG_SPACE` for a control message with `len` bytes of data.
+///
+/// See the [`cmsg_space!`](../../macro.cmsg_space.html) macro for the
+/// common case of fixed-size data.
+pub fn cmsg_space(len: usize) -> usize {
+    cmsg_align(mem::size_of::<cmsghdr>()) + cmsg_align(len)
+}
+
+/// An owned buffer for receiving control messages with `recvmsg`.
+///
+/// The buffer is suitably aligned for `cmsghdr` and can be reused across
+/// calls.  Size it with [`cmsg_space!`](../../macro.cmsg_space.html): if it
+/// is too small the kernel truncates the control messages, sets
+/// `MSG_CTRUNC`, and closes any file descriptors that did not fit.
+#[derive(Clone, Debug)]
+pub struct CmsgBuffer {
+    buf: Vec<align_of_cmsg_data>,
+    space: usize,
+}
+
+impl CmsgBuffer {
+    /// Allocates a buffer with room for `space` bytes of control messages.
+    pub fn with_space(space: usize) -> CmsgBuffer {
+        let words = (space + mem::size_of::<align_of_cmsg_data>() - 1) /
+            mem::size_of::<align_of_cmsg_data>();
+        CmsgBuffer {
+            buf: vec![0; words],
+            space,
+        }
+    }
+
+    /// The number of bytes of control messages this buffer can hold.
+    pub fn space(&self) -> usize {
+        self.space
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut c_void {
+        self.buf.as_mut_ptr() as *mut c_void
+    }
+
+    // The first `len` bytes of the buffer.
+    fn bytes(&self, len: usize) -> &[u8] {
+        unsafe {
+            slice::from_raw_parts(self.buf.as_ptr() as *const u8, cmp::min(len, self.space))
+        }
+    }
+}
+
 #[allow(missing_debug_implementations)]
-pub struct RecvMsg {
+pub struct RecvMsg<'a> {
     // The number of bytes received.
     pub bytes: usize,
-    cmsg_buffer: Vec<u8>,
+    cmsg_buffer: &'a [u8],
     pub address: Option<SockAddr>,
     pub flags: MsgFlags,
 }
 
-impl RecvMsg {
+impl<'a> RecvMsg<'a> {
     /// Iterate over the valid control messages pointed to by this
     /// msghdr.
-    pub fn cmsgs(&self) -> CmsgIterator {
+    pub fn cmsgs(&self) -> CmsgIterator<'a> {
         CmsgIterator {
-            buf: &self.cmsg_buffer,
-            next: 0
+            buf: self.cmsg_buffer,
+        }
+    }
+
+    /// Like `cmsgs`, but fails with `ENOBUFS` if the control messages were
+    /// truncated (`MSG_CTRUNC`) because the `CmsgBuffer` was too small.
+    ///
+    /// File descriptors that did fit are still delivered and can be
+    /// collected with `cmsgs`; the ones that did not are lost.
+    pub fn checked_cmsgs(&self) -> Result<CmsgIterator<'a>> {
+        if self.flags.contains(MsgFlags::MSG_CTRUNC) {
+            return Err(Error::Sys(Errno::ENOBUFS));
         }
+        Ok(self.cmsgs())
     }
 }
 
 #[allow(missing_debug_implementations)]
 pub struct CmsgIterator<'a> {
     buf: &'a [u8],
-    next: usize,
 }
 
 impl<'a> Iterator for CmsgIterator<'a> {
@@ -376,21 +552,15 @@ impl<'a> Iterator for CmsgIterator<'a> {
             return None;
         }
         let len = cmsg_len - sizeof_cmsghdr;
-        let aligned_cmsg_len = if self.next == 0 {
-            // CMSG_FIRSTHDR
-            cmsg_len
-        } else {
-            // CMSG_NXTHDR
-            cmsg_align(cmsg_len)
-        };
-
-        // Advance our internal pointer.
-        if aligned_cmsg_len > self.buf.len() {
+        if cmsg_len > self.buf.len() {
             return None;
         }
+
+        // Advance our internal pointer.  Like CMSG_NXTHDR, the next header
+        // starts after the padding; the last message may lack it.
+        let aligned_cmsg_len = cmp::min(cmsg_align(cmsg_len), self.buf.len());
         let cmsg_data = &self.buf[cmsg_align(sizeof_cmsghdr)..cmsg_len];
         self.buf = &self.buf[aligned_cmsg_len..];
-        self.next += 1;
 
         match (cmsg.cmsg_level, cmsg.cmsg_type) {
             (libc::SOL_SOCKET, libc::SCM_RIGHTS) => unsafe {
@@ -402,6 +572,69 @@ impl<'a> Iterator for CmsgIterator<'a> {
                 Some(ControlMessage::ScmTimestamp(
                     &*(cmsg_data.as_ptr() as *const _)))
             },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (libc::SOL_SOCKET, libc::SCM_CREDENTIALS) => unsafe {
+                Some(ControlMessage::ScmCredentials(
+                    &*(cmsg_data.as_ptr() as *const _)))
+            },
+            #[cfg(target_os = "linux")]
+            (libc::SOL_SOCKET, libc::SCM_TIMESTAMPNS) => unsafe {
+                Some(ControlMessage::ScmTimestampns(
+                    &*(cmsg_data.as_ptr() as *const _)))
+            },
+            #[cfg(target_os = "linux")]
+            (libc::SOL_SOCKET, libc::SCM_TIMESTAMPING) => unsafe {
+                Some(ControlMessage::ScmTimestamping(
+                    &*(cmsg_data.as_ptr() as *const _)))
+            },
+            #[cfg(target_os = "linux")]
+            (libc::SOL_SOCKET, SCM_PIDFD) => unsafe {
+                Some(ControlMessage::ScmPidfd(
+                    &*(cmsg_data.as_ptr() as *const _)))
+            },
+            #[cfg(target_os = "linux")]
+            (libc::IPPROTO_IP, libc::IP_PKTINFO) => unsafe {
+                Some(ControlMessage::IpPktInfo(
+                    &*(cmsg_data.as_ptr() as *const _)))
+            },
+            #[cfg(target_os = "linux")]
+            (libc::IPPROTO_IPV6, libc::IPV6_PKTINFO) => unsafe {
+                Some(ControlMessage::Ipv6PktInfo(
+                    &*(cmsg_data.as_ptr() as *const _)))
+            },
+            #[cfg(target_os = "linux")]
+            (libc::IPPROTO_IP, libc::IP_TTL) => unsafe {
+                Some(ControlMessage::IpTtl(
+                    &*(cmsg_data.as_ptr() as *const _)))
+            },
+            #[cfg(target_os = "linux")]
+            (libc::IPPROTO_IP, libc::IP_TOS) => {
+                Some(ControlMessage::IpTos(&cmsg_data[0]))
+            },
+            #[cfg(target_os = "linux")]
+            (libc::IPPROTO_UDP, libc::UDP_SEGMENT) => unsafe {
+                Some(ControlMessage::UdpGsoSegments(
+                    &*(cmsg_data.as_ptr() as *const _)))
+            },
+            #[cfg(target_os = "linux")]
+            (libc::IPPROTO_UDP, libc::UDP_GRO) => unsafe {
+                Some(ControlMessage::UdpGroSegments(
+                    &*(cmsg_data.as_ptr() as *const _)))
+            },
+            #[cfg(target_os = "linux")]
+            (libc::IPPROTO_IP, libc::IP_RECVERR) => {
+                match decode_extended_err(cmsg_data) {
+                    Some((err, offender)) => Some(ControlMessage::IpRecvErr(err, offender)),
+                    None => Some(ControlMessage::Unknown(UnknownCmsg(cmsg, cmsg_data))),
+                }
+            },
+            #[cfg(target_os = "linux")]
+            (libc::IPPROTO_IPV6, libc::IPV6_RECVERR) => {
+                match decode_extended_err(cmsg_data) {
+                    Some((err, offender)) => Some(ControlMessage::Ipv6RecvErr(err, offender)),
+                    None => Some(ControlMessage::Unknown(UnknownCmsg(cmsg, cmsg_data))),
+                }
+            },
             (_, _) => unsafe {
                 Some(ControlMessage::Unknown(UnknownCmsg(
                     cmsg,
@@ -441,12 +674,13 @@ pub enum ControlMessage<'a> {
     // https://bugs.freebsd.org/bugzilla/show_bug.cgi?id=222039
     #[cfg_attr(not(all(target_os = "freebsd", target_arch = "x86")), doc = " ```")]
     #[cfg_attr(all(target_os = "freebsd", target_arch = "x86"), doc = " ```no_run")]
+    /// # #[macro_use] extern crate nix;
     /// use nix::sys::socket::*;
     /// use nix::sys::uio::IoVec;
     /// use nix::sys::time::*;
     /// use std::time::*;
-    /// use std::mem;
     ///
+    /// # fn main() {
     /// // Set up
     /// let message1 = "Ohayō!".as_bytes();
     /// let message2 = "Jā ne".as_bytes();
@@ -464,14 +698,14 @@ pub enum ControlMessage<'a> {
     ///
     /// // Receive the first
     /// let mut buffer1 = vec![0u8; message1.len() + message2.len()];
-    /// let mut time1 = TimeVal::seconds(0);
-    /// let received1 = recvmsg(in_socket, &[IoVec::from_mut_slice(&mut buffer1)], mem::size_of_val(&time1), MsgFlags::empty()).unwrap();
+    /// let mut cmsg_buffer1 = CmsgBuffer::with_space(cmsg_space!(TimeVal));
+    /// let received1 = recvmsg(in_socket, &[IoVec::from_mut_slice(&mut buffer1)], Some(&mut cmsg_buffer1), MsgFlags::empty()).unwrap();
     /// let mut time1 = if let Some(ControlMessage::ScmTimestamp(&time1)) = received1.cmsgs().next() { time1 } else { panic!("Unexpected or no control message") };
     ///
     /// // Receive the second
     /// let mut buffer2 = vec![0u8; message1.len() + message2.len()];
-    /// let mut time2 = TimeVal::seconds(0);
-    /// let received2 = recvmsg(in_socket, &[IoVec::from_mut_slice(&mut buffer2)], mem::size_of_val(&time2), MsgFlags::empty()).unwrap();
+    /// let mut cmsg_buffer2 = CmsgBuffer::with_space(cmsg_space!(TimeVal));
+    /// let received2 = recvmsg(in_socket, &[IoVec::from_mut_slice(&mut buffer2)], Some(&mut cmsg_buffer2), MsgFlags::empty()).unwrap();
     /// let mut time2 = if let Some(ControlMessage::ScmTimestamp(&time2)) = received2.cmsgs().next() { time2 } else { panic!("Unexpected or no control message") };
     ///
     /// // Swap if needed; UDP is unordered
@@ -496,12 +730,123 @@ pub enum ControlMessage<'a> {
     ///
     /// // Close socket
     /// nix::unistd::close(in_socket).unwrap();
+    /// # }
     /// ```
     ScmTimestamp(&'a TimeVal),
+    /// A message of type `SCM_CREDENTIALS`, containing the pid, uid and gid
+    /// of the peer of a Unix domain socket.
+    ///
+    /// The receiver must enable the `PassCred` socket option, in which case
+    /// the kernel attaches the sender's credentials even if it did not send
+    /// this message.  A sender may only send credentials other than its own
+    /// with the appropriate privileges.
+    ///
+    /// See the description in the "Ancillary messages" section of the
+    /// [unix(7) man page](http://man7.org/linux/man-pages/man7/unix.7.html).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    ScmCredentials(&'a UnixCredentials),
+    /// A message of type `SCM_TIMESTAMPNS`, containing the time the packet
+    /// was received by the kernel with nanosecond resolution.
+    ///
+    /// Enabled with the `ReceiveTimestampns` socket option.
+    #[cfg(target_os = "linux")]
+    ScmTimestampns(&'a TimeSpec),
+    /// A message of type `SCM_TIMESTAMPING`, containing the software and
+    /// hardware timestamps requested with `SO_TIMESTAMPING`.
+    ///
+    /// See the kernel's explanation in "SO_TIMESTAMPING" of
+    /// [networking/timestamping](https://www.kernel.org/doc/Documentation/networking/timestamping.txt).
+    #[cfg(target_os = "linux")]
+    ScmTimestamping(&'a Timestamps),
+    /// A message of type `SCM_PIDFD`, containing a pidfd for the peer of a
+    /// Unix domain socket.  Requires `SO_PASSPIDFD` and Linux 6.5.
+    ///
+    /// The receiver owns the file descriptor and is responsible for closing it.
+    #[cfg(target_os = "linux")]
+    ScmPidfd(&'a RawFd),
+    /// A message of type `IP_PKTINFO`.  When received (with `IP_PKTINFO`
+    /// enabled) it holds the interface and addresses of the packet; when
+    /// sent it selects the outgoing interface and source address.
+    ///
+    /// [Further reading](http://man7.org/linux/man-pages/man7/ip.7.html)
+    #[cfg(target_os = "linux")]
+    IpPktInfo(&'a libc::in_pktinfo),
+    /// A message of type `IPV6_PKTINFO`, the IPv6 counterpart of `IpPktInfo`.
+    ///
+    /// [Further reading](http://man7.org/linux/man-pages/man7/ipv6.7.html)
+    #[cfg(target_os = "linux")]
+    Ipv6PktInfo(&'a libc::in6_pktinfo),
+    /// A message of type `IP_TTL`.  Received with `IP_RECVTTL` enabled, or
+    /// sent to override the time-to-live of a single packet.
+    #[cfg(target_os = "linux")]
+    IpTtl(&'a c_int),
+    /// A message of type `IP_TOS`.  Received with `IP_RECVTOS` enabled, or
+    /// sent to override the type-of-service of a single packet.
+    #[cfg(target_os = "linux")]
+    IpTos(&'a u8),
+    /// A message of type `UDP_SEGMENT`, which asks the kernel to split the
+    /// sent buffer into datagrams of the given size (UDP GSO).
+    ///
+    /// Requires Linux 4.18.
+    #[cfg(target_os = "linux")]
+    UdpGsoSegments(&'a u16),
+    /// A message of type `UDP_GRO`, containing the size of the segments that
+    /// were coalesced into the received buffer.  Enabled with `UDP_GRO`.
+    ///
+    /// Requires Linux 5.0.
+    #[cfg(target_os = "linux")]
+    UdpGroSegments(&'a c_int),
+    /// A message of type `IP_RECVERR`, read from the error queue with
+    /// `MSG_ERRQUEUE`.  Contains the extended error and, if known, the
+    /// address of the node that caused it.
+    ///
+    /// Only the `sock_extended_err` is encoded when this message is sent.
+    /// [Further reading](http://man7.org/linux/man-pages/man7/ip.7.html)
+    #[cfg(target_os = "linux")]
+    IpRecvErr(libc::sock_extended_err, Option<SockAddr>),
+    /// A message of type `IPV6_RECVERR`, the IPv6 counterpart of `IpRecvErr`.
+    #[cfg(target_os = "linux")]
+    Ipv6RecvErr(libc::sock_extended_err, Option<SockAddr>),
     #[doc(hidden)]
     Unknown(UnknownCmsg<'a>),
 }
 
+// Splits the payload of an `IP_RECVERR` or `IPV6_RECVERR` message into the
+// extended error and the offending address that follows it.  Returns `None`
+// if the payload is too short to hold the extended error.
+#[cfg(target_os = "linux")]
+fn decode_extended_err(data: &[u8]) -> Option<(libc::sock_extended_err, Option<SockAddr>)> {
+    let err_size = mem::size_of::<libc::sock_extended_err>();
+    if data.len() < err_size {
+        return None;
+    }
+    let err = unsafe { ptr::read_unaligned(data.as_ptr() as *const libc::sock_extended_err) };
+    let offender = &data[err_size..];
+    if offender.len() < mem::size_of::<sa_family_t>() {
+        return Some((err, None));
+    }
+    let family = unsafe { ptr::read_unaligned(offender.as_ptr() as *const sa_family_t) };
+    let addr = match family as c_int {
+        libc::AF_INET if offender.len() >= mem::size_of::<sockaddr_in>() => {
+            let sin = unsafe { ptr::read_unaligned(offender.as_ptr() as *const sockaddr_in) };
+            Some(SockAddr::Inet(InetAddr::V4(sin)))
+        },
+        libc::AF_INET6 if offender.len() >= mem::size_of::<sockaddr_in6>() => {
+            let sin6 = unsafe { ptr::read_unaligned(offender.as_ptr() as *const sockaddr_in6) };
+            Some(SockAddr::Inet(InetAddr::V6(sin6)))
+        },
+        _ => None,
+    };
+    Some((err, addr))
+}
+
+// Views any value as its raw bytes.
+fn as_bytes<T: ?Sized>(value: &T) -> &[u8] {
+    unsafe {
+        slice::from_raw_parts(value as *const T as *const u8, mem::size_of_val(value))
+    }
+}
+
 // An opaque structure used to prevent cmsghdr from being a public type
 #[doc(hidden)]
 #[allow(missing_debug_implementations)]
@@ -525,15 +870,68 @@ impl<'a> ControlMessage<'a> {
 
     /// The value of CMSG_LEN on this message.
     fn len(&self) -> usize {
-        cmsg_align(mem::size_of::<cmsghdr>()) + match *self {
+        cmsg_align(mem::size_of::<cmsghdr>()) + self.parts().2.len()
+    }
+
+    /// The level, type and payload of this message.
+    fn parts(&self) -> (c_int, c_int, &[u8]) {
+        match *self {
             ControlMessage::ScmRights(fds) => {
-                mem::size_of_val(fds)
+                (libc::SOL_SOCKET, libc::SCM_RIGHTS, as_bytes(fds))
             },
             ControlMessage::ScmTimestamp(t) => {
-                mem::size_of_val(t)
+                (libc::SOL_SOCKET, libc::SCM_TIMESTAMP, as_bytes(t))
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::ScmCredentials(cred) => {
+                (libc::SOL_SOCKET, libc::SCM_CREDENTIALS, as_bytes(cred))
+            },
+            #[cfg(target_os = "linux")]
+            ControlMessage::ScmTimestampns(t) => {
+                (libc::SOL_SOCKET, libc::SCM_TIMESTAMPNS, as_bytes(t))
+            },
+            #[cfg(target_os = "linux")]
+            ControlMessage::ScmTimestamping(t) => {
+                (libc::SOL_SOCKET, libc::SCM_TIMESTAMPING, as_bytes(t))
             },
-            ControlMessage::Unknown(UnknownCmsg(_, bytes)) => {
-                mem::size_of_val(bytes)
+            #[cfg(target_os = "linux")]
+            ControlMessage::ScmPidfd(fd) => {
+                (libc::SOL_SOCKET, SCM_PIDFD, as_bytes(fd))
+            },
+            #[cfg(target_os = "linux")]
+            ControlMessage::IpPktInfo(info) => {
+                (libc::IPPROTO_IP, libc::IP_PKTINFO, as_bytes(info))
+            },
+            #[cfg(target_os = "linux")]
+            ControlMessage::Ipv6PktInfo(info) => {
+                (libc::IPPROTO_IPV6, libc::IPV6_PKTINFO, as_bytes(info))
+            },
+            #[cfg(target_os = "linux")]
+            ControlMessage::IpTtl(ttl) => {
+                (libc::IPPROTO_IP, libc::IP_TTL, as_bytes(ttl))
+            },
+            #[cfg(target_os = "linux")]
+            ControlMessage::IpTos(tos) => {
+                (libc::IPPROTO_IP, libc::IP_TOS, as_bytes(tos))
+            },
+            #[cfg(target_os = "linux")]
+            ControlMessage::UdpGsoSegments(size) => {
+                (libc::IPPROTO_UDP, libc::UDP_SEGMENT, as_bytes(size))
+            },
+            #[cfg(target_os = "linux")]
+            ControlMessage::UdpGroSegments(size) => {
+                (libc::IPPROTO_UDP, libc::UDP_GRO, as_bytes(size))
+            },
+            #[cfg(target_os = "linux")]
+            ControlMessage::IpRecvErr(ref err, _) => {
+                (libc::IPPROTO_IP, libc::IP_RECVERR, as_bytes(err))
+            },
+            #[cfg(target_os = "linux")]
+            ControlMessage::Ipv6RecvErr(ref err, _) => {
+                (libc::IPPROTO_IPV6, libc::IPV6_RECVERR, as_bytes(err))
+            },
+            ControlMessage::Unknown(UnknownCmsg(orig_cmsg, bytes)) => {
+                (orig_cmsg.cmsg_level, orig_cmsg.cmsg_type, bytes)
             }
         }
     }
@@ -541,56 +939,23 @@ impl<'a> ControlMessage<'a> {
     // Unsafe: start and end of buffer must be cmsg_align'd. Updates
     // the provided slice; panics if the buffer is too small.
     unsafe fn encode_into(&self, buf: &mut [u8]) {
-        match *self {
-            ControlMessage::ScmRights(fds) => {
-                let cmsg = cmsghdr {
-                    cmsg_len: self.len() as _,
-                    cmsg_level: libc::SOL_SOCKET,
-                    cmsg_type: libc::SCM_RIGHTS,
-                    ..mem::uninitialized()
-                };
-                let buf = copy_bytes(&cmsg, buf);
-
-                let padlen = cmsg_align(mem::size_of_val(&cmsg)) -
-                    mem::size_of_val(&cmsg);
-                let buf = pad_bytes(padlen, buf);
-
-                let buf = copy_bytes(fds, buf);
-
-                let padlen = self.space() - self.len();
-                pad_bytes(padlen, buf);
-            },
-            ControlMessage::ScmTimestamp(t) => {
-                let cmsg = cmsghdr {
-                    cmsg_len: self.len() as _,
-                    cmsg_level: libc::SOL_SOCKET,
-                    cmsg_type: libc::SCM_TIMESTAMP,
-                    ..mem::uninitialized()
-                };
-                let buf = copy_bytes(&cmsg, buf);
-
-                let padlen = cmsg_align(mem::size_of_val(&cmsg)) -
-                    mem::size_of_val(&cmsg);
-                let buf = pad_bytes(padlen, buf);
-
-                let buf = copy_bytes(t, buf);
-
-                let padlen = self.space() - self.len();
-                pad_bytes(padlen, buf);
-            },
-            ControlMessage::Unknown(UnknownCmsg(orig_cmsg, bytes)) => {
-                let buf = copy_bytes(orig_cmsg, buf);
+        let (level, ty, data) = self.parts();
+        let cmsg = cmsghdr {
+            cmsg_len: self.len() as _,
+            cmsg_level: level,
+            cmsg_type: ty,
+            ..mem::uninitialized()
+        };
+        let buf = copy_bytes(&cmsg, buf);
 
-                let padlen = cmsg_align(mem::size_of_val(&orig_cmsg)) -
-                    mem::size_of_val(&orig_cmsg);
-                let buf = pad_bytes(padlen, buf);
+        let padlen = cmsg_align(mem::size_of_val(&cmsg)) -
+            mem::size_of_val(&cmsg);
+        let buf = pad_bytes(padlen, buf);
 
-                let buf = copy_bytes(bytes, buf);
+        let buf = copy_bytes(data, buf);
 
-                let padlen = self.space() - self.len();
-                pad_bytes(padlen, buf);
-            }
-        }
+        let padlen = self.space() - self.len();
+        pad_bytes(padlen, buf);
     }
 }
 
@@ -601,6 +966,15 @@ impl<'a> ControlMessage<'a> {
 ///
 /// Allocates if cmsgs is nonempty.
 pub fn sendmsg<'a>(fd: RawFd, iov: &[IoVec<&'a [u8]>], cmsgs: &[ControlMessage<'a>], flags: MsgFlags, addr: Option<&'a SockAddr>) -> Result<usize> {
+    let cmsg_buffer = encode_cmsgs(cmsgs);
+    let mhdr = unsafe { pack_send_msghdr(iov, &cmsg_buffer, addr) };
+    let ret = unsafe { libc::sendmsg(fd, &mhdr, flags.bits()) };
+
+    Errno::result(ret).map(|r| r as usize)
+}
+
+// Encodes `cmsgs` back to back into a freshly allocated buffer.
+fn encode_cmsgs(cmsgs: &[ControlMessage]) -> Vec<u8> {
     let mut capacity = 0;
     for cmsg in cmsgs {
         capacity += cmsg.space();
@@ -615,59 +989,52 @@ pub fn sendmsg<'a>(fd: RawFd, iov: &[IoVec<&'a [u8]>], cmsgs: &[ControlMessage<'
     {
         let mut ofs = 0;
         for cmsg in cmsgs {
-            let mut ptr = &mut cmsg_buffer[ofs..];
+            let ptr = &mut cmsg_buffer[ofs..];
             unsafe {
                 cmsg.encode_into(ptr);
             }
             ofs += cmsg.space();
         }
     }
+    cmsg_buffer
+}
 
+// Unsafe: the returned msghdr points into `iov`, `cmsg_buffer` and `addr`,
+// which must outlive it.
+unsafe fn pack_send_msghdr(iov: &[IoVec<&[u8]>], cmsg_buffer: &[u8], addr: Option<&SockAddr>) -> msghdr {
     let (name, namelen) = match addr {
-        Some(addr) => { let (x, y) = unsafe { addr.as_ffi_pair() }; (x as *const _, y) }
+        Some(addr) => { let (x, y) = addr.as_ffi_pair(); (x as *const _, y) }
         None => (ptr::null(), 0),
     };
 
-    let cmsg_ptr = if capacity > 0 {
+    let cmsg_ptr = if !cmsg_buffer.is_empty() {
         cmsg_buffer.as_ptr() as *const c_void
     } else {
         ptr::null()
     };
 
-    let mhdr = unsafe {
-        let mut mhdr: msghdr = mem::uninitialized();
-        mhdr.msg_name =  name as *mut _;
-        mhdr.msg_namelen =  namelen;
-        mhdr.msg_iov =  iov.as_ptr() as *mut _;
-        mhdr.msg_iovlen =  iov.len() as _;
-        mhdr.msg_control =  cmsg_ptr as *mut _;
-        mhdr.msg_controllen =  capacity as _;
-        mhdr.msg_flags =  0;
-        mhdr
-    };
-    let ret = unsafe { libc::sendmsg(fd, &mhdr, flags.bits()) };
-
-    Errno::result(ret).map(|r| r as usize)
+    let mut mhdr: msghdr = mem::zeroed();
+    mhdr.msg_name =  name as *mut _;
+    mhdr.msg_namelen =  namelen;
+    mhdr.msg_iov =  iov.as_ptr() as *mut _;
+    mhdr.msg_iovlen =  iov.len() as _;
+    mhdr.msg_control =  cmsg_ptr as *mut _;
+    mhdr.msg_controllen =  cmsg_buffer.len() as _;
+    mhdr.msg_flags =  0;
+    mhdr
 }
 
 /// Receive message in scatter-gather vectors from a socket, and
 /// optionally receive ancillary data into the provided buffer.
-/// If no ancillary data is desired, use () as the type parameter.
-pub fn recvmsg(fd: RawFd, iov: &[IoVec<&mut [u8]>], cmsg_size: usize, flags: MsgFlags) -> Result<RecvMsg> {
+///
+/// Size `cmsg_buffer` with [`cmsg_space!`](../../macro.cmsg_space.html),
+/// or pass `None` if no ancillary data is desired.
+pub fn recvmsg<'a>(fd: RawFd, iov: &[IoVec<&mut [u8]>], mut cmsg_buffer: Option<&'a mut CmsgBuffer>, flags: MsgFlags) -> Result<RecvMsg<'a>> {
     let mut address: sockaddr_storage = unsafe { mem::uninitialized() };
 
-    let msg_controllen = if cmsg_size > 0 {
-        mem::size_of::<cmsghdr>() + cmsg_size
-    } else {
-        0
-    };
-    let mut cmsg_buffer = Vec::<u8>::with_capacity(msg_controllen);
-
-    let msg_control = if msg_controllen > 0 {
-        unsafe { cmsg_buffer.set_len(msg_controllen) };
-        cmsg_buffer.as_ptr() as *mut c_void
-    } else {
-        ptr::null_mut()
+    let (msg_control, msg_controllen) = match cmsg_buffer {
+        Some(ref mut buf) => (buf.as_mut_ptr(), buf.space()),
+        None => (ptr::null_mut(), 0),
     };
 
     let mut mhdr = unsafe {
@@ -682,17 +1049,138 @@ pub fn recvmsg(fd: RawFd, iov: &[IoVec<&mut [u8]>], cmsg_size: usize, flags: Msg
         mhdr
     };
     let ret = unsafe { libc::recvmsg(fd, &mut mhdr, flags.bits()) };
+    let bytes = try!(Errno::result(ret)) as usize;
+
+    // Only the part of the buffer the kernel filled in holds messages.
+    let cmsg_buffer: &'a [u8] = match cmsg_buffer {
+        Some(buf) if !mhdr.msg_control.is_null() => buf.bytes(mhdr.msg_controllen as usize),
+        _ => &[],
+    };
 
-    Ok(unsafe { RecvMsg {
-        bytes: try!(Errno::result(ret)) as usize,
+    Ok(RecvMsg {
+        bytes,
         cmsg_buffer,
-        address: sockaddr_storage_to_addr(&address,
-                                          mhdr.msg_namelen as usize).ok(),
+        address: unsafe {
+            sockaddr_storage_to_addr(&address, mhdr.msg_namelen as usize).ok()
+        },
         flags: MsgFlags::from_bits_truncate(mhdr.msg_flags),
-    } })
+    })
 }
 
 
+/// One message of a [`sendmmsg`](fn.sendmmsg.html) batch.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[allow(missing_debug_implementations)]
+pub struct SendMmsgData<'a> {
+    /// The data to send, as for `sendmsg`.
+    pub iov: &'a [IoVec<&'a [u8]>],
+    /// Control messages to send along with the data.
+    pub cmsgs: &'a [ControlMessage<'a>],
+    /// The destination, for unconnected sockets.
+    pub addr: Option<&'a SockAddr>,
+}
+
+/// Send several messages on a socket with a single system call.
+///
+/// Returns the number of bytes sent for each message that was sent; this may
+/// be fewer messages than were passed in, in which case the caller should
+/// retry with the remainder.  An error is only returned if no message could
+/// be sent.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/sendmmsg.2.html)
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn sendmmsg(fd: RawFd, data: &[SendMmsgData], flags: MsgFlags) -> Result<Vec<usize>> {
+    let cmsg_buffers: Vec<Vec<u8>> = data.iter()
+        .map(|d| encode_cmsgs(d.cmsgs))
+        .collect();
+    let mut msgs: Vec<libc::mmsghdr> = data.iter()
+        .zip(cmsg_buffers.iter())
+        .map(|(d, cmsg_buffer)| libc::mmsghdr {
+            msg_hdr: unsafe { pack_send_msghdr(d.iov, cmsg_buffer, d.addr) },
+            msg_len: 0,
+        })
+        .collect();
+
+    let ret = unsafe {
+        libc::sendmmsg(fd, msgs.as_mut_ptr(), msgs.len() as _, flags.bits() as _)
+    };
+    let sent = try!(Errno::result(ret)) as usize;
+
+    Ok(msgs[..sent].iter().map(|m| m.msg_len as usize).collect())
+}
+
+/// One receive slot of a [`recvmmsg`](fn.recvmmsg.html) batch.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[allow(missing_debug_implementations)]
+pub struct RecvMmsgData<'a> {
+    /// The buffers to receive the data into, as for `recvmsg`.
+    pub iov: &'a [IoVec<&'a mut [u8]>],
+    /// The buffer to receive control messages into, if any.
+    pub cmsg_buffer: Option<&'a mut CmsgBuffer>,
+}
+
+/// Receive several messages from a socket with a single system call.
+///
+/// Fills the slots of `data` in order and returns one `RecvMsg` for each
+/// message received.  With a `timeout`, waiting stops once it has expired
+/// and at least one message has arrived; note that the timeout is only
+/// checked after each message, so a blocking socket may still wait forever
+/// for the first one.
+///
+/// [Further reading](http://man7.org/linux/man-pages/man2/recvmmsg.2.html)
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn recvmmsg<'a, 'b>(fd: RawFd, data: &'b mut [RecvMmsgData<'a>], flags: MsgFlags, timeout: Option<TimeSpec>) -> Result<Vec<RecvMsg<'b>>> {
+    let mut addresses: Vec<sockaddr_storage> = vec![unsafe { mem::zeroed() }; data.len()];
+    let mut msgs: Vec<libc::mmsghdr> = data.iter_mut()
+        .zip(addresses.iter_mut())
+        .map(|(d, address)| {
+            let (msg_control, msg_controllen) = match d.cmsg_buffer {
+                Some(ref mut buf) => (buf.as_mut_ptr(), buf.space()),
+                None => (ptr::null_mut(), 0),
+            };
+            let mut mhdr: msghdr = unsafe { mem::zeroed() };
+            mhdr.msg_name = address as *mut _ as *mut _;
+            mhdr.msg_namelen = mem::size_of::<sockaddr_storage>() as socklen_t;
+            mhdr.msg_iov = d.iov.as_ptr() as *mut _;
+            mhdr.msg_iovlen = d.iov.len() as _;
+            mhdr.msg_control = msg_control as *mut _;
+            mhdr.msg_controllen = msg_controllen as _;
+            libc::mmsghdr { msg_hdr: mhdr, msg_len: 0 }
+        })
+        .collect();
+
+    let mut timeout = timeout.map(|t| *t.as_ref());
+    let timeout_ptr = match timeout {
+        Some(ref mut t) => t as *mut libc::timespec,
+        None => ptr::null_mut(),
+    };
+
+    let ret = unsafe {
+        libc::recvmmsg(fd, msgs.as_mut_ptr(), msgs.len() as _, flags.bits() as _, timeout_ptr)
+    };
+    let received = try!(Errno::result(ret)) as usize;
+
+    let data: &'b [RecvMmsgData<'a>] = data;
+    Ok(msgs[..received].iter()
+        .zip(data.iter())
+        .zip(addresses.iter())
+        .map(|((m, d), address)| {
+            let cmsg_buffer: &'b [u8] = match d.cmsg_buffer {
+                Some(ref buf) => buf.bytes(m.msg_hdr.msg_controllen as usize),
+                None => &[],
+            };
+            RecvMsg {
+                bytes: m.msg_len as usize,
+                cmsg_buffer,
+                address: unsafe {
+                    sockaddr_storage_to_addr(address, m.msg_hdr.msg_namelen as usize).ok()
+                },
+                flags: MsgFlags::from_bits_truncate(m.msg_hdr.msg_flags),
+            }
+        })
+        .collect())
+}
+
 /// Create an endpoint for communication
 ///
 /// The `protocol` specifies a particular protocol to be used with the
@@ -713,11 +1201,13 @@ pub fn socket<T: Into<Option<SockProtocol>>>(domain: AddressFamily, ty: SockType
     // little easier to understand by separating it out. So we have to merge these bitfields
     // here.
     let mut ty = ty as c_int;
-    ty |= flags.bits();
+    ty |= atomic_sock_flags(flags).bits();
 
     let res = unsafe { libc::socket(domain as c_int, ty, protocol) };
 
-    Errno::result(res)
+    let fd = Errno::result(res)?;
+    emulate_sock_flags(&[fd], flags)?;
+    Ok(fd)
 }
 
 /// Create a pair of connected sockets
@@ -734,16 +1224,81 @@ pub fn socketpair<T: Into<Option<SockProtocol>>>(domain: AddressFamily, ty: Sock
     // little easier to understand by separating it out. So we have to merge these bitfields
     // here.
     let mut ty = ty as c_int;
-    ty |= flags.bits();
+    ty |= atomic_sock_flags(flags).bits();
 
     let mut fds = [-1, -1];
 
     let res = unsafe { libc::socketpair(domain as c_int, ty, protocol, fds.as_mut_ptr()) };
     Errno::result(res)?;
+    emulate_sock_flags(&fds, flags)?;
 
     Ok((fds[0], fds[1]))
 }
 
+/// The part of `flags` that the running kernel can apply atomically when
+/// creating a socket.  Linux older than 2.6.27 rejects `SOCK_CLOEXEC` and
+/// `SOCK_NONBLOCK`, so `emulate_sock_flags` sets them afterwards instead.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn atomic_sock_flags(flags: SockFlag) -> SockFlag {
+    if features::socket_atomic_cloexec() {
+        flags
+    } else {
+        flags - (SockFlag::SOCK_CLOEXEC | SockFlag::SOCK_NONBLOCK)
+    }
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+fn atomic_sock_flags(flags: SockFlag) -> SockFlag {
+    flags
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn emulate_sock_flags(fds: &[RawFd], flags: SockFlag) -> Result<()> {
+    if atomic_sock_flags(flags) == flags {
+        return Ok(());
+    }
+    set_sock_flags(fds, flags)
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+fn emulate_sock_flags(_fds: &[RawFd], _flags: SockFlag) -> Result<()> {
+    Ok(())
+}
+
+// Sets `SOCK_CLOEXEC` and `SOCK_NONBLOCK` with `fcntl`, closing `fds` on
+// failure.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn set_sock_flags(fds: &[RawFd], flags: SockFlag) -> Result<()> {
+    use fcntl::{fcntl, FdFlag, OFlag};
+    use fcntl::FcntlArg::{F_GETFL, F_SETFD, F_SETFL};
+    use unistd::close;
+
+    let set_flags = |fd| -> Result<()> {
+        if flags.contains(SockFlag::SOCK_CLOEXEC) {
+            fcntl(fd, F_SETFD(FdFlag::FD_CLOEXEC))?;
+        }
+        if flags.contains(SockFlag::SOCK_NONBLOCK) {
+            let fl = OFlag::from_bits_truncate(fcntl(fd, F_GETFL)?);
+            fcntl(fd, F_SETFL(fl | OFlag::O_NONBLOCK))?;
+        }
+        Ok(())
+    };
+    let mut res = Ok(());
+    for &fd in fds {
+        res = set_flags(fd);
+        if res.is_err() {
+            break;
+        }
+    }
+
+    if res.is_err() {
+        for &fd in fds {
+            let _ = close(fd);
+        }
+    }
+    res
+}
+
 /// Listen for connections on a socket
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/listen.html)
@@ -782,11 +1337,33 @@ pub fn accept(sockfd: RawFd) -> Result<RawFd> {
           target_os = "linux",
           target_os = "openbsd"))]
 pub fn accept4(sockfd: RawFd, flags: SockFlag) -> Result<RawFd> {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        if !features::accept_atomic_cloexec() {
+            return accept4_emulated(sockfd, flags);
+        }
+    }
+
     let res = unsafe { libc::accept4(sockfd, ptr::null_mut(), ptr::null_mut(), flags.bits()) };
 
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        // Some architectures only gained accept4 after 2.6.28
+        if res == -1 && Errno::last() == Errno::ENOSYS {
+            return accept4_emulated(sockfd, flags);
+        }
+    }
+
     Errno::result(res)
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn accept4_emulated(sockfd: RawFd, flags: SockFlag) -> Result<RawFd> {
+    let fd = accept(sockfd)?;
+    set_sock_flags(&[fd], flags)?;
+    Ok(fd)
+}
+
 /// Initiate a connection on a socket
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/connect.html)
@@ -972,14 +1549,23 @@ pub unsafe fn sockaddr_storage_to_addr(
         }
         libc::AF_UNIX => {
             let sun = *(addr as *const _ as *const sockaddr_un);
-            let pathlen = len - offset_of!(sockaddr_un, sun_path);
-            Ok(SockAddr::Unix(UnixAddr(sun, pathlen)))
+            Ok(SockAddr::Unix(UnixAddr::from_raw_parts(sun, len)))
         }
         #[cfg(any(target_os = "linux", target_os = "android"))]
         libc::AF_NETLINK => {
             use libc::sockaddr_nl;
             Ok(SockAddr::Netlink(NetlinkAddr(*(addr as *const _ as *const sockaddr_nl))))
         }
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        libc::AF_PACKET => {
+            use libc::sockaddr_ll;
+            Ok(SockAddr::Link(LinkAddr(*(addr as *const _ as *const sockaddr_ll))))
+        }
+        #[cfg(target_os = "linux")]
+        libc::AF_VSOCK => {
+            use libc::sockaddr_vm;
+            Ok(SockAddr::Vsock(VsockAddr(*(addr as *const _ as *const sockaddr_vm))))
+        }
         af => panic!("unexpected address family {}", af),
     }
 }
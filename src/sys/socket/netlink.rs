@@ -0,0 +1,601 @@
+//! Building and parsing netlink messages
+//!
+//! Netlink messages are a `nlmsghdr` followed by a family-specific header
+//! (such as `ifinfomsg` for rtnetlink) and a list of type-length-value
+//! attributes, which may themselves contain nested attributes.
+//! [`NlMsgBuilder`](struct.NlMsgBuilder.html) writes such messages,
+//! [`NlMsgIter`](struct.NlMsgIter.html) and
+//! [`NlAttrIter`](struct.NlAttrIter.html) read them back, and
+//! [`NetlinkSocket`](struct.NetlinkSocket.html) takes care of sequence
+//! numbers, multipart replies and mapping `NLMSG_ERROR` to `Errno`.
+//!
+//! [Further reading](http://man7.org/linux/man-pages/man7/netlink.7.html)
+use {Error, Result};
+use errno::Errno;
+use libc::{self, c_int};
+use std::{cmp, fmt, mem, ptr, str};
+use std::os::unix::io::{AsRawFd, RawFd};
+use sys::socket::{self, AddressFamily, MsgFlags, NetlinkAddr, SockAddr, SockFlag,
+                  SockProtocol, SockType};
+use unistd;
+
+libc_bitflags!{
+    /// Flags for the `nlmsg_flags` field of a netlink message header.
+    ///
+    /// Several of the flags share a value and are only meaningful for
+    /// particular requests: the `ROOT`, `MATCH` and `ATOMIC` flags apply to
+    /// `GET` requests, while `REPLACE`, `EXCL`, `CREATE` and `APPEND` apply to
+    /// `NEW` requests.
+    pub struct NlmFlags: u16 {
+        /// The message is a request.  Set on every message sent to the kernel.
+        NLM_F_REQUEST as u16;
+        /// The message is part of a multipart reply terminated by `NLMSG_DONE`.
+        NLM_F_MULTI as u16;
+        /// Request an acknowledgement on success.
+        NLM_F_ACK as u16;
+        /// Echo this request back to the sender.
+        NLM_F_ECHO as u16;
+        /// The dump was inconsistent because the data changed while dumping.
+        NLM_F_DUMP_INTR as u16;
+        /// The dump was filtered as requested.
+        NLM_F_DUMP_FILTERED as u16;
+        /// Return the complete table instead of a single entry.
+        NLM_F_ROOT as u16;
+        /// Return all entries matching the criteria in the request.
+        NLM_F_MATCH as u16;
+        /// Return an atomic snapshot of the table.
+        NLM_F_ATOMIC as u16;
+        /// Shorthand for `NLM_F_ROOT | NLM_F_MATCH`.
+        NLM_F_DUMP as u16;
+        /// Replace an existing object.
+        NLM_F_REPLACE as u16;
+        /// Do not touch the object if it already exists.
+        NLM_F_EXCL as u16;
+        /// Create the object if it does not exist.
+        NLM_F_CREATE as u16;
+        /// Add to the end of the object list.
+        NLM_F_APPEND as u16;
+    }
+}
+
+/// The alignment of netlink messages and attributes.
+const NLMSG_ALIGNTO: usize = 4;
+
+/// The value of `NLMSG_ALIGN` and `NLA_ALIGN`: rounds `len` up to the
+/// alignment of netlink messages and attributes.
+pub fn nlmsg_align(len: usize) -> usize {
+    (len + NLMSG_ALIGNTO - 1) & !(NLMSG_ALIGNTO - 1)
+}
+
+const NLMSG_HDRLEN: usize = 16;
+const NLA_HDRLEN: usize = 4;
+
+// Appends the raw bytes of `value` to `buf`, padded to the netlink alignment.
+fn push_aligned(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(bytes);
+    let padded = nlmsg_align(buf.len());
+    buf.resize(padded, 0);
+}
+
+// Reads a `T` from the start of `bytes`, if it is long enough.
+fn read_struct<T: Copy>(bytes: &[u8]) -> Option<T> {
+    if bytes.len() < mem::size_of::<T>() {
+        return None;
+    }
+    Some(unsafe { ptr::read_unaligned(bytes.as_ptr() as *const T) })
+}
+
+fn write_u16(buf: &mut [u8], offset: usize, value: u16) {
+    buf[offset..offset + 2].copy_from_slice(super::as_bytes(&value));
+}
+
+fn write_u32(buf: &mut [u8], offset: usize, value: u32) {
+    buf[offset..offset + 4].copy_from_slice(super::as_bytes(&value));
+}
+
+/// Builds a single netlink message: the `nlmsghdr`, an optional
+/// family-specific header and a list of attributes.
+///
+/// The length, sequence number and port id in the header are filled in when
+/// the message is sent with [`NetlinkSocket`](struct.NetlinkSocket.html) or
+/// finished with [`finish`](#method.finish).
+///
+/// # Examples
+///
+/// ```
+/// use nix::sys::socket::netlink::{NlMsgBuilder, NlmFlags};
+///
+/// # const RTM_GETLINK: u16 = 18;
+/// # const IFLA_IFNAME: u16 = 3;
+/// let mut msg = NlMsgBuilder::new(RTM_GETLINK, NlmFlags::NLM_F_REQUEST);
+/// msg.push_header(&[0u8; 16])
+///    .attr_str(IFLA_IFNAME, "lo");
+/// let bytes = msg.finish(1);
+/// assert_eq!(bytes.len(), 16 + 16 + 8);
+/// ```
+#[derive(Clone, Debug)]
+pub struct NlMsgBuilder {
+    buf: Vec<u8>,
+    nests: Vec<usize>,
+}
+
+impl NlMsgBuilder {
+    /// Starts a message of type `ty`.
+    pub fn new(ty: u16, flags: NlmFlags) -> NlMsgBuilder {
+        let mut buf = vec![0; NLMSG_HDRLEN];
+        write_u16(&mut buf, 4, ty);
+        write_u16(&mut buf, 6, flags.bits());
+        NlMsgBuilder {
+            buf,
+            nests: Vec::new(),
+        }
+    }
+
+    /// The type of the message.
+    pub fn ty(&self) -> u16 {
+        read_struct(&self.buf[4..]).unwrap()
+    }
+
+    /// The flags of the message.
+    pub fn flags(&self) -> NlmFlags {
+        NlmFlags::from_bits_truncate(read_struct(&self.buf[6..]).unwrap())
+    }
+
+    /// Replaces the flags of the message.
+    pub fn set_flags(&mut self, flags: NlmFlags) -> &mut Self {
+        write_u16(&mut self.buf, 6, flags.bits());
+        self
+    }
+
+    /// Appends a family-specific header, such as an `ifinfomsg` or
+    /// `ifaddrmsg`.  Must be called before any attribute is added.
+    pub fn push_header<T: Copy>(&mut self, header: &T) -> &mut Self {
+        push_aligned(&mut self.buf, super::as_bytes(header));
+        self
+    }
+
+    /// Appends an attribute with the given payload.
+    pub fn attr(&mut self, ty: u16, data: &[u8]) -> &mut Self {
+        let len = NLA_HDRLEN + data.len();
+        assert!(len <= u16::max_value() as usize, "netlink attribute too long");
+        let start = self.buf.len();
+        self.buf.resize(start + NLA_HDRLEN, 0);
+        write_u16(&mut self.buf, start, len as u16);
+        write_u16(&mut self.buf, start + 2, ty);
+        push_aligned(&mut self.buf, data);
+        self
+    }
+
+    /// Appends an attribute holding a `u8`.
+    pub fn attr_u8(&mut self, ty: u16, value: u8) -> &mut Self {
+        self.attr(ty, &[value])
+    }
+
+    /// Appends an attribute holding a `u16` in native byte order.
+    pub fn attr_u16(&mut self, ty: u16, value: u16) -> &mut Self {
+        self.attr(ty, super::as_bytes(&value))
+    }
+
+    /// Appends an attribute holding a `u32` in native byte order.
+    pub fn attr_u32(&mut self, ty: u16, value: u32) -> &mut Self {
+        self.attr(ty, super::as_bytes(&value))
+    }
+
+    /// Appends an attribute holding a NUL-terminated string.
+    pub fn attr_str(&mut self, ty: u16, value: &str) -> &mut Self {
+        let mut data = Vec::with_capacity(value.len() + 1);
+        data.extend_from_slice(value.as_bytes());
+        data.push(0);
+        self.attr(ty, &data)
+    }
+
+    /// Opens a nested attribute.  Every attribute added until the matching
+    /// [`end_nested`](#method.end_nested) becomes part of its payload.
+    ///
+    /// Generic netlink families expect `NLA_F_NESTED` to be set in `ty`;
+    /// rtnetlink does not.
+    pub fn begin_nested(&mut self, ty: u16) -> &mut Self {
+        let start = self.buf.len();
+        self.attr(ty, &[]);
+        self.nests.push(start);
+        self
+    }
+
+    /// Closes the innermost nested attribute.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no open nested attribute.
+    pub fn end_nested(&mut self) -> &mut Self {
+        let start = self.nests.pop().expect("end_nested without begin_nested");
+        let len = self.buf.len() - start;
+        assert!(len <= u16::max_value() as usize, "netlink attribute too long");
+        write_u16(&mut self.buf, start, len as u16);
+        self
+    }
+
+    /// Fills in the length and sequence number and returns the encoded message.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a nested attribute is still open.
+    pub fn finish(mut self, seq: u32) -> Vec<u8> {
+        assert!(self.nests.is_empty(), "unterminated nested netlink attribute");
+        let len = self.buf.len() as u32;
+        write_u32(&mut self.buf, 0, len);
+        write_u32(&mut self.buf, 8, seq);
+        self.buf
+    }
+}
+
+/// A netlink message received from the kernel, borrowed from the receive
+/// buffer.
+#[derive(Clone, Copy)]
+pub struct NlMsg<'a> {
+    header: libc::nlmsghdr,
+    payload: &'a [u8],
+}
+
+impl<'a> NlMsg<'a> {
+    /// The message type, such as `NLMSG_ERROR` or `RTM_NEWLINK`.
+    pub fn ty(&self) -> u16 {
+        self.header.nlmsg_type
+    }
+
+    /// The message flags.
+    pub fn flags(&self) -> NlmFlags {
+        NlmFlags::from_bits_truncate(self.header.nlmsg_flags)
+    }
+
+    /// The sequence number of the request this message answers.
+    pub fn seq(&self) -> u32 {
+        self.header.nlmsg_seq
+    }
+
+    /// The port id of the sender, which is 0 for the kernel.
+    pub fn pid(&self) -> u32 {
+        self.header.nlmsg_pid
+    }
+
+    /// The bytes following the `nlmsghdr`.
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Reads the family-specific header at the start of the payload, if the
+    /// payload is long enough.
+    pub fn header<T: Copy>(&self) -> Option<T> {
+        read_struct(self.payload)
+    }
+
+    /// Iterates over the attributes following a family-specific header of
+    /// type `T`.
+    pub fn attrs<T>(&self) -> NlAttrIter<'a> {
+        let offset = cmp::min(nlmsg_align(mem::size_of::<T>()), self.payload.len());
+        NlAttrIter { buf: &self.payload[offset..] }
+    }
+
+    /// For `NLMSG_ERROR` and `NLMSG_DONE` messages, the error they report:
+    /// `Some(Ok(()))` for an acknowledgement and `Some(Err(_))` for a failed
+    /// request.  Returns `None` for all other messages.
+    pub fn error(&self) -> Option<Result<()>> {
+        match self.ty() as c_int {
+            libc::NLMSG_ERROR | libc::NLMSG_DONE => {
+                let code: c_int = read_struct(self.payload).unwrap_or(0);
+                if code < 0 {
+                    Some(Err(Error::Sys(Errno::from_i32(-code))))
+                } else {
+                    Some(Ok(()))
+                }
+            },
+            _ => None,
+        }
+    }
+}
+
+impl<'a> fmt::Debug for NlMsg<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NlMsg")
+            .field("ty", &self.ty())
+            .field("flags", &self.flags())
+            .field("seq", &self.seq())
+            .field("pid", &self.pid())
+            .field("payload", &self.payload)
+            .finish()
+    }
+}
+
+/// Iterates over the netlink messages in a buffer, as returned by a single
+/// `recv` on a netlink socket.  Stops at the first malformed message.
+#[derive(Clone, Debug)]
+pub struct NlMsgIter<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> NlMsgIter<'a> {
+    /// Iterates over the messages in `buf`.
+    pub fn new(buf: &'a [u8]) -> NlMsgIter<'a> {
+        NlMsgIter { buf }
+    }
+}
+
+impl<'a> Iterator for NlMsgIter<'a> {
+    type Item = NlMsg<'a>;
+
+    fn next(&mut self) -> Option<NlMsg<'a>> {
+        let header: libc::nlmsghdr = match read_struct(self.buf) {
+            Some(header) => header,
+            None => return None,
+        };
+        let len = header.nlmsg_len as usize;
+        if len < NLMSG_HDRLEN || len > self.buf.len() {
+            self.buf = &[];
+            return None;
+        }
+        let payload = &self.buf[NLMSG_HDRLEN..len];
+        self.buf = &self.buf[cmp::min(nlmsg_align(len), self.buf.len())..];
+        Some(NlMsg { header, payload })
+    }
+}
+
+/// A netlink attribute (`nlattr` or `rtattr`), borrowed from a message.
+#[derive(Clone, Copy, Debug)]
+pub struct NlAttr<'a> {
+    ty: u16,
+    data: &'a [u8],
+}
+
+impl<'a> NlAttr<'a> {
+    /// The attribute type, without the `NLA_F_NESTED` and
+    /// `NLA_F_NET_BYTEORDER` flags.
+    pub fn ty(&self) -> u16 {
+        self.ty & libc::NLA_TYPE_MASK as u16
+    }
+
+    /// Whether the sender flagged this attribute as nested.  rtnetlink
+    /// never sets the flag even for nested attributes.
+    pub fn is_nested(&self) -> bool {
+        self.ty & libc::NLA_F_NESTED as u16 != 0
+    }
+
+    /// The payload of the attribute.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The payload as a `u8`.
+    pub fn as_u8(&self) -> Option<u8> {
+        read_struct(self.data)
+    }
+
+    /// The payload as a `u16` in native byte order.
+    pub fn as_u16(&self) -> Option<u16> {
+        read_struct(self.data)
+    }
+
+    /// The payload as a `u32` in native byte order.
+    pub fn as_u32(&self) -> Option<u32> {
+        read_struct(self.data)
+    }
+
+    /// The payload as a `u64` in native byte order.
+    pub fn as_u64(&self) -> Option<u64> {
+        read_struct(self.data)
+    }
+
+    /// The payload as a string, without the trailing NUL.
+    pub fn as_str(&self) -> Option<&'a str> {
+        let data = match self.data.iter().position(|&b| b == 0) {
+            Some(nul) => &self.data[..nul],
+            None => self.data,
+        };
+        str::from_utf8(data).ok()
+    }
+
+    /// Iterates over the attributes nested in this one.
+    pub fn nested(&self) -> NlAttrIter<'a> {
+        NlAttrIter { buf: self.data }
+    }
+}
+
+/// Iterates over a list of netlink attributes.  Stops at the first malformed
+/// attribute.
+#[derive(Clone, Debug)]
+pub struct NlAttrIter<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> NlAttrIter<'a> {
+    /// Iterates over the attributes in `buf`.
+    pub fn new(buf: &'a [u8]) -> NlAttrIter<'a> {
+        NlAttrIter { buf }
+    }
+
+    /// Returns the first remaining attribute of the given type.
+    pub fn find_attr(mut self, ty: u16) -> Option<NlAttr<'a>> {
+        Iterator::find(&mut self, |attr| attr.ty() == ty)
+    }
+}
+
+impl<'a> Iterator for NlAttrIter<'a> {
+    type Item = NlAttr<'a>;
+
+    fn next(&mut self) -> Option<NlAttr<'a>> {
+        let header: libc::nlattr = match read_struct(self.buf) {
+            Some(header) => header,
+            None => return None,
+        };
+        let len = header.nla_len as usize;
+        if len < NLA_HDRLEN || len > self.buf.len() {
+            self.buf = &[];
+            return None;
+        }
+        let data = &self.buf[NLA_HDRLEN..len];
+        self.buf = &self.buf[cmp::min(nlmsg_align(len), self.buf.len())..];
+        Some(NlAttr { ty: header.nla_type, data })
+    }
+}
+
+/// A netlink socket that numbers its requests and collects the replies.
+///
+/// # Examples
+///
+/// ```
+/// use nix::sys::socket::SockProtocol;
+/// use nix::sys::socket::netlink::{NetlinkSocket, NlMsgBuilder, NlmFlags};
+///
+/// # const RTM_GETLINK: u16 = 18;
+/// let mut sock = NetlinkSocket::new(SockProtocol::NetlinkRoute).unwrap();
+/// let mut msg = NlMsgBuilder::new(RTM_GETLINK, NlmFlags::NLM_F_DUMP);
+/// msg.push_header(&[0u8; 16]);
+/// let mut links = 0;
+/// sock.request(msg, |_| { links += 1; Ok(()) }).unwrap();
+/// assert!(links >= 1);
+/// ```
+#[derive(Debug)]
+pub struct NetlinkSocket {
+    fd: RawFd,
+    seq: u32,
+    buf: Vec<u8>,
+}
+
+impl NetlinkSocket {
+    /// Opens and binds a netlink socket for the given protocol family.
+    pub fn new(protocol: SockProtocol) -> Result<NetlinkSocket> {
+        let fd = socket::socket(AddressFamily::Netlink, SockType::Raw, SockFlag::SOCK_CLOEXEC,
+                                protocol)?;
+        let sock = NetlinkSocket {
+            fd,
+            seq: 0,
+            buf: vec![0; 32768],
+        };
+        socket::bind(fd, &SockAddr::Netlink(NetlinkAddr::new(0, 0)))?;
+        Ok(sock)
+    }
+
+    /// Sends `msg` to the kernel and returns the sequence number it was
+    /// given.  `NLM_F_REQUEST` is added to the message flags.
+    pub fn send(&mut self, mut msg: NlMsgBuilder) -> Result<u32> {
+        self.seq = self.seq.wrapping_add(1);
+        let flags = msg.flags() | NlmFlags::NLM_F_REQUEST;
+        msg.set_flags(flags);
+        let bytes = msg.finish(self.seq);
+        let kernel = SockAddr::Netlink(NetlinkAddr::new(0, 0));
+        socket::sendto(self.fd, &bytes, &kernel, MsgFlags::empty())?;
+        Ok(self.seq)
+    }
+
+    /// Receives one datagram, which may hold several messages, growing the
+    /// receive buffer as needed.
+    pub fn recv<'a>(&'a mut self) -> Result<NlMsgIter<'a>> {
+        let len = socket::recv(self.fd, &mut self.buf[..],
+                               MsgFlags::MSG_PEEK | MsgFlags::MSG_TRUNC)?;
+        if len > self.buf.len() {
+            self.buf.resize(len, 0);
+        }
+        let len = socket::recv(self.fd, &mut self.buf[..], MsgFlags::empty())?;
+        Ok(NlMsgIter::new(&self.buf[..len]))
+    }
+
+    /// Sends `msg` with `NLM_F_ACK` and passes every reply to `f` until the
+    /// request is acknowledged or, for dumps, until `NLMSG_DONE`.
+    ///
+    /// An `NLMSG_ERROR` reply is returned as the corresponding `Errno`, as is
+    /// the first error returned by `f` or `ENOBUFS` for `NLMSG_OVERRUN`.
+    /// After such an error the remaining replies are still read up to the
+    /// final `NLMSG_DONE` or acknowledgement, so that the socket can be
+    /// reused.  Only a failure to receive returns early.  Messages with
+    /// another sequence number, such as multicast notifications, are skipped.
+    pub fn request<F>(&mut self, mut msg: NlMsgBuilder, mut f: F) -> Result<()>
+        where F: FnMut(&NlMsg) -> Result<()>
+    {
+        let flags = msg.flags() | NlmFlags::NLM_F_ACK;
+        msg.set_flags(flags);
+        let seq = self.send(msg)?;
+
+        let mut result = Ok(());
+        loop {
+            for msg in self.recv()? {
+                if msg.seq() != seq {
+                    continue;
+                }
+                match msg.ty() as c_int {
+                    libc::NLMSG_NOOP => (),
+                    libc::NLMSG_OVERRUN => {
+                        if result.is_ok() {
+                            result = Err(Error::Sys(Errno::ENOBUFS));
+                        }
+                    },
+                    libc::NLMSG_ERROR | libc::NLMSG_DONE => {
+                        return result.and(msg.error().unwrap());
+                    },
+                    _ => {
+                        if result.is_ok() {
+                            result = f(&msg);
+                        }
+                    },
+                }
+            }
+        }
+    }
+
+    /// Sends `msg` and waits for the kernel to acknowledge it, ignoring any
+    /// other replies.
+    pub fn ack(&mut self, msg: NlMsgBuilder) -> Result<()> {
+        self.request(msg, |_| Ok(()))
+    }
+}
+
+impl AsRawFd for NetlinkSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl Drop for NetlinkSocket {
+    fn drop(&mut self) {
+        let _ = unistd::close(self.fd);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_and_parse_nested() {
+        let mut msg = NlMsgBuilder::new(0x10, NlmFlags::NLM_F_REQUEST);
+        msg.push_header(&[1u8, 2, 3])
+           .attr_u32(1, 42)
+           .begin_nested(2)
+               .attr_str(3, "veth0")
+               .attr_u8(4, 7)
+           .end_nested()
+           .attr_u16(5, 0xbeef);
+        let bytes = msg.finish(9);
+        assert_eq!(bytes.len() % NLMSG_ALIGNTO, 0);
+
+        let msgs: Vec<_> = NlMsgIter::new(&bytes).collect();
+        assert_eq!(msgs.len(), 1);
+        let msg = msgs[0];
+        assert_eq!(msg.ty(), 0x10);
+        assert_eq!(msg.seq(), 9);
+        assert_eq!(msg.flags(), NlmFlags::NLM_F_REQUEST);
+        assert_eq!(msg.header::<[u8; 3]>(), Some([1, 2, 3]));
+
+        let attrs: Vec<_> = msg.attrs::<[u8; 3]>().collect();
+        assert_eq!(attrs.len(), 3);
+        assert_eq!(attrs[0].as_u32(), Some(42));
+        let nested: Vec<_> = attrs[1].nested().collect();
+        assert_eq!(nested[0].as_str(), Some("veth0"));
+        assert_eq!(nested[1].as_u8(), Some(7));
+        assert_eq!(msg.attrs::<[u8; 3]>().find_attr(5).and_then(|a| a.as_u16()), Some(0xbeef));
+    }
+
+    #[test]
+    fn truncated_messages() {
+        let bytes = NlMsgBuilder::new(0x10, NlmFlags::empty()).finish(1);
+        assert_eq!(NlMsgIter::new(&bytes[..bytes.len() - 1]).count(), 0);
+        assert_eq!(NlAttrIter::new(&[8, 0, 1, 0, 0]).count(), 0);
+    }
+}
@@ -1,9 +1,13 @@
 use super::{GetSockOpt, SetSockOpt};
-use Result;
+use {Error, Result};
 use errno::Errno;
 use sys::time::TimeVal;
+#[cfg(target_os = "linux")]
+use unistd::Gid;
 use libc::{self, c_int, uint8_t, c_void, socklen_t};
-use std::mem;
+use std::{cmp, mem};
+use std::ffi::{OsStr, OsString};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
 use std::os::unix::io::RawFd;
 
 /// Helper for implementing `SetSockOpt` for a given socket option. See
@@ -69,6 +73,9 @@ macro_rules! setsockopt_impl {
 /// * Type of the value that you are going to get.
 /// * Type that implements the `Get` trait for the type from the previous item (`GetBool` for
 ///    `bool`, `GetUsize` for `usize`, etc.).
+///
+/// If `getsockopt` fails with `ERANGE` and the getter can grow its buffer (see `Get::grow`), the
+/// call is retried.
 macro_rules! getsockopt_impl {
     ($name:ident, $level:path, $flag:path, $ty:ty, $getter:ty) => {
         impl GetSockOpt for $name {
@@ -78,12 +85,16 @@ macro_rules! getsockopt_impl {
                 unsafe {
                     let mut getter: $getter = Get::blank();
 
-                    let res = libc::getsockopt(fd, $level, $flag,
-                                               getter.ffi_ptr(),
-                                               getter.ffi_len());
-                    try!(Errno::result(res));
-
-                    Ok(getter.unwrap())
+                    loop {
+                        let res = libc::getsockopt(fd, $level, $flag,
+                                                   getter.ffi_ptr(),
+                                                   getter.ffi_len());
+                        match Errno::result(res) {
+                            Err(Error::Sys(Errno::ERANGE)) if getter.grow() => (),
+                            Err(e) => return Err(e),
+                            Ok(_) => return Ok(getter.unwrap()),
+                        }
+                    }
                 }
             }
         }
@@ -152,6 +163,14 @@ macro_rules! sockopt_impl {
         sockopt_impl!(Both, $name, $level, $flag, usize, GetUsize, SetUsize);
     };
 
+    (GetOnly, $name:ident, $level:path, $flag:path, OsString) => {
+        sockopt_impl!(GetOnly, $name, $level, $flag, OsString, GetOsString);
+    };
+
+    (Both, $name:ident, $level:path, $flag:path, OsString) => {
+        sockopt_impl!(Both, $name, $level, $flag, OsString, GetOsString, SetOsString);
+    };
+
     /*
      * Matchers with generic getter types must be placed at the end, so
      * they'll only match _after_ specialized matchers fail
@@ -227,6 +246,8 @@ sockopt_impl!(GetOnly, SocketError, libc::SOL_SOCKET, libc::SO_ERROR, i32);
 sockopt_impl!(Both, KeepAlive, libc::SOL_SOCKET, libc::SO_KEEPALIVE, bool);
 #[cfg(any(target_os = "android", target_os = "linux"))]
 sockopt_impl!(GetOnly, PeerCredentials, libc::SOL_SOCKET, libc::SO_PEERCRED, super::UnixCredentials);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, PassCred, libc::SOL_SOCKET, libc::SO_PASSCRED, bool);
 #[cfg(any(target_os = "ios",
           target_os = "macos"))]
 sockopt_impl!(Both, TcpKeepAlive, libc::IPPROTO_TCP, libc::TCP_KEEPALIVE, u32);
@@ -247,6 +268,8 @@ sockopt_impl!(GetOnly, AcceptConn, libc::SOL_SOCKET, libc::SO_ACCEPTCONN, bool);
 #[cfg(any(target_os = "android", target_os = "linux"))]
 sockopt_impl!(GetOnly, OriginalDst, libc::SOL_IP, libc::SO_ORIGINAL_DST, libc::sockaddr_in);
 sockopt_impl!(Both, ReceiveTimestamp, libc::SOL_SOCKET, libc::SO_TIMESTAMP, bool);
+#[cfg(target_os = "linux")]
+sockopt_impl!(Both, ReceiveTimestampns, libc::SOL_SOCKET, libc::SO_TIMESTAMPNS, bool);
 #[cfg(any(target_os = "android", target_os = "linux"))]
 sockopt_impl!(Both, IpTransparent, libc::SOL_IP, libc::IP_TRANSPARENT, bool);
 #[cfg(target_os = "openbsd")]
@@ -255,6 +278,65 @@ sockopt_impl!(Both, BindAny, libc::SOL_SOCKET, libc::SO_BINDANY, bool);
 sockopt_impl!(Both, BindAny, libc::IPPROTO_IP, libc::IP_BINDANY, bool);
 #[cfg(target_os = "linux")]
 sockopt_impl!(Both, Mark, libc::SOL_SOCKET, libc::SO_MARK, u32);
+#[cfg(target_os = "linux")]
+sockopt_impl!(GetOnly, TcpInfo, libc::IPPROTO_TCP, libc::TCP_INFO, libc::tcp_info);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, TcpCongestion, libc::IPPROTO_TCP, libc::TCP_CONGESTION, OsString);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, TcpUserTimeout, libc::IPPROTO_TCP, libc::TCP_USER_TIMEOUT, u32);
+#[cfg(any(target_os = "android",
+          target_os = "dragonfly",
+          target_os = "freebsd",
+          target_os = "linux",
+          target_os = "nacl"))]
+sockopt_impl!(Both, TcpKeepCount, libc::IPPROTO_TCP, libc::TCP_KEEPCNT, u32);
+#[cfg(any(target_os = "android",
+          target_os = "dragonfly",
+          target_os = "freebsd",
+          target_os = "linux",
+          target_os = "nacl"))]
+sockopt_impl!(Both, TcpKeepInterval, libc::IPPROTO_TCP, libc::TCP_KEEPINTVL, u32);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, TcpFastOpen, libc::IPPROTO_TCP, libc::TCP_FASTOPEN, u32);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, TcpMaxSeg, libc::IPPROTO_TCP, libc::TCP_MAXSEG, u32);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, Ipv6V6Only, libc::IPPROTO_IPV6, libc::IPV6_V6ONLY, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, Ipv6RecvPktInfo, libc::IPPROTO_IPV6, libc::IPV6_RECVPKTINFO, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, IpPktInfo, libc::IPPROTO_IP, libc::IP_PKTINFO, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, IpTos, libc::IPPROTO_IP, libc::IP_TOS, u32);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, IpTtl, libc::IPPROTO_IP, libc::IP_TTL, u32);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, IpFreebind, libc::IPPROTO_IP, libc::IP_FREEBIND, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, BindToDevice, libc::SOL_SOCKET, libc::SO_BINDTODEVICE, OsString);
+#[cfg(target_os = "linux")]
+sockopt_impl!(GetOnly, PeerSec, libc::SOL_SOCKET, libc::SO_PEERSEC, OsString);
+#[cfg(target_os = "linux")]
+sockopt_impl!(GetOnly, PeerGroups, libc::SOL_SOCKET, libc::SO_PEERGROUPS, Vec<Gid>, GetGroups);
+// The caller owns the returned file descriptor.
+#[cfg(target_os = "linux")]
+sockopt_impl!(GetOnly, PeerPidfd, libc::SOL_SOCKET, libc::SO_PEERPIDFD, RawFd);
+// Both BPF options fail with `EINVAL` for programs longer than `BPF_MAXINSNS` (4096)
+// instructions.
+#[cfg(target_os = "linux")]
+sockopt_impl!(SetOnly, ReusePortCBPF, libc::SOL_SOCKET, libc::SO_ATTACH_REUSEPORT_CBPF, Vec<libc::sock_filter>, SetBpfProgram);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(SetOnly, AttachFilter, libc::SOL_SOCKET, libc::SO_ATTACH_FILTER, Vec<libc::sock_filter>, SetBpfProgram);
+// The value holds the fanout group id in its lower 16 bits and the `PACKET_FANOUT_*` mode and
+// flags in its upper 16 bits.
+#[cfg(target_os = "linux")]
+sockopt_impl!(Both, PacketFanout, libc::SOL_PACKET, libc::PACKET_FANOUT, u32);
+// The address family of the socket, such as `AddressFamily::Inet as i32`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(GetOnly, Domain, libc::SOL_SOCKET, libc::SO_DOMAIN, i32);
+// The protocol of the socket, such as `libc::IPPROTO_TCP`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(GetOnly, Protocol, libc::SOL_SOCKET, libc::SO_PROTOCOL, i32);
 
 /*
  *
@@ -274,6 +356,11 @@ unsafe trait Get<T> {
     fn ffi_len(&mut self) -> *mut socklen_t;
     /// Returns the stored value.
     unsafe fn unwrap(self) -> T;
+    /// Makes room for the length the kernel asked for after `getsockopt` failed with `ERANGE`.
+    /// Returns whether the call should be retried.
+    fn grow(&mut self) -> bool {
+        false
+    }
 }
 
 /// Helper trait that describes what is expected from a `SetSockOpt` setter.
@@ -476,6 +563,135 @@ unsafe impl<'a> Set<'a, usize> for SetUsize {
     }
 }
 
+/// Getter for a string, such as the name of a network interface. Trailing NUL bytes are removed.
+struct GetOsString {
+    len: socklen_t,
+    val: Vec<u8>,
+}
+
+unsafe impl Get<OsString> for GetOsString {
+    unsafe fn blank() -> Self {
+        GetOsString {
+            len: 64,
+            val: vec![0; 64],
+        }
+    }
+
+    fn ffi_ptr(&mut self) -> *mut c_void {
+        self.val.as_mut_ptr() as *mut c_void
+    }
+
+    fn ffi_len(&mut self) -> *mut socklen_t {
+        &mut self.len
+    }
+
+    unsafe fn unwrap(mut self) -> OsString {
+        self.val.truncate(self.len as usize);
+        let len = self.val.iter().position(|&b| b == 0).unwrap_or(self.val.len());
+        self.val.truncate(len);
+        OsString::from_vec(self.val)
+    }
+
+    fn grow(&mut self) -> bool {
+        let len = cmp::max(self.len as usize, self.val.len() * 2);
+        self.val.resize(len, 0);
+        self.len = len as socklen_t;
+        true
+    }
+}
+
+/// Setter for a string.
+struct SetOsString<'a> {
+    val: &'a OsStr,
+}
+
+unsafe impl<'a> Set<'a, OsString> for SetOsString<'a> {
+    fn new(val: &'a OsString) -> SetOsString<'a> {
+        SetOsString { val: val.as_os_str() }
+    }
+
+    fn ffi_ptr(&self) -> *const c_void {
+        self.val.as_bytes().as_ptr() as *const c_void
+    }
+
+    fn ffi_len(&self) -> socklen_t {
+        self.val.len() as socklen_t
+    }
+}
+
+/// Getter for the supplementary groups of a peer, whose number is only known to the kernel.
+#[cfg(target_os = "linux")]
+struct GetGroups {
+    len: socklen_t,
+    val: Vec<libc::gid_t>,
+}
+
+#[cfg(target_os = "linux")]
+unsafe impl Get<Vec<Gid>> for GetGroups {
+    unsafe fn blank() -> Self {
+        GetGroups {
+            len: (16 * mem::size_of::<libc::gid_t>()) as socklen_t,
+            val: vec![0; 16],
+        }
+    }
+
+    fn ffi_ptr(&mut self) -> *mut c_void {
+        self.val.as_mut_ptr() as *mut c_void
+    }
+
+    fn ffi_len(&mut self) -> *mut socklen_t {
+        &mut self.len
+    }
+
+    unsafe fn unwrap(mut self) -> Vec<Gid> {
+        self.val.truncate(self.len as usize / mem::size_of::<libc::gid_t>());
+        self.val.into_iter().map(Gid::from_raw).collect()
+    }
+
+    fn grow(&mut self) -> bool {
+        let len = cmp::max(self.len as usize / mem::size_of::<libc::gid_t>(), self.val.len() * 2);
+        self.val.resize(len, 0);
+        self.len = (len * mem::size_of::<libc::gid_t>()) as socklen_t;
+        true
+    }
+}
+
+/// Setter for a classic BPF program, passed to the kernel as a `sock_fprog`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+struct SetBpfProgram<'a> {
+    prog: libc::sock_fprog,
+    _filter: ::std::marker::PhantomData<&'a [libc::sock_filter]>,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+unsafe impl<'a> Set<'a, Vec<libc::sock_filter>> for SetBpfProgram<'a> {
+    fn new(val: &'a Vec<libc::sock_filter>) -> SetBpfProgram<'a> {
+        // A program too long for `sock_fprog` is passed as an empty one, which the kernel
+        // rejects with EINVAL like any other program above `BPF_MAXINSNS`, instead of being
+        // truncated.
+        let len = if val.len() > libc::c_ushort::max_value() as usize {
+            0
+        } else {
+            val.len() as libc::c_ushort
+        };
+        SetBpfProgram {
+            prog: libc::sock_fprog {
+                len,
+                filter: val.as_ptr() as *mut libc::sock_filter,
+            },
+            _filter: ::std::marker::PhantomData,
+        }
+    }
+
+    fn ffi_ptr(&self) -> *const c_void {
+        &self.prog as *const libc::sock_fprog as *const c_void
+    }
+
+    fn ffi_len(&self) -> socklen_t {
+        mem::size_of::<libc::sock_fprog>() as socklen_t
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[cfg(any(target_os = "android", target_os = "linux"))]
@@ -1,5 +1,17 @@
 use std::{cmp, fmt, ops};
 use libc::{c_long, time_t, suseconds_t, timespec, timeval};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use libc::{self, clockid_t, itimerspec};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::mem;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::os::unix::io::RawFd;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use errno::Errno;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use unistd::Pid;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use {Error, Result};
 
 pub trait TimeValLike: Sized {
     #[inline]
@@ -65,6 +77,12 @@ impl AsRef<timespec> for TimeSpec {
     }
 }
 
+impl From<timespec> for TimeSpec {
+    fn from(ts: timespec) -> TimeSpec {
+        TimeSpec(ts)
+    }
+}
+
 impl fmt::Debug for TimeSpec {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.debug_struct("TimeSpec")
@@ -255,6 +273,212 @@ impl fmt::Display for TimeSpec {
 }
 
 
+/// When and how often a timer expires, such as a `TimerFd`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Expiration {
+    /// Expire once, at the given time.
+    OneShot(TimeSpec),
+    /// Expire first at the first given time and then repeatedly with the second as interval.
+    IntervalDelayed(TimeSpec, TimeSpec),
+    /// Expire repeatedly with the given interval, starting one interval from now.
+    Interval(TimeSpec),
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl Expiration {
+    /// Convert the setting of a timer as returned by the kernel.
+    ///
+    /// Returns `None` if the timer is disarmed.
+    pub fn from_itimerspec(spec: &itimerspec) -> Option<Expiration> {
+        let value = TimeSpec(spec.it_value);
+        let interval = TimeSpec(spec.it_interval);
+        let zero = TimeSpec::zero();
+        if value == zero {
+            None
+        } else if interval == zero {
+            Some(Expiration::OneShot(value))
+        } else if value == interval {
+            Some(Expiration::Interval(interval))
+        } else {
+            Some(Expiration::IntervalDelayed(value, interval))
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl From<Expiration> for itimerspec {
+    fn from(expiration: Expiration) -> itimerspec {
+        let (value, interval) = match expiration {
+            Expiration::OneShot(value) => (value, TimeSpec::zero()),
+            Expiration::IntervalDelayed(value, interval) => (value, interval),
+            Expiration::Interval(interval) => (interval, interval),
+        };
+        itimerspec { it_interval: interval.0, it_value: value.0 }
+    }
+}
+
+/// The identifier of a system clock, for use with `clock_gettime` and friends.
+///
+/// Besides the static clocks, which are available as associated constants, there are dynamic
+/// clocks that measure the CPU time of a process (`ClockId::pid_cpu_clock_id`) or that belong to
+/// a device such as a PTP hardware clock (`ClockId::from_fd`).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ClockId(clockid_t);
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl ClockId {
+    /// A settable system-wide clock that measures real (wall-clock) time.
+    pub const CLOCK_REALTIME: ClockId = ClockId(libc::CLOCK_REALTIME);
+    /// Like `CLOCK_REALTIME`, but faster and less precise.
+    pub const CLOCK_REALTIME_COARSE: ClockId = ClockId(libc::CLOCK_REALTIME_COARSE);
+    /// A clock that can't be set and that doesn't count the time the system was suspended.
+    pub const CLOCK_MONOTONIC: ClockId = ClockId(libc::CLOCK_MONOTONIC);
+    /// Like `CLOCK_MONOTONIC`, but faster and less precise.
+    pub const CLOCK_MONOTONIC_COARSE: ClockId = ClockId(libc::CLOCK_MONOTONIC_COARSE);
+    /// Like `CLOCK_MONOTONIC`, but not subject to NTP frequency adjustments.
+    pub const CLOCK_MONOTONIC_RAW: ClockId = ClockId(libc::CLOCK_MONOTONIC_RAW);
+    /// Like `CLOCK_MONOTONIC`, but including the time the system was suspended.
+    pub const CLOCK_BOOTTIME: ClockId = ClockId(libc::CLOCK_BOOTTIME);
+    /// Like `CLOCK_REALTIME`, but timers on it wake the system up if it is suspended.
+    ///
+    /// Arming such a timer requires `CAP_WAKE_ALARM`.
+    pub const CLOCK_REALTIME_ALARM: ClockId = ClockId(libc::CLOCK_REALTIME_ALARM);
+    /// Like `CLOCK_BOOTTIME`, but timers on it wake the system up if it is suspended.
+    ///
+    /// Arming such a timer requires `CAP_WAKE_ALARM`.
+    pub const CLOCK_BOOTTIME_ALARM: ClockId = ClockId(libc::CLOCK_BOOTTIME_ALARM);
+    /// A system-wide clock derived from wall-clock time but ignoring leap seconds.
+    pub const CLOCK_TAI: ClockId = ClockId(libc::CLOCK_TAI);
+    /// The CPU time consumed by all threads of the calling process.
+    pub const CLOCK_PROCESS_CPUTIME_ID: ClockId = ClockId(libc::CLOCK_PROCESS_CPUTIME_ID);
+    /// The CPU time consumed by the calling thread.
+    pub const CLOCK_THREAD_CPUTIME_ID: ClockId = ClockId(libc::CLOCK_THREAD_CPUTIME_ID);
+
+    /// Creates a `ClockId` from a raw `clockid_t`.
+    pub fn from_raw(clk_id: clockid_t) -> ClockId {
+        ClockId(clk_id)
+    }
+
+    /// Returns the raw `clockid_t`.
+    pub fn as_raw(self) -> clockid_t {
+        self.0
+    }
+
+    /// Returns the clock measuring the CPU time consumed by the process `pid`.
+    ///
+    /// For more documentation, please read [clock_getcpuclockid(3)](http://man7.org/linux/man-pages/man3/clock_getcpuclockid.3.html).
+    pub fn pid_cpu_clock_id(pid: Pid) -> Result<ClockId> {
+        let mut clk_id: clockid_t = 0;
+        // Returns the error number instead of setting `errno`.
+        let res = unsafe { libc::clock_getcpuclockid(pid.into(), &mut clk_id) };
+        if res == 0 {
+            Ok(ClockId(clk_id))
+        } else {
+            Err(Error::Sys(Errno::from_i32(res)))
+        }
+    }
+
+    /// Returns the dynamic clock of an open clock device, such as `/dev/ptp0`.
+    ///
+    /// The clock is only valid for as long as `fd` remains open.
+    pub fn from_fd(fd: RawFd) -> ClockId {
+        // FD_TO_CLOCKID from the kernel's posix-timers.h.
+        ClockId(((!fd) << 3) | 3)
+    }
+
+    /// Returns the current time of this clock.
+    pub fn now(self) -> Result<TimeSpec> {
+        clock_gettime(self)
+    }
+
+    /// Returns the resolution of this clock.
+    pub fn res(self) -> Result<TimeSpec> {
+        clock_getres(self)
+    }
+
+    /// Sets the time of this clock.
+    pub fn set_time(self, timespec: TimeSpec) -> Result<()> {
+        clock_settime(self, timespec)
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl From<ClockId> for clockid_t {
+    fn from(clock_id: ClockId) -> clockid_t {
+        clock_id.0
+    }
+}
+
+/// Returns the current time of the clock `clock_id`.
+///
+/// For more documentation, please read [clock_gettime(2)](http://man7.org/linux/man-pages/man2/clock_gettime.2.html).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn clock_gettime(clock_id: ClockId) -> Result<TimeSpec> {
+    let mut tp: timespec = unsafe { mem::zeroed() };
+    let res = unsafe { libc::clock_gettime(clock_id.0, &mut tp) };
+
+    Errno::result(res).map(|_| TimeSpec(tp))
+}
+
+/// Returns the resolution of the clock `clock_id`.
+///
+/// For more documentation, please read [clock_getres(2)](http://man7.org/linux/man-pages/man2/clock_getres.2.html).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn clock_getres(clock_id: ClockId) -> Result<TimeSpec> {
+    let mut res: timespec = unsafe { mem::zeroed() };
+    let ret = unsafe { libc::clock_getres(clock_id.0, &mut res) };
+
+    Errno::result(ret).map(|_| TimeSpec(res))
+}
+
+/// Sets the time of the clock `clock_id`.
+///
+/// For more documentation, please read [clock_settime(2)](http://man7.org/linux/man-pages/man2/clock_settime.2.html).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn clock_settime(clock_id: ClockId, timespec: TimeSpec) -> Result<()> {
+    let res = unsafe { libc::clock_settime(clock_id.0, timespec.as_ref()) };
+
+    Errno::result(res).map(drop)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+libc_bitflags! {
+    /// Flags for `clock_nanosleep` and `sys::timer::Timer::set`.
+    pub struct ClockNanosleepFlags: libc::c_int {
+        /// The requested time is an absolute time of the clock instead of being relative to now.
+        TIMER_ABSTIME;
+    }
+}
+
+/// Suspends the calling thread until `request` has elapsed on the clock `clock_id`, or until
+/// the clock reaches `request` if `TIMER_ABSTIME` is given.
+///
+/// Returns `None` if the sleep completed. If it was interrupted by a signal handler, returns
+/// the request to resume sleeping with: the remaining time for a relative sleep, or `request`
+/// itself for an absolute one.
+///
+/// For more documentation, please read [clock_nanosleep(2)](http://man7.org/linux/man-pages/man2/clock_nanosleep.2.html).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn clock_nanosleep(clock_id: ClockId, flags: ClockNanosleepFlags, request: &TimeSpec)
+    -> Result<Option<TimeSpec>>
+{
+    let mut remain: timespec = unsafe { mem::zeroed() };
+    // Returns the error number instead of setting `errno`.
+    let res = unsafe {
+        libc::clock_nanosleep(clock_id.0, flags.bits(), request.as_ref(), &mut remain)
+    };
+    if res == 0 {
+        return Ok(None);
+    }
+    match Errno::from_i32(res) {
+        Errno::EINTR if flags.contains(ClockNanosleepFlags::TIMER_ABSTIME) => Ok(Some(*request)),
+        Errno::EINTR => Ok(Some(TimeSpec(remain))),
+        errno => Err(Error::Sys(errno)),
+    }
+}
+
 
 #[repr(C)]
 #[derive(Clone, Copy)]
@@ -569,4 +793,23 @@ mod test {
         assert_eq!(TimeVal::nanoseconds(1402).to_string(), "0.000001 seconds");
         assert_eq!(TimeVal::seconds(-86401).to_string(), "-86401 seconds");
     }
+
+    #[test]
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn test_expiration_itimerspec() {
+        use libc::itimerspec;
+        use super::Expiration;
+
+        let one = TimeSpec::seconds(1);
+        let two = TimeSpec::seconds(2);
+        for &expiration in &[Expiration::OneShot(one),
+                             Expiration::IntervalDelayed(one, two),
+                             Expiration::Interval(two)] {
+            let spec: itimerspec = expiration.into();
+            assert_eq!(Expiration::from_itimerspec(&spec), Some(expiration));
+        }
+
+        let spec: itimerspec = Expiration::OneShot(TimeSpec::zero()).into();
+        assert_eq!(Expiration::from_itimerspec(&spec), None);
+    }
 }
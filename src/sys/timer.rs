@@ -0,0 +1,99 @@
+//! POSIX per-process timers.
+//!
+//! A `Timer` notifies the process or a specific thread when it expires, usually by sending a
+//! signal. Unlike a `TimerFd` it doesn't need a file descriptor, which makes it suitable for
+//! watchdogs that have to interrupt a blocked thread.
+//!
+//! For more documentation, please read [timer_create(2)](http://man7.org/linux/man-pages/man2/timer_create.2.html).
+//!
+//! # Examples
+//!
+//! Create a timer that sends `SIGALRM` once after 100 milliseconds:
+//! ```no_run
+//! # use nix::sys::signal::{SigEvent, SigevNotify, Signal};
+//! # use nix::sys::time::{ClockId, ClockNanosleepFlags, Expiration, TimeSpec, TimeValLike};
+//! # use nix::sys::timer::Timer;
+//! let sigevent = SigEvent::new(SigevNotify::SigevSignal {
+//!     signal: Signal::SIGALRM,
+//!     si_value: 0,
+//! });
+//! let timer = Timer::new(ClockId::CLOCK_MONOTONIC, sigevent).unwrap();
+//! timer.set(Expiration::OneShot(TimeSpec::milliseconds(100)),
+//!           ClockNanosleepFlags::empty()).unwrap();
+//! ```
+
+use libc;
+use std::mem;
+use std::ptr;
+use sys::signal::SigEvent;
+use sys::time::{ClockId, ClockNanosleepFlags, Expiration, TimeSpec, TimeValLike};
+use Result;
+use errno::Errno;
+
+/// A POSIX per-process timer. It is deleted when dropped.
+#[derive(Debug)]
+pub struct Timer(libc::timer_t);
+
+// The timer ID is a process-wide handle that may be used from any thread.
+unsafe impl Send for Timer {}
+
+impl Timer {
+    /// Creates a new timer based on the clock `clock_id`, which notifies as described by
+    /// `sigevent` when it expires.
+    ///
+    /// Use `SigevNotify::SigevThreadId` to direct the signal at a specific thread. The timer is
+    /// initially disarmed.
+    pub fn new(clock_id: ClockId, sigevent: SigEvent) -> Result<Timer> {
+        let mut sigevent = sigevent.sigevent();
+        let mut timer_id: libc::timer_t = ptr::null_mut();
+        let res = unsafe { libc::timer_create(clock_id.as_raw(), &mut sigevent, &mut timer_id) };
+
+        Errno::result(res).map(|_| Timer(timer_id))
+    }
+
+    /// Arms the timer.
+    ///
+    /// Any previous setting of the timer is replaced. With `TIMER_ABSTIME` the first expiration
+    /// is an absolute time of the timer's clock, otherwise it is relative to now.
+    ///
+    /// For more documentation, please read [timer_settime(2)](http://man7.org/linux/man-pages/man2/timer_settime.2.html).
+    pub fn set(&self, expiration: Expiration, flags: ClockNanosleepFlags) -> Result<()> {
+        let new_value: libc::itimerspec = expiration.into();
+        let res = unsafe {
+            libc::timer_settime(self.0, flags.bits(), &new_value, ptr::null_mut())
+        };
+
+        Errno::result(res).map(drop)
+    }
+
+    /// Returns the current setting of the timer, or `None` if it is disarmed.
+    ///
+    /// The first expiration is always reported relative to now.
+    pub fn get(&self) -> Result<Option<Expiration>> {
+        let mut curr_value: libc::itimerspec = unsafe { mem::zeroed() };
+        let res = unsafe { libc::timer_gettime(self.0, &mut curr_value) };
+
+        Errno::result(res).map(|_| Expiration::from_itimerspec(&curr_value))
+    }
+
+    /// Disarms the timer.
+    pub fn unset(&self) -> Result<()> {
+        self.set(Expiration::OneShot(TimeSpec::zero()), ClockNanosleepFlags::empty())
+    }
+
+    /// Returns the number of expirations that were not delivered because the signal of an
+    /// earlier expiration was still pending.
+    ///
+    /// For more documentation, please read [timer_getoverrun(2)](http://man7.org/linux/man-pages/man2/timer_getoverrun.2.html).
+    pub fn overruns(&self) -> Result<i32> {
+        let res = unsafe { libc::timer_getoverrun(self.0) };
+
+        Errno::result(res)
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        unsafe { libc::timer_delete(self.0) };
+    }
+}
@@ -0,0 +1,130 @@
+//! Timers that notify via file descriptors.
+//!
+//! A `TimerFd` becomes readable each time the timer expires. Reading it returns the number of
+//! expirations since the last read, which makes it easy to drive timers from `poll`, `select`
+//! or `epoll` based event loops.
+//!
+//! For more documentation, please read [timerfd_create(2)](http://man7.org/linux/man-pages/man2/timerfd_create.2.html).
+//!
+//! # Examples
+//!
+//! Create a timer that expires every 100 milliseconds and wait for it five times:
+//! ```
+//! # use nix::sys::time::{ClockId, Expiration, TimeSpec, TimeValLike};
+//! # use nix::sys::timerfd::{TimerFd, TimerFlags, TimerSetTimeFlags};
+//! let timer = TimerFd::new(ClockId::CLOCK_MONOTONIC, TimerFlags::empty()).unwrap();
+//! timer.set(Expiration::Interval(TimeSpec::milliseconds(100)),
+//!           TimerSetTimeFlags::empty()).unwrap();
+//!
+//! let mut expirations = 0;
+//! while expirations < 5 {
+//!     expirations += timer.wait().unwrap();
+//! }
+//! ```
+
+use libc::{self, c_int};
+use std::mem;
+use std::os::unix::io::{AsRawFd, RawFd};
+use sys::time::{ClockId, Expiration, TimeSpec, TimeValLike};
+use unistd::{close, read};
+use {Error, Result};
+use errno::Errno;
+
+libc_bitflags! {
+    /// Additional flags to change the behaviour of the file descriptor at the time of creation.
+    pub struct TimerFlags: c_int {
+        /// Set the `O_NONBLOCK` flag on the open file description.
+        TFD_NONBLOCK;
+        /// Set the `FD_CLOEXEC` flag on the file descriptor.
+        TFD_CLOEXEC;
+    }
+}
+
+libc_bitflags! {
+    /// Flags that are used for arming the timer.
+    pub struct TimerSetTimeFlags: c_int {
+        /// The expiration time is an absolute time of the timer's clock instead of being
+        /// relative to the current time.
+        TFD_TIMER_ABSTIME;
+        /// Together with `TFD_TIMER_ABSTIME` on a `CLOCK_REALTIME` timer, cancel the timer when
+        /// the clock is set. Waiting on a cancelled timer fails with `ECANCELED`.
+        TFD_TIMER_CANCEL_ON_SET;
+    }
+}
+
+/// A timer that notifies via a file descriptor. It is closed when dropped.
+#[derive(Debug, Eq, Hash, PartialEq)]
+pub struct TimerFd(RawFd);
+
+impl TimerFd {
+    /// Creates a new timer based on the given clock.
+    ///
+    /// Only `CLOCK_REALTIME`, `CLOCK_MONOTONIC`, `CLOCK_BOOTTIME` and their `_ALARM` variants
+    /// are supported. The timer is initially disarmed.
+    pub fn new(clockid: ClockId, flags: TimerFlags) -> Result<TimerFd> {
+        let res = unsafe { libc::timerfd_create(clockid.as_raw(), flags.bits()) };
+
+        Errno::result(res).map(TimerFd)
+    }
+
+    /// Arms the timer.
+    ///
+    /// Any previous setting of the timer is replaced. With `TFD_TIMER_ABSTIME` the first
+    /// expiration is an absolute time of the timer's clock, otherwise it is relative to now.
+    ///
+    /// Note that a `TimeSpec` of zero as first expiration disarms the timer.
+    pub fn set(&self, expiration: Expiration, flags: TimerSetTimeFlags) -> Result<()> {
+        let new_value: libc::itimerspec = expiration.into();
+        let res = unsafe {
+            libc::timerfd_settime(self.0, flags.bits(), &new_value, ::std::ptr::null_mut())
+        };
+
+        Errno::result(res).map(drop)
+    }
+
+    /// Returns the current setting of the timer, or `None` if it is disarmed.
+    ///
+    /// The first expiration is always reported relative to now.
+    pub fn get(&self) -> Result<Option<Expiration>> {
+        let mut curr_value: libc::itimerspec = unsafe { mem::zeroed() };
+        let res = unsafe { libc::timerfd_gettime(self.0, &mut curr_value) };
+
+        Errno::result(res).map(|_| Expiration::from_itimerspec(&curr_value))
+    }
+
+    /// Disarms the timer.
+    pub fn unset(&self) -> Result<()> {
+        self.set(Expiration::OneShot(TimeSpec::zero()), TimerSetTimeFlags::empty())
+    }
+
+    /// Waits for the timer to expire.
+    ///
+    /// Returns the number of expirations since the timer was set or last waited on, which is
+    /// at least 1. If the timer was created with `TFD_NONBLOCK` and has not expired, this fails
+    /// with `EAGAIN` instead.
+    pub fn wait(&self) -> Result<u64> {
+        let mut buf = [0u8; 8];
+        loop {
+            match read(self.0, &mut buf) {
+                Ok(8) => {
+                    return Ok(unsafe { ::std::ptr::read_unaligned(buf.as_ptr() as *const u64) });
+                }
+                Ok(_) => return Err(Error::Sys(Errno::EIO)),
+                Err(Error::Sys(Errno::EINTR)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for TimerFd {
+    fn drop(&mut self) {
+        let _ = close(self.0);
+    }
+}
+
+impl AsRawFd for TimerFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
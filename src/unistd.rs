@@ -1055,6 +1055,17 @@ pub fn pipe() -> Result<(RawFd, RawFd)> {
 pub fn pipe2(flags: OFlag) -> Result<(RawFd, RawFd)> {
     let mut fds: [c_int; 2] = unsafe { mem::uninitialized() };
 
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        // pipe2 was added in Linux 2.6.27
+        if !::features::pipe_atomic_cloexec() {
+            let res = unsafe { libc::pipe(fds.as_mut_ptr()) };
+            try!(Errno::result(res));
+            try!(pipe2_setflags(fds[0], fds[1], flags));
+            return Ok((fds[0], fds[1]));
+        }
+    }
+
     let res = unsafe { libc::pipe2(fds.as_mut_ptr(), flags.bits()) };
 
     try!(Errno::result(res));
@@ -1086,7 +1097,10 @@ pub fn pipe2(flags: OFlag) -> Result<(RawFd, RawFd)> {
     Ok((fds[0], fds[1]))
 }
 
-#[cfg(any(target_os = "ios", target_os = "macos"))]
+#[cfg(any(target_os = "android",
+          target_os = "ios",
+          target_os = "linux",
+          target_os = "macos"))]
 fn pipe2_setflags(fd1: RawFd, fd2: RawFd, flags: OFlag) -> Result<()> {
     use fcntl::FdFlag;
     use fcntl::FcntlArg::F_SETFL;
@@ -19,12 +19,22 @@ mod test_select;
 #[cfg(any(target_os = "android", target_os = "linux"))]
 mod test_sysinfo;
 mod test_termios;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod test_timerfd;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod test_time;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod test_timer;
 mod test_ioctl;
 mod test_wait;
 mod test_uio;
 
 #[cfg(target_os = "linux")]
 mod test_epoll;
+#[cfg(target_os = "linux")]
+mod test_fanotify;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod test_inotify;
 mod test_pthread;
 #[cfg(any(target_os = "android",
           target_os = "linux"))]
@@ -1,7 +1,10 @@
 use nix::sys::epoll::{EpollCreateFlags, EpollFlags, EpollOp, EpollEvent};
-use nix::sys::epoll::{epoll_create1, epoll_ctl};
+use nix::sys::epoll::{epoll_create1, epoll_ctl, Epoll};
+use nix::sys::eventfd::{EfdFlags, EventFd};
+use nix::sys::time::{TimeSpec, TimeValLike};
 use nix::Error;
 use nix::errno::Errno;
+use std::time::Duration;
 
 #[test]
 pub fn test_epoll_errno() {
@@ -22,3 +25,65 @@ pub fn test_epoll_ctl() {
     epoll_ctl(efd, EpollOp::EpollCtlAdd, 1, &mut event).unwrap();
     epoll_ctl(efd, EpollOp::EpollCtlDel, 1, None).unwrap();
 }
+
+#[test]
+pub fn test_epoll_eventfd() {
+    let epoll = Epoll::new(EpollCreateFlags::EPOLL_CLOEXEC).unwrap();
+    let efd = EventFd::new().unwrap();
+    epoll.add(&efd, EpollEvent::new(EpollFlags::EPOLLIN, 42)).unwrap();
+
+    let mut events = [EpollEvent::empty(); 2];
+    assert_eq!(epoll.wait(&mut events, Some(Duration::from_millis(10))).unwrap(), 0);
+
+    efd.write(3).unwrap();
+    assert_eq!(epoll.wait(&mut events, None).unwrap(), 1);
+    assert_eq!(events[0].data(), 42);
+    assert_eq!(events[0].events(), EpollFlags::EPOLLIN);
+    assert_eq!(efd.read().unwrap(), 3);
+
+    epoll.modify(&efd, EpollEvent::new(EpollFlags::EPOLLOUT, 7)).unwrap();
+    assert_eq!(epoll.wait(&mut events, None).unwrap(), 1);
+    assert_eq!(events[0].data(), 7);
+
+    epoll.delete(&efd).unwrap();
+    assert_eq!(epoll.wait(&mut events, Some(Duration::from_millis(0))).unwrap(), 0);
+}
+
+#[test]
+pub fn test_epoll_exclusive() {
+    let epoll = Epoll::new(EpollCreateFlags::empty()).unwrap();
+    let efd = EventFd::new().unwrap();
+    epoll.add(&efd, EpollEvent::new(EpollFlags::EPOLLIN | EpollFlags::EPOLLEXCLUSIVE, 0))
+        .unwrap();
+
+    // EPOLLEXCLUSIVE can't be modified after registration.
+    assert_eq!(epoll.modify(&efd, EpollEvent::new(EpollFlags::EPOLLIN, 0)),
+               Err(Error::Sys(Errno::EINVAL)));
+}
+
+#[test]
+pub fn test_epoll_pwait2() {
+    let epoll = Epoll::new(EpollCreateFlags::empty()).unwrap();
+    let efd = EventFd::new().unwrap();
+    epoll.add(&efd, EpollEvent::new(EpollFlags::EPOLLIN, 1)).unwrap();
+
+    let mut events = [EpollEvent::empty(); 1];
+    match epoll.pwait2(&mut events, Some(TimeSpec::microseconds(100)), None) {
+        Err(Error::Sys(Errno::ENOSYS)) => return,
+        res => assert_eq!(res.unwrap(), 0),
+    }
+    efd.write(1).unwrap();
+    assert_eq!(epoll.pwait2(&mut events, None, None).unwrap(), 1);
+    assert_eq!(events[0].data(), 1);
+}
+
+#[test]
+pub fn test_eventfd_semaphore() {
+    let efd = EventFd::from_value_and_flags(2, EfdFlags::EFD_SEMAPHORE | EfdFlags::EFD_NONBLOCK)
+        .unwrap();
+    assert_eq!(efd.read().unwrap(), 1);
+    assert_eq!(efd.read().unwrap(), 1);
+    assert_eq!(efd.read(), Err(Error::Sys(Errno::EAGAIN)));
+    efd.write(1).unwrap();
+    assert_eq!(efd.read().unwrap(), 1);
+}
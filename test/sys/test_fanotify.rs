@@ -0,0 +1,68 @@
+use nix::Error;
+use nix::errno::Errno;
+use nix::fcntl::OFlag;
+use nix::sys::fanotify::{Fanotify, FidInfoType, InitFlags, MarkFlags, MaskFlags};
+use nix::unistd::getpid;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::Write;
+use tempdir::TempDir;
+
+// fanotify requires CAP_SYS_ADMIN, so these tests do nothing when run without it.
+fn init(flags: InitFlags) -> Option<Fanotify> {
+    match Fanotify::init(flags | InitFlags::FAN_NONBLOCK, OFlag::O_RDONLY) {
+        Ok(fan) => Some(fan),
+        Err(Error::Sys(Errno::EPERM)) => None,
+        Err(e) => panic!("fanotify_init failed: {}", e),
+    }
+}
+
+#[test]
+fn test_fanotify_fd() {
+    let fan = match init(InitFlags::FAN_CLASS_NOTIF) {
+        Some(fan) => fan,
+        None => return,
+    };
+    let tempdir = TempDir::new("test_fanotify").unwrap();
+    let path = tempdir.path().join("file");
+    File::create(&path).unwrap();
+
+    fan.mark(MarkFlags::FAN_MARK_ADD, MaskFlags::FAN_CLOSE_WRITE, None, Some(&path)).unwrap();
+    assert_eq!(fan.read_events().unwrap_err(), Error::Sys(Errno::EAGAIN));
+
+    File::create(&path).unwrap().write_all(b"foo").unwrap();
+
+    let events = fan.read_events().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].mask(), MaskFlags::FAN_CLOSE_WRITE);
+    assert_eq!(events[0].pid(), getpid());
+    assert!(events[0].fd().is_some());
+    assert!(events[0].fids().is_empty());
+}
+
+#[test]
+fn test_fanotify_fid() {
+    let fan = match init(InitFlags::FAN_CLASS_NOTIF | InitFlags::FAN_REPORT_DFID_NAME) {
+        Some(fan) => fan,
+        None => return,
+    };
+    let tempdir = TempDir::new("test_fanotify").unwrap();
+
+    // Filesystems without file handle support can't be used in FID mode.
+    match fan.mark(MarkFlags::FAN_MARK_ADD, MaskFlags::FAN_CREATE, None, Some(tempdir.path())) {
+        Err(Error::Sys(Errno::EOPNOTSUPP)) | Err(Error::Sys(Errno::ENODEV)) => return,
+        res => res.unwrap(),
+    }
+
+    File::create(tempdir.path().join("file")).unwrap();
+
+    let events = fan.read_events().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].mask(), MaskFlags::FAN_CREATE);
+    assert_eq!(events[0].fd(), None);
+    let fids = events[0].fids();
+    assert_eq!(fids.len(), 1);
+    assert_eq!(fids[0].info_type, FidInfoType::DirFidName);
+    assert!(!fids[0].handle.is_empty());
+    assert_eq!(fids[0].name, Some(OsString::from("file")));
+}
@@ -1,5 +1,6 @@
 use nix::unistd::*;
 use nix::sys::signal::*;
+use libc;
 
 #[test]
 fn test_kill_none() {
@@ -61,3 +62,184 @@ fn test_sigprocmask() {
     sigprocmask(SigmaskHow::SIG_UNBLOCK, Some(&signal_set), None)
         .expect("expect to be able to block signals");
 }
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_sigwaitinfo() {
+    #[allow(unused_variables)]
+    let m = ::SIGNAL_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let mut mask = SigSet::empty();
+    mask.add(SIGUSR1);
+    mask.thread_block().unwrap();
+
+    raise(SIGUSR1).unwrap();
+    match sigwaitinfo(&mask).unwrap() {
+        SigInfo::Kill { signal, pid, uid } | SigInfo::Tkill { signal, pid, uid } => {
+            assert_eq!(signal, SIGUSR1);
+            assert_eq!(pid, getpid());
+            assert_eq!(uid, getuid());
+        },
+        info => panic!("unexpected siginfo {:?}", info),
+    }
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_realtime_signal() {
+    let min = RealtimeSignal::min();
+    assert!(min <= RealtimeSignal::max());
+    assert_eq!(RealtimeSignal::new(0).unwrap(), min);
+    assert_eq!(RealtimeSignal::new(1).unwrap().offset(), 1);
+    assert!(RealtimeSignal::new(-1).is_err());
+    assert!(RealtimeSignal::new(RealtimeSignal::max().offset() + 1).is_err());
+    assert!(RealtimeSignal::from_c_int(SIGUSR1 as libc::c_int).is_err());
+
+    assert_eq!(SigNum::from_c_int(SIGUSR1 as libc::c_int).unwrap(), SIGUSR1);
+    assert_eq!(SigNum::from_c_int(libc::c_int::from(min)).unwrap(), min);
+    assert!(SigNum::from_c_int(0).is_err());
+
+    let mut mask = SigSet::empty();
+    mask.add_realtime(min);
+    assert!(mask.contains_realtime(min));
+    mask.remove_realtime(min);
+    assert!(!mask.contains_realtime(min));
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_rt_tgsigqueueinfo() {
+    use std::time::Duration;
+
+    #[allow(unused_variables)]
+    let m = ::SIGNAL_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let signal = RealtimeSignal::new(1).unwrap();
+    let mut mask = SigSet::empty();
+    mask.add_realtime(signal);
+    mask.thread_block().unwrap();
+
+    assert_eq!(mask.timed_wait(Duration::from_millis(1)).unwrap(), None);
+
+    // Both signals are queued, and delivered in order.
+    rt_tgsigqueueinfo(getpid(), gettid(), signal, SigVal::Ptr(42 as *mut libc::c_void)).unwrap();
+    tgkill(getpid(), gettid(), signal).unwrap();
+    assert_eq!(mask.wait_info().unwrap(), SigInfo::Queue {
+        signal: SigNum::Realtime(signal),
+        pid: getpid(),
+        uid: getuid(),
+        value: 42,
+    });
+    // Depending on the kernel version, `tgkill` reports either `SI_USER` or `SI_TKILL`.
+    match mask.timed_wait(Duration::from_secs(1)).unwrap() {
+        Some(SigInfo::Kill { signal: s, pid, .. }) | Some(SigInfo::Tkill { signal: s, pid, .. }) => {
+            assert_eq!(s, signal);
+            assert_eq!(pid, getpid());
+        },
+        info => panic!("unexpected siginfo {:?}", info),
+    }
+
+    mask.thread_unblock().unwrap();
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_sigqueue() {
+    use libc::_exit;
+    use nix::sys::wait::{waitpid, WaitStatus};
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let signal = RealtimeSignal::max();
+    match fork().unwrap() {
+        ForkResult::Child => {
+            // The child is single-threaded, so a signal sent to the process is received by this
+            // thread.
+            let mut mask = SigSet::empty();
+            mask.add_realtime(signal);
+            let ok = mask.thread_block().is_ok() &&
+                     sigqueue(getpid(), signal, SigVal::Ptr(7 as *mut libc::c_void)).is_ok() &&
+                     match mask.wait_info() {
+                         Ok(SigInfo::Queue { signal: s, value, .. }) => s == signal && value == 7,
+                         _ => false,
+                     };
+            unsafe { _exit(if ok { 0 } else { 1 }) };
+        },
+        ForkResult::Parent { child } => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+    }
+}
+
+#[test]
+fn test_sigaction_guard() {
+    extern fn handler(_: libc::c_int) {}
+
+    #[allow(unused_variables)]
+    let m = ::SIGNAL_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let action = SigAction::new(SigHandler::Handler(handler), SaFlags::empty(), SigSet::empty());
+    let dfl = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+    {
+        let guard = unsafe { SigActionGuard::new(SIGUSR2, &action) }.unwrap();
+        assert_eq!(guard.signal(), SIGUSR2);
+        let current = unsafe { sigaction(SIGUSR2, &action) }.unwrap();
+        assert_eq!(current.handler(), SigHandler::Handler(handler));
+    }
+    let restored = unsafe { sigaction(SIGUSR2, &dfl) }.unwrap();
+    assert_eq!(restored.handler(), SigHandler::SigDfl);
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_sigaltstack() {
+    use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+    use std::thread;
+
+    static HANDLER_SS_FLAGS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    extern fn handler(_: libc::c_int) {
+        let mut current: libc::stack_t = unsafe { ::std::mem::zeroed() };
+        unsafe { libc::sigaltstack(::std::ptr::null(), &mut current) };
+        HANDLER_SS_FLAGS.store(current.ss_flags as usize, Ordering::SeqCst);
+    }
+
+    #[allow(unused_variables)]
+    let m = ::SIGNAL_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let stack = AltStack::new(0).unwrap();
+    assert!(stack.size() >= 8192);
+
+    let action = SigAction::new(SigHandler::Handler(handler), SaFlags::SA_ONSTACK,
+                                SigSet::empty());
+    let _guard = unsafe { SigActionGuard::new(SIGUSR2, &action) }.unwrap();
+
+    // Run on a fresh thread so that the alternate stack of the test thread is left alone.
+    thread::spawn(move || {
+        let _stack_guard = sigaltstack(&stack).unwrap();
+        raise(SIGUSR2).unwrap();
+        let flags = HANDLER_SS_FLAGS.load(Ordering::SeqCst) as libc::c_int;
+        assert!(flags & libc::SS_ONSTACK != 0);
+    }).join().unwrap();
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_signal_pipe() {
+    use nix::Error;
+    use nix::errno::Errno;
+
+    #[allow(unused_variables)]
+    let m = ::SIGNAL_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let pipe = SignalPipe::new(&[SIGUSR1, SIGUSR2]).unwrap();
+    assert_eq!(SignalPipe::new(&[SIGUSR1]).err(), Some(Error::Sys(Errno::EBUSY)));
+    assert_eq!(pipe.read().unwrap(), None);
+
+    raise(SIGUSR2).unwrap();
+    raise(SIGUSR1).unwrap();
+    assert_eq!(pipe.read().unwrap(), Some(SIGUSR2));
+    assert_eq!(pipe.read().unwrap(), Some(SIGUSR1));
+    assert_eq!(pipe.read().unwrap(), None);
+}
@@ -24,3 +24,30 @@ fn test_signalfd() {
     let signo = Signal::from_c_int(res.ssi_signo as i32).unwrap();
     assert_eq!(signo, signal::SIGUSR1);
 }
+
+#[test]
+fn test_signalfd_siginfo() {
+    use nix::sys::signalfd::{SigInfo, SignalFd};
+    use nix::sys::signal::{raise, Signal, SigSet};
+    use nix::unistd::{getpid, getuid};
+
+    #[allow(unused_variables)]
+    let m = ::SIGNAL_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let mut mask = SigSet::empty();
+    mask.add(Signal::SIGUSR2);
+    mask.thread_block().unwrap();
+
+    let mut fd = SignalFd::new(&mask).unwrap();
+
+    // Depending on the libc, `raise` uses either `kill` or `tgkill`.
+    raise(Signal::SIGUSR2).unwrap();
+    match fd.read_siginfo().unwrap().unwrap() {
+        SigInfo::Kill { signal, pid, uid } | SigInfo::Tkill { signal, pid, uid } => {
+            assert_eq!(signal, Signal::SIGUSR2);
+            assert_eq!(pid, getpid());
+            assert_eq!(uid, getuid());
+        },
+        info => panic!("unexpected siginfo {:?}", info),
+    }
+}
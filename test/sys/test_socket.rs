@@ -119,11 +119,10 @@ pub fn test_socketpair() {
 #[test]
 pub fn test_scm_rights() {
     use nix::sys::uio::IoVec;
-    use std::mem;
     use nix::unistd::{pipe, read, write, close};
     use nix::sys::socket::{socketpair, sendmsg, recvmsg,
                            AddressFamily, SockType, SockFlag,
-                           ControlMessage, MsgFlags};
+                           ControlMessage, MsgFlags, CmsgBuffer};
 
     let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
                      .unwrap();
@@ -141,8 +140,9 @@ pub fn test_scm_rights() {
 
     {
         let mut buf = [0u8; 5];
+        let mut cmsg_buffer = CmsgBuffer::with_space(cmsg_space!([RawFd; 1]));
         let iov = [IoVec::from_mut_slice(&mut buf[..])];
-        let msg = recvmsg(fd2, &iov, mem::size_of::<[RawFd; 1]>(), MsgFlags::empty()).unwrap();
+        let msg = recvmsg(fd2, &iov, Some(&mut cmsg_buffer), MsgFlags::empty()).unwrap();
 
         for cmsg in msg.cmsgs() {
             if let ControlMessage::ScmRights(fd) = cmsg {
@@ -173,19 +173,19 @@ fn test_scm_rights_single_cmsg_multiple_fds() {
     use std::os::unix::net::UnixDatagram;
     use std::os::unix::io::{RawFd, AsRawFd};
     use std::thread;
-    use nix::sys::socket::{ControlMessage, MsgFlags, sendmsg, recvmsg};
+    use nix::sys::socket::{ControlMessage, MsgFlags, CmsgBuffer, sendmsg, recvmsg};
     use nix::sys::uio::IoVec;
-    use std::mem;
     use libc;
 
     let (send, receive) = UnixDatagram::pair().unwrap();
     let thread = thread::spawn(move || {
         let mut buf = [0u8; 8];
+        let mut cmsg_buffer = CmsgBuffer::with_space(cmsg_space!([RawFd; 2]));
         let iovec = [IoVec::from_mut_slice(&mut buf)];
         let msg = recvmsg(
             receive.as_raw_fd(),
             &iovec,
-            mem::size_of::<[RawFd; 2]>(),
+            Some(&mut cmsg_buffer),
             MsgFlags::empty()
         ).unwrap();
         assert!(!msg.flags.intersects(MsgFlags::MSG_TRUNC | MsgFlags::MSG_CTRUNC));
@@ -212,6 +212,45 @@ fn test_scm_rights_single_cmsg_multiple_fds() {
     thread.join().unwrap();
 }
 
+// Verify that an undersized `CmsgBuffer` is reported as `MSG_CTRUNC`
+// instead of silently losing file descriptors.
+#[test]
+pub fn test_cmsg_truncated() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+    use nix::sys::socket::{socketpair, sendmsg, recvmsg,
+                           AddressFamily, SockType, SockFlag,
+                           ControlMessage, MsgFlags, CmsgBuffer};
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Datagram, None, SockFlag::empty())
+                     .unwrap();
+
+    let iov = [IoVec::from_slice(b"hello")];
+    let fds = [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO];
+    let cmsg = ControlMessage::ScmRights(&fds);
+    sendmsg(fd1, &iov, &[cmsg], MsgFlags::empty(), None).unwrap();
+
+    let mut buf = [0u8; 5];
+    let mut cmsg_buffer = CmsgBuffer::with_space(cmsg_space!([RawFd; 2]));
+    let iov = [IoVec::from_mut_slice(&mut buf[..])];
+    let msg = recvmsg(fd2, &iov, Some(&mut cmsg_buffer), MsgFlags::empty()).unwrap();
+    assert!(msg.flags.contains(MsgFlags::MSG_CTRUNC));
+    assert_eq!(msg.checked_cmsgs().err(), Some(Error::Sys(Errno::ENOBUFS)));
+
+    for cmsg in msg.cmsgs() {
+        if let ControlMessage::ScmRights(fds) = cmsg {
+            assert!(fds.len() < 3);
+            for fd in fds {
+                close(*fd).unwrap();
+            }
+        }
+    }
+    close(fd1).unwrap();
+    close(fd2).unwrap();
+}
+
 // Verify `sendmsg` builds a valid `msghdr` when passing an empty
 // `cmsgs` argument.  This should result in a msghdr with a nullptr
 // msg_control field and a msg_controllen of 0 when calling into the
@@ -220,10 +259,9 @@ fn test_scm_rights_single_cmsg_multiple_fds() {
 pub fn test_sendmsg_empty_cmsgs() {
     use nix::sys::uio::IoVec;
     use nix::unistd::close;
-    use std::mem;
     use nix::sys::socket::{socketpair, sendmsg, recvmsg,
                            AddressFamily, SockType, SockFlag,
-                           MsgFlags};
+                           MsgFlags, CmsgBuffer};
 
     let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
                      .unwrap();
@@ -236,8 +274,9 @@ pub fn test_sendmsg_empty_cmsgs() {
 
     {
         let mut buf = [0u8; 5];
+        let mut cmsg_buffer = CmsgBuffer::with_space(cmsg_space!([RawFd; 1]));
         let iov = [IoVec::from_mut_slice(&mut buf[..])];
-        let msg = recvmsg(fd2, &iov, mem::size_of::<[RawFd; 1]>(), MsgFlags::empty()).unwrap();
+        let msg = recvmsg(fd2, &iov, Some(&mut cmsg_buffer), MsgFlags::empty()).unwrap();
 
         for _ in msg.cmsgs() {
             panic!("unexpected cmsg");
@@ -247,6 +286,217 @@ pub fn test_sendmsg_empty_cmsgs() {
     }
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_scm_credentials() {
+    use nix::sys::uio::IoVec;
+    use nix::unistd::{close, getpid, getuid, getgid};
+    use nix::sys::socket::{socketpair, sendmsg, recvmsg, setsockopt,
+                           AddressFamily, SockType, SockFlag,
+                           ControlMessage, MsgFlags, UnixCredentials, CmsgBuffer};
+    use nix::sys::socket::sockopt::PassCred;
+
+    let (send, recv) = socketpair(AddressFamily::Unix, SockType::Datagram, None, SockFlag::empty())
+                       .unwrap();
+    setsockopt(recv, PassCred, &true).unwrap();
+
+    {
+        let iov = [IoVec::from_slice(b"hello")];
+        let cred = UnixCredentials::current();
+        let cmsg = ControlMessage::ScmCredentials(&cred);
+        assert_eq!(sendmsg(send, &iov, &[cmsg], MsgFlags::empty(), None).unwrap(), 5);
+        close(send).unwrap();
+    }
+
+    {
+        let mut buf = [0u8; 5];
+        let mut cmsg_buffer = CmsgBuffer::with_space(cmsg_space!(UnixCredentials));
+        let iov = [IoVec::from_mut_slice(&mut buf[..])];
+        let msg = recvmsg(recv, &iov, Some(&mut cmsg_buffer), MsgFlags::empty()).unwrap();
+        let mut received_cred = None;
+
+        for cmsg in msg.cmsgs() {
+            if let ControlMessage::ScmCredentials(cred) = cmsg {
+                assert!(received_cred.is_none());
+                assert_eq!(cred.pid(), libc::pid_t::from(getpid()));
+                assert_eq!(cred.uid(), libc::uid_t::from(getuid()));
+                assert_eq!(cred.gid(), libc::gid_t::from(getgid()));
+                received_cred = Some(*cred);
+            } else {
+                panic!("unexpected cmsg");
+            }
+        }
+        received_cred.expect("no creds received");
+        assert!(!msg.flags.intersects(MsgFlags::MSG_TRUNC | MsgFlags::MSG_CTRUNC));
+        close(recv).unwrap();
+    }
+}
+
+// The payload of `SCM_CREDENTIALS` is not a multiple of the alignment, so the
+// message following it starts after some padding.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_scm_credentials_and_rights() {
+    use nix::sys::uio::IoVec;
+    use nix::unistd::{close, getpid, pipe};
+    use nix::sys::socket::{socketpair, sendmsg, recvmsg, setsockopt,
+                           AddressFamily, SockType, SockFlag,
+                           ControlMessage, MsgFlags, UnixCredentials, CmsgBuffer};
+    use nix::sys::socket::sockopt::PassCred;
+
+    let (send, recv) = socketpair(AddressFamily::Unix, SockType::Datagram, None, SockFlag::empty())
+                       .unwrap();
+    setsockopt(recv, PassCred, &true).unwrap();
+    let (r, w) = pipe().unwrap();
+
+    {
+        let iov = [IoVec::from_slice(b"hello")];
+        let cred = UnixCredentials::current();
+        let fds = [r, w];
+        let cmsgs = [ControlMessage::ScmCredentials(&cred), ControlMessage::ScmRights(&fds)];
+        assert_eq!(sendmsg(send, &iov, &cmsgs, MsgFlags::empty(), None).unwrap(), 5);
+        close(send).unwrap();
+    }
+
+    {
+        let mut buf = [0u8; 5];
+        let mut cmsg_buffer = CmsgBuffer::with_space(cmsg_space!(UnixCredentials, [RawFd; 2]));
+        let iov = [IoVec::from_mut_slice(&mut buf[..])];
+        let msg = recvmsg(recv, &iov, Some(&mut cmsg_buffer), MsgFlags::empty()).unwrap();
+        let mut received_cred = None;
+        let mut received_fds = Vec::new();
+
+        for cmsg in msg.cmsgs() {
+            match cmsg {
+                ControlMessage::ScmCredentials(cred) => received_cred = Some(*cred),
+                ControlMessage::ScmRights(fds) => received_fds.extend_from_slice(fds),
+                _ => panic!("unexpected cmsg"),
+            }
+        }
+        let cred = received_cred.expect("no creds received");
+        assert_eq!(cred.pid(), libc::pid_t::from(getpid()));
+        assert_eq!(received_fds.len(), 2);
+        assert!(!msg.flags.intersects(MsgFlags::MSG_TRUNC | MsgFlags::MSG_CTRUNC));
+        for fd in received_fds {
+            close(fd).unwrap();
+        }
+        close(recv).unwrap();
+    }
+    close(r).unwrap();
+    close(w).unwrap();
+}
+
+// Sends a datagram over loopback with an `IP_TTL` message and checks that
+// the receiver sees both the TTL and the nanosecond timestamp.
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_ip_ttl_timestampns_cmsgs() {
+    use libc;
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+    use nix::sys::socket::{socket, bind, getsockname, sendmsg, recvmsg, setsockopt,
+                           AddressFamily, SockType, SockFlag, SockAddr, IpAddr,
+                           ControlMessage, MsgFlags, CmsgBuffer};
+    use nix::sys::socket::sockopt::ReceiveTimestampns;
+    use nix::sys::time::TimeValLike;
+    use std::mem;
+
+    let sock = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None).unwrap();
+    bind(sock, &SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0))).unwrap();
+    let address = getsockname(sock).unwrap();
+    setsockopt(sock, ReceiveTimestampns, &true).unwrap();
+    let one: libc::c_int = 1;
+    let res = unsafe {
+        libc::setsockopt(sock, libc::IPPROTO_IP, libc::IP_RECVTTL,
+                         &one as *const _ as *const libc::c_void,
+                         mem::size_of_val(&one) as libc::socklen_t)
+    };
+    assert_eq!(res, 0);
+
+    let ttl: libc::c_int = 7;
+    let iov = [IoVec::from_slice(b"hello")];
+    let cmsg = ControlMessage::IpTtl(&ttl);
+    assert_eq!(sendmsg(sock, &iov, &[cmsg], MsgFlags::empty(), Some(&address)).unwrap(), 5);
+
+    let mut buf = [0u8; 5];
+    let mut cmsg_buffer = CmsgBuffer::with_space(cmsg_space!(libc::c_int, nix::sys::time::TimeSpec));
+    let iov = [IoVec::from_mut_slice(&mut buf[..])];
+    let msg = recvmsg(sock, &iov, Some(&mut cmsg_buffer), MsgFlags::empty()).unwrap();
+    let mut received_ttl = None;
+    let mut received_time = None;
+    for cmsg in msg.cmsgs() {
+        match cmsg {
+            ControlMessage::IpTtl(&ttl) => received_ttl = Some(ttl),
+            ControlMessage::ScmTimestampns(&time) => received_time = Some(time),
+            _ => panic!("unexpected cmsg"),
+        }
+    }
+    assert_eq!(received_ttl, Some(7));
+    assert!(received_time.expect("no timestamp received").num_nanoseconds() > 0);
+    assert!(!msg.flags.intersects(MsgFlags::MSG_TRUNC | MsgFlags::MSG_CTRUNC));
+    close(sock).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_sendmmsg_recvmmsg() {
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+    use nix::sys::socket::{socket, bind, getsockname, sendmmsg, recvmmsg, setsockopt,
+                           AddressFamily, SockType, SockFlag, SockAddr, IpAddr,
+                           ControlMessage, MsgFlags, CmsgBuffer,
+                           SendMmsgData, RecvMmsgData};
+    use nix::sys::socket::sockopt::ReceiveTimestampns;
+    use nix::sys::time::{TimeSpec, TimeValLike};
+
+    let rsock = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None).unwrap();
+    bind(rsock, &SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0))).unwrap();
+    let raddr = getsockname(rsock).unwrap();
+    setsockopt(rsock, ReceiveTimestampns, &true).unwrap();
+    let ssock = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None).unwrap();
+    bind(ssock, &SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0))).unwrap();
+    let saddr = getsockname(ssock).unwrap();
+
+    let payloads: [&[u8]; 3] = [b"one", b"three", b"fifteen"];
+    let iovs: Vec<[IoVec<&[u8]>; 1]> = payloads.iter()
+        .map(|p| [IoVec::from_slice(p)])
+        .collect();
+    let data: Vec<SendMmsgData> = iovs.iter()
+        .map(|iov| SendMmsgData { iov, cmsgs: &[], addr: Some(&raddr) })
+        .collect();
+    assert_eq!(sendmmsg(ssock, &data, MsgFlags::empty()).unwrap(), vec![3, 5, 7]);
+
+    let mut bufs = [[0u8; 16]; 4];
+    let mut cmsg_buffers = vec![CmsgBuffer::with_space(cmsg_space!(TimeSpec)); 4];
+    let iovs: Vec<[IoVec<&mut [u8]>; 1]> = bufs.iter_mut()
+        .map(|b| [IoVec::from_mut_slice(&mut b[..])])
+        .collect();
+    let mut data: Vec<RecvMmsgData> = iovs.iter()
+        .zip(cmsg_buffers.iter_mut())
+        .map(|(iov, cmsg_buffer)| RecvMmsgData { iov, cmsg_buffer: Some(cmsg_buffer) })
+        .collect();
+    {
+        let msgs = recvmmsg(rsock, &mut data, MsgFlags::MSG_WAITFORONE,
+                            Some(TimeSpec::seconds(1))).unwrap();
+        assert_eq!(msgs.len(), 3);
+        for (msg, payload) in msgs.iter().zip(payloads.iter()) {
+            assert_eq!(msg.bytes, payload.len());
+            assert_eq!(msg.address.unwrap().to_str(), saddr.to_str());
+            match msg.cmsgs().next() {
+                Some(ControlMessage::ScmTimestampns(_)) => (),
+                _ => panic!("no timestamp received"),
+            }
+        }
+    }
+    drop(data);
+    drop(iovs);
+    for (buf, payload) in bufs.iter().zip(payloads.iter()) {
+        assert_eq!(&buf[..payload.len()], *payload);
+    }
+    close(ssock).unwrap();
+    close(rsock).unwrap();
+}
+
 // Test creating and using named unix domain sockets
 #[test]
 pub fn test_unixdomain() {
@@ -300,3 +550,358 @@ pub fn test_syscontrol() {
     // requires root privileges
     // connect(fd, &sockaddr).expect("connect failed");
 }
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_netlink_request() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::sys::socket::SockProtocol;
+    use nix::sys::socket::netlink::{NetlinkSocket, NlMsgBuilder, NlmFlags};
+
+    const RTM_GETLINK: u16 = 18;
+    const IFLA_IFNAME: u16 = 3;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct IfInfoMsg {
+        family: u8,
+        pad: u8,
+        ty: u16,
+        index: i32,
+        flags: u32,
+        change: u32,
+    }
+
+    fn ifinfomsg(index: i32) -> IfInfoMsg {
+        IfInfoMsg { family: 0, pad: 0, ty: 0, index, flags: 0, change: 0 }
+    }
+
+    let mut sock = NetlinkSocket::new(SockProtocol::NetlinkRoute).unwrap();
+
+    // The loopback device always has index 1
+    let mut msg = NlMsgBuilder::new(RTM_GETLINK, NlmFlags::empty());
+    msg.push_header(&ifinfomsg(1));
+    let mut names = Vec::new();
+    sock.request(msg, |reply| {
+        assert_eq!(reply.ty(), RTM_GETLINK - 2);
+        let name = reply.attrs::<IfInfoMsg>().find_attr(IFLA_IFNAME).unwrap();
+        names.push(name.as_str().unwrap().to_owned());
+        Ok(())
+    }).unwrap();
+    assert_eq!(names, vec!["lo".to_owned()]);
+
+    let mut msg = NlMsgBuilder::new(RTM_GETLINK, NlmFlags::empty());
+    msg.push_header(&ifinfomsg(0x7fff_ffff));
+    assert_eq!(sock.ack(msg), Err(Error::Sys(Errno::ENODEV)));
+
+    // The socket is still usable after an error
+    let mut msg = NlMsgBuilder::new(RTM_GETLINK, NlmFlags::NLM_F_DUMP);
+    msg.push_header(&ifinfomsg(0));
+    let mut links = 0;
+    sock.request(msg, |_| { links += 1; Ok(()) }).unwrap();
+    assert!(links >= 1);
+
+    // The rest of a dump is drained when `f` fails, so the next request
+    // only sees its own replies
+    let mut msg = NlMsgBuilder::new(RTM_GETLINK, NlmFlags::NLM_F_DUMP);
+    msg.push_header(&ifinfomsg(0));
+    assert_eq!(sock.request(msg, |_| Err(Error::Sys(Errno::EIO))), Err(Error::Sys(Errno::EIO)));
+    let mut msg = NlMsgBuilder::new(RTM_GETLINK, NlmFlags::NLM_F_DUMP);
+    msg.push_header(&ifinfomsg(0));
+    let mut relinks = 0;
+    sock.request(msg, |reply| {
+        assert_eq!(reply.ty(), RTM_GETLINK - 2);
+        relinks += 1;
+        Ok(())
+    }).unwrap();
+    assert_eq!(relinks, links);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_vsock_addr() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::sys::socket::*;
+    use nix::unistd::close;
+
+    let addr = SockAddr::new_vsock(VMADDR_CID_LOCAL, 1234);
+    assert_eq!(addr.family(), AddressFamily::Vsock);
+    assert_eq!(addr.to_str(), "cid: 1 port: 1234");
+    let decoded = unsafe { SockAddr::from_libc_sockaddr(addr.as_ffi_pair().0) };
+    assert_eq!(decoded, Some(addr));
+
+    let fd = match socket(AddressFamily::Vsock, SockType::Stream, SockFlag::empty(), None) {
+        Ok(fd) => fd,
+        // The vsock module is not loaded
+        Err(_) => return,
+    };
+    bind(fd, &SockAddr::new_vsock(VMADDR_CID_ANY, VMADDR_PORT_ANY)).unwrap();
+    let port = match getsockname(fd).unwrap() {
+        SockAddr::Vsock(addr) => {
+            assert_eq!(addr.cid(), VMADDR_CID_ANY);
+            assert!(addr.port() != VMADDR_PORT_ANY);
+            addr.port()
+        },
+        addr => panic!("unexpected address {:?}", addr),
+    };
+    close(fd).unwrap();
+
+    // Loopback connections need the vsock_loopback transport
+    let server = socket(AddressFamily::Vsock, SockType::Stream, SockFlag::empty(), None).unwrap();
+    match bind(server, &SockAddr::new_vsock(VMADDR_CID_LOCAL, port)) {
+        Ok(()) => (),
+        Err(Error::Sys(Errno::EADDRNOTAVAIL)) => {
+            close(server).unwrap();
+            return;
+        },
+        Err(e) => panic!("bind failed: {}", e),
+    }
+    listen(server, 1).unwrap();
+    let client = socket(AddressFamily::Vsock, SockType::Stream, SockFlag::empty(), None).unwrap();
+    connect(client, &SockAddr::new_vsock(VMADDR_CID_LOCAL, port)).unwrap();
+    let conn = accept(server).unwrap();
+    match getpeername(conn).unwrap() {
+        SockAddr::Vsock(addr) => assert_eq!(addr.cid(), VMADDR_CID_LOCAL),
+        addr => panic!("unexpected address {:?}", addr),
+    }
+    close(conn).unwrap();
+    close(client).unwrap();
+    close(server).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_packet_link_addr() {
+    use nix::sys::socket::*;
+    use nix::unistd::close;
+
+    // An EtherType reserved for local experiments
+    const ETH_P_EXPERIMENTAL: u16 = 0x88b5;
+    let protocol = ETH_P_EXPERIMENTAL.to_be();
+
+    let fd = match socket(AddressFamily::Packet, SockType::Datagram, SockFlag::empty(), None) {
+        Ok(fd) => fd,
+        // Packet sockets need CAP_NET_RAW
+        Err(_) => return,
+    };
+    let local = LinkAddr::new(protocol, 1, 0, 0, &[]);
+    bind(fd, &SockAddr::Link(local)).unwrap();
+    match getsockname(fd).unwrap() {
+        SockAddr::Link(addr) => {
+            assert_eq!(addr.protocol(), protocol);
+            assert_eq!(addr.ifindex(), 1);
+        },
+        addr => panic!("unexpected address {:?}", addr),
+    }
+
+    let dst = LinkAddr::new(protocol, 1, 0, 0, &[0; 6]);
+    assert_eq!(sendto(fd, b"hello", &SockAddr::Link(dst), MsgFlags::empty()), Ok(5));
+    let mut buf = [0u8; 16];
+    let (len, from) = recvfrom(fd, &mut buf).unwrap();
+    assert_eq!(&buf[..len], b"hello");
+    match from {
+        SockAddr::Link(addr) => {
+            assert_eq!(addr.family(), AddressFamily::Packet);
+            assert_eq!(addr.protocol(), protocol);
+            assert_eq!(addr.ifindex(), 1);
+            assert_eq!(addr.halen(), 6);
+        },
+        addr => panic!("unexpected address {:?}", addr),
+    }
+    close(fd).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_unix_addr_kind() {
+    use nix::sys::socket::*;
+    use nix::unistd::close;
+
+    let addr = UnixAddr::new("/tmp/foo").unwrap();
+    assert_eq!(addr.kind(), UnixAddrKind::Pathname(Path::new("/tmp/foo")));
+    let addr = UnixAddr::new_abstract(b"nix\0test").unwrap();
+    assert_eq!(addr.kind(), UnixAddrKind::Abstract(b"nix\0test"));
+    assert_eq!(UnixAddr::new_autobind().kind(), UnixAddrKind::Unnamed);
+
+    let (a, b) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
+                 .unwrap();
+    match getsockname(a).unwrap() {
+        SockAddr::Unix(addr) => assert_eq!(addr.kind(), UnixAddrKind::Unnamed),
+        addr => panic!("unexpected address {:?}", addr),
+    }
+    close(a).unwrap();
+    close(b).unwrap();
+
+    // Binding to an unnamed address picks a name in the abstract namespace
+    let fd = socket(AddressFamily::Unix, SockType::Datagram, SockFlag::empty(), None).unwrap();
+    bind(fd, &SockAddr::Unix(UnixAddr::new_autobind())).unwrap();
+    match getsockname(fd).unwrap() {
+        SockAddr::Unix(addr) => match addr.kind() {
+            UnixAddrKind::Abstract(name) => assert_eq!(name.len(), 5),
+            kind => panic!("unexpected address kind {:?}", kind),
+        },
+        addr => panic!("unexpected address {:?}", addr),
+    }
+    close(fd).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_unix_addr_path_lengths() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::sys::socket::*;
+    use nix::unistd::close;
+    use std::fs;
+    use tempdir::TempDir;
+
+    let tempdir = TempDir::new("test_unix_addr").unwrap();
+    let sun_path_len = 108;
+
+    // A path that fills sun_path completely, without a terminating null
+    let dir = tempdir.path().to_str().unwrap().to_owned();
+    let full = format!("{}/{}", dir, "x".repeat(sun_path_len - dir.len() - 1));
+    assert_eq!(full.len(), sun_path_len);
+    let addr = UnixAddr::new(full.as_str()).unwrap();
+    assert_eq!(addr.path(), Some(Path::new(&full)));
+    let fd = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None).unwrap();
+    bind(fd, &SockAddr::Unix(addr)).unwrap();
+    assert_eq!(getsockname(fd).unwrap(), SockAddr::Unix(addr));
+    close(fd).unwrap();
+
+    // Pathnames read back from the kernel compare equal to the ones we bound
+    let short = tempdir.path().join("short");
+    let fd = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None).unwrap();
+    bind(fd, &SockAddr::new_unix(&short).unwrap()).unwrap();
+    assert_eq!(getsockname(fd).unwrap(), SockAddr::new_unix(&short).unwrap());
+    close(fd).unwrap();
+
+    // Longer paths have to go through /proc/self/fd
+    let long_dir = tempdir.path().join("a".repeat(60)).join("b".repeat(60));
+    fs::create_dir_all(&long_dir).unwrap();
+    let long = long_dir.join("sock");
+    assert_eq!(UnixAddr::new(&long), Err(Error::Sys(Errno::ENAMETOOLONG)));
+
+    let server = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None).unwrap();
+    UnixAddr::with_long_path(&long, |addr| bind(server, &SockAddr::Unix(*addr))).unwrap();
+    listen(server, 1).unwrap();
+    assert!(long.exists());
+    let client = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None).unwrap();
+    UnixAddr::with_long_path(&long, |addr| connect(client, &SockAddr::Unix(*addr))).unwrap();
+    let conn = accept(server).unwrap();
+    close(conn).unwrap();
+    close(client).unwrap();
+    close(server).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_unix_channel() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::fcntl::{fcntl, FcntlArg, FdFlag};
+    use nix::sys::socket::{UnixChannel, UnixCredentials};
+    use nix::unistd::{close, pipe, read, write};
+    use std::os::unix::io::AsRawFd;
+
+    let (mut a, b) = UnixChannel::pair().unwrap();
+
+    // Payload and fds arrive together, with FD_CLOEXEC set on the fds
+    let (r, w) = pipe().unwrap();
+    assert_eq!(b.send(b"hello", &[r, w]).unwrap(), 5);
+    close(r).unwrap();
+
+    let mut buf = [0u8; 16];
+    let msg = a.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..msg.bytes], b"hello");
+    assert_eq!(msg.fds.len(), 2);
+    assert!(msg.credentials.is_none());
+    for fd in &msg.fds {
+        let flags = fcntl(fd.as_raw_fd(), FcntlArg::F_GETFD).unwrap();
+        assert!(FdFlag::from_bits_truncate(flags).contains(FdFlag::FD_CLOEXEC));
+    }
+    write(w, b"x").unwrap();
+    assert_eq!(read(msg.fds[0].as_raw_fd(), &mut buf).unwrap(), 1);
+    close(w).unwrap();
+    drop(msg);
+
+    // Credentials are only delivered once the receiver asks for them
+    a.set_pass_credentials(true).unwrap();
+    b.send_with_credentials(b"creds", &[]).unwrap();
+    let msg = a.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..msg.bytes], b"creds");
+    assert!(msg.fds.is_empty());
+    assert_eq!(msg.credentials, Some(UnixCredentials::current()));
+
+    // Credentials and fds in the same message
+    let (r, w) = pipe().unwrap();
+    b.send_with_credentials(b"both", &[r, w]).unwrap();
+    close(r).unwrap();
+    close(w).unwrap();
+    let msg = a.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..msg.bytes], b"both");
+    assert_eq!(msg.fds.len(), 2);
+    assert_eq!(msg.credentials, Some(UnixCredentials::current()));
+    write(msg.fds[1].as_raw_fd(), b"y").unwrap();
+    assert_eq!(read(msg.fds[0].as_raw_fd(), &mut buf).unwrap(), 1);
+    drop(msg);
+
+    // Too many fds for the receive buffer
+    a.set_pass_credentials(false).unwrap();
+    a.set_max_fds(1);
+    let fds = [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO];
+    b.send(b"many", &fds).unwrap();
+    assert_eq!(a.recv(&mut buf).err(), Some(Error::Sys(Errno::ENOBUFS)));
+
+    // Payload larger than the receive buffer
+    b.send(b"too long", &[]).unwrap();
+    assert_eq!(a.recv(&mut buf[..4]).err(), Some(Error::Sys(Errno::EMSGSIZE)));
+
+    // The peer hanging up reads as an empty message
+    drop(b);
+    let msg = a.recv(&mut buf).unwrap();
+    assert_eq!(msg.bytes, 0);
+    assert!(msg.fds.is_empty());
+}
+
+// socket, socketpair and accept4 set the requested flags, whether the kernel
+// supports doing so atomically or not
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_sock_flags() {
+    use nix::fcntl::{fcntl, FcntlArg, FdFlag, OFlag};
+    use nix::sys::socket::*;
+    use nix::unistd::close;
+
+    fn assert_flags(fd: RawFd) {
+        let fd_flags = FdFlag::from_bits_truncate(fcntl(fd, FcntlArg::F_GETFD).unwrap());
+        assert!(fd_flags.contains(FdFlag::FD_CLOEXEC));
+        let fl = OFlag::from_bits_truncate(fcntl(fd, FcntlArg::F_GETFL).unwrap());
+        assert!(fl.contains(OFlag::O_NONBLOCK));
+    }
+
+    let flags = SockFlag::SOCK_CLOEXEC | SockFlag::SOCK_NONBLOCK;
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None, flags).unwrap();
+    assert_flags(fd1);
+    assert_flags(fd2);
+    close(fd1).unwrap();
+    close(fd2).unwrap();
+
+    let server = socket(AddressFamily::Unix, SockType::Stream, flags, None).unwrap();
+    assert_flags(server);
+    bind(server, &SockAddr::Unix(UnixAddr::new_autobind())).unwrap();
+    listen(server, 1).unwrap();
+    let addr = getsockname(server).unwrap();
+
+    let client = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None).unwrap();
+    connect(client, &addr).unwrap();
+    let conn = accept4(server, flags).unwrap();
+    assert_flags(conn);
+
+    close(conn).unwrap();
+    close(client).unwrap();
+    close(server).unwrap();
+}
@@ -13,3 +13,163 @@ fn test_so_buf() {
     let actual = getsockopt(fd, sockopt::RcvBuf).unwrap();
     assert!(actual >= bufsize);
 }
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_tcp_options() {
+    use std::ffi::OsString;
+    use nix::sys::socket::{accept, bind, connect, listen, getsockname, InetAddr, IpAddr, SockAddr};
+    use nix::unistd::close;
+
+    let listener = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), SockProtocol::Tcp)
+                   .unwrap();
+    bind(listener, &SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0))).unwrap();
+    listen(listener, 1).unwrap();
+    setsockopt(listener, sockopt::TcpFastOpen, &5).unwrap();
+    assert_eq!(getsockopt(listener, sockopt::TcpFastOpen).unwrap(), 5);
+    assert_eq!(getsockopt(listener, sockopt::Domain).unwrap(), AddressFamily::Inet as i32);
+    assert_eq!(getsockopt(listener, sockopt::Protocol).unwrap(), libc::IPPROTO_TCP);
+
+    let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), SockProtocol::Tcp)
+             .unwrap();
+    setsockopt(fd, sockopt::TcpUserTimeout, &5000).unwrap();
+    assert_eq!(getsockopt(fd, sockopt::TcpUserTimeout).unwrap(), 5000);
+    setsockopt(fd, sockopt::TcpKeepCount, &7).unwrap();
+    assert_eq!(getsockopt(fd, sockopt::TcpKeepCount).unwrap(), 7);
+    setsockopt(fd, sockopt::TcpKeepInterval, &11).unwrap();
+    assert_eq!(getsockopt(fd, sockopt::TcpKeepInterval).unwrap(), 11);
+    setsockopt(fd, sockopt::TcpMaxSeg, &1200).unwrap();
+    // "reno" is built into every kernel
+    let reno = OsString::from("reno");
+    setsockopt(fd, sockopt::TcpCongestion, &reno).unwrap();
+    assert_eq!(getsockopt(fd, sockopt::TcpCongestion).unwrap(), reno);
+    assert!(setsockopt(fd, sockopt::TcpCongestion, &OsString::from("no-such-algorithm")).is_err());
+
+    connect(fd, &getsockname(listener).unwrap()).unwrap();
+    let conn = accept(listener).unwrap();
+    let info = getsockopt(fd, sockopt::TcpInfo).unwrap();
+    assert_eq!(info.tcpi_state, 1); // TCP_ESTABLISHED
+    assert!(info.tcpi_snd_mss > 0 && info.tcpi_snd_mss <= 1200);
+    close(conn).unwrap();
+    close(fd).unwrap();
+    close(listener).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_ip_options() {
+    use std::ffi::OsString;
+    use nix::unistd::close;
+
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), SockProtocol::Udp)
+             .unwrap();
+    setsockopt(fd, sockopt::IpTos, &0x10).unwrap();
+    assert_eq!(getsockopt(fd, sockopt::IpTos).unwrap(), 0x10);
+    setsockopt(fd, sockopt::IpTtl, &17).unwrap();
+    assert_eq!(getsockopt(fd, sockopt::IpTtl).unwrap(), 17);
+    setsockopt(fd, sockopt::IpPktInfo, &true).unwrap();
+    assert!(getsockopt(fd, sockopt::IpPktInfo).unwrap());
+    setsockopt(fd, sockopt::IpFreebind, &true).unwrap();
+    assert!(getsockopt(fd, sockopt::IpFreebind).unwrap());
+
+    assert_eq!(getsockopt(fd, sockopt::BindToDevice).unwrap(), OsString::new());
+    if setsockopt(fd, sockopt::BindToDevice, &OsString::from("lo")).is_ok() {
+        assert_eq!(getsockopt(fd, sockopt::BindToDevice).unwrap(), OsString::from("lo"));
+    }
+    close(fd).unwrap();
+
+    let fd = socket(AddressFamily::Inet6, SockType::Datagram, SockFlag::empty(), SockProtocol::Udp)
+             .unwrap();
+    setsockopt(fd, sockopt::Ipv6V6Only, &true).unwrap();
+    assert!(getsockopt(fd, sockopt::Ipv6V6Only).unwrap());
+    setsockopt(fd, sockopt::Ipv6RecvPktInfo, &true).unwrap();
+    assert!(getsockopt(fd, sockopt::Ipv6RecvPktInfo).unwrap());
+    close(fd).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_unix_peer_options() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::sys::socket::socketpair;
+    use nix::sys::stat::fstat;
+    use nix::unistd::{close, getgroups};
+
+    let (a, b) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty()).unwrap();
+    let mut groups = getsockopt(a, sockopt::PeerGroups).unwrap();
+    let mut expected = getgroups().unwrap();
+    groups.sort_by_key(|&g| libc::gid_t::from(g));
+    expected.sort_by_key(|&g| libc::gid_t::from(g));
+    expected.dedup();
+    groups.dedup();
+    assert_eq!(groups, expected);
+
+    // Without an LSM the peer has no security context
+    match getsockopt(a, sockopt::PeerSec) {
+        Ok(_) | Err(Error::Sys(Errno::ENOPROTOOPT)) => (),
+        Err(e) => panic!("SO_PEERSEC failed: {}", e),
+    }
+
+    // SO_PEERPIDFD appeared in Linux 6.5
+    if let Ok(pidfd) = getsockopt(a, sockopt::PeerPidfd) {
+        assert!(fstat(pidfd).is_ok());
+        close(pidfd).unwrap();
+    }
+    close(a).unwrap();
+    close(b).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_attach_filter() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::sys::socket::{recv, send, socketpair, MsgFlags};
+    use nix::unistd::close;
+
+    // BPF_RET | BPF_K: accept only the first two bytes of every packet
+    let prog = vec![libc::sock_filter { code: 0x06, jt: 0, jf: 0, k: 2 }];
+    let (a, b) = socketpair(AddressFamily::Unix, SockType::Datagram, None, SockFlag::empty())
+                 .unwrap();
+    setsockopt(b, sockopt::AttachFilter, &prog).unwrap();
+    send(a, b"hello", MsgFlags::empty()).unwrap();
+    let mut buf = [0u8; 16];
+    assert_eq!(recv(b, &mut buf, MsgFlags::empty()).unwrap(), 2);
+    assert_eq!(&buf[..2], b"he");
+
+    // Too long for sock_fprog: 65537 instructions must not be truncated to 1
+    let long = vec![libc::sock_filter { code: 0x06, jt: 0, jf: 0, k: 0 }; 65537];
+    assert_eq!(setsockopt(b, sockopt::AttachFilter, &long), Err(Error::Sys(Errno::EINVAL)));
+    close(a).unwrap();
+    close(b).unwrap();
+
+    let udp = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), SockProtocol::Udp)
+              .unwrap();
+    setsockopt(udp, sockopt::ReuseAddr, &true).unwrap();
+    setsockopt(udp, sockopt::ReusePort, &true).unwrap();
+    // BPF_RET | BPF_K: always pick the first socket of the group
+    let prog = vec![libc::sock_filter { code: 0x06, jt: 0, jf: 0, k: 0 }];
+    setsockopt(udp, sockopt::ReusePortCBPF, &prog).unwrap();
+    close(udp).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_packet_fanout() {
+    use nix::sys::socket::{bind, LinkAddr, SockAddr};
+    use nix::unistd::close;
+
+    let fd = match socket(AddressFamily::Packet, SockType::Raw, SockFlag::empty(), None) {
+        Ok(fd) => fd,
+        // Packet sockets need CAP_NET_RAW
+        Err(_) => return,
+    };
+    // The socket needs a protocol before it can join a fanout group.
+    let addr = LinkAddr::new((libc::ETH_P_ALL as u16).to_be(), 0, 0, 0, &[]);
+    bind(fd, &SockAddr::Link(addr)).unwrap();
+    // Group 0x1234 in PACKET_FANOUT_HASH mode
+    setsockopt(fd, sockopt::PacketFanout, &0x1234).unwrap();
+    assert_eq!(getsockopt(fd, sockopt::PacketFanout).unwrap(), 0x1234);
+    close(fd).unwrap();
+}
@@ -0,0 +1,52 @@
+use nix::Error;
+use nix::errno::Errno;
+use nix::sys::time::{clock_getres, clock_gettime, clock_nanosleep};
+use nix::sys::time::{ClockId, ClockNanosleepFlags, TimeSpec, TimeValLike};
+use nix::unistd::Pid;
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
+
+#[test]
+fn test_clock_gettime() {
+    let clocks = [ClockId::CLOCK_REALTIME, ClockId::CLOCK_REALTIME_COARSE,
+                  ClockId::CLOCK_MONOTONIC, ClockId::CLOCK_MONOTONIC_COARSE,
+                  ClockId::CLOCK_MONOTONIC_RAW, ClockId::CLOCK_BOOTTIME, ClockId::CLOCK_TAI,
+                  ClockId::CLOCK_PROCESS_CPUTIME_ID, ClockId::CLOCK_THREAD_CPUTIME_ID];
+    for &clock in &clocks {
+        let res = clock_getres(clock).unwrap();
+        assert!(res > TimeSpec::zero(), "{:?}", clock);
+        clock.now().unwrap();
+    }
+
+    let before = clock_gettime(ClockId::CLOCK_MONOTONIC).unwrap();
+    let after = ClockId::CLOCK_MONOTONIC.now().unwrap();
+    assert!(after >= before);
+}
+
+#[test]
+fn test_pid_cpu_clock_id() {
+    let clock = ClockId::pid_cpu_clock_id(Pid::this()).unwrap();
+    assert!(clock.now().unwrap() > TimeSpec::zero());
+}
+
+#[test]
+fn test_clock_from_fd() {
+    // A regular file isn't a clock device.
+    let file = File::open("/dev/null").unwrap();
+    let clock = ClockId::from_fd(file.as_raw_fd());
+    assert_eq!(clock.now(), Err(Error::Sys(Errno::EINVAL)));
+}
+
+#[test]
+fn test_clock_nanosleep() {
+    let clock = ClockId::CLOCK_MONOTONIC;
+    let start = clock.now().unwrap();
+    let request = TimeSpec::milliseconds(10);
+    assert_eq!(clock_nanosleep(clock, ClockNanosleepFlags::empty(), &request).unwrap(), None);
+    assert!(clock.now().unwrap() - start >= request);
+
+    let deadline = clock.now().unwrap() + request;
+    assert_eq!(clock_nanosleep(clock, ClockNanosleepFlags::TIMER_ABSTIME, &deadline).unwrap(),
+               None);
+    assert!(clock.now().unwrap() >= deadline);
+}
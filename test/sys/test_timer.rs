@@ -0,0 +1,53 @@
+use nix::sys::signal::{SigEvent, SigevNotify, SigSet, Signal};
+use nix::sys::time::{ClockId, ClockNanosleepFlags, Expiration, TimeSpec, TimeValLike};
+use nix::sys::timer::Timer;
+use nix::unistd::gettid;
+
+#[test]
+fn test_timer_set_get() {
+    let sigevent = SigEvent::new(SigevNotify::SigevSignal {
+        signal: Signal::SIGALRM,
+        si_value: 0,
+    });
+    let timer = Timer::new(ClockId::CLOCK_MONOTONIC, sigevent).unwrap();
+    assert_eq!(timer.get().unwrap(), None);
+
+    timer.set(Expiration::IntervalDelayed(TimeSpec::seconds(10), TimeSpec::seconds(20)),
+              ClockNanosleepFlags::empty()).unwrap();
+    match timer.get().unwrap() {
+        Some(Expiration::IntervalDelayed(value, interval)) => {
+            assert!(value <= TimeSpec::seconds(10));
+            assert_eq!(interval, TimeSpec::seconds(20));
+        },
+        other => panic!("unexpected timer setting {:?}", other),
+    }
+    assert_eq!(timer.overruns().unwrap(), 0);
+
+    timer.unset().unwrap();
+    assert_eq!(timer.get().unwrap(), None);
+}
+
+#[test]
+fn test_timer_thread_signal() {
+    // Grab the mutex for altering signals so we don't interfere with other tests.
+    #[allow(unused_variables)]
+    let m = ::SIGNAL_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let mut mask = SigSet::empty();
+    mask.add(Signal::SIGALRM);
+    mask.thread_block().unwrap();
+
+    let sigevent = SigEvent::new(SigevNotify::SigevThreadId {
+        signal: Signal::SIGALRM,
+        thread_id: gettid().into(),
+        si_value: 0,
+    });
+    let timer = Timer::new(ClockId::CLOCK_MONOTONIC, sigevent).unwrap();
+    timer.set(Expiration::OneShot(TimeSpec::milliseconds(10)), ClockNanosleepFlags::empty())
+        .unwrap();
+
+    assert_eq!(mask.wait().unwrap(), Signal::SIGALRM);
+    assert_eq!(timer.get().unwrap(), None);
+
+    mask.thread_unblock().unwrap();
+}
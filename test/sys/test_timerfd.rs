@@ -0,0 +1,55 @@
+use nix::Error;
+use nix::errno::Errno;
+use nix::sys::time::{ClockId, Expiration, TimeSpec, TimeValLike};
+use nix::sys::timerfd::{TimerFd, TimerFlags, TimerSetTimeFlags};
+use std::time::Instant;
+
+#[test]
+pub fn test_timerfd_oneshot() {
+    let timer = TimerFd::new(ClockId::CLOCK_MONOTONIC, TimerFlags::empty()).unwrap();
+
+    let before = Instant::now();
+
+    timer.set(Expiration::OneShot(TimeSpec::seconds(1)), TimerSetTimeFlags::empty()).unwrap();
+
+    assert_eq!(timer.wait().unwrap(), 1);
+
+    let millis = before.elapsed().as_secs() * 1000 +
+                 before.elapsed().subsec_nanos() as u64 / 1_000_000;
+    assert!(millis > 900);
+}
+
+#[test]
+pub fn test_timerfd_interval() {
+    let timer = TimerFd::new(ClockId::CLOCK_MONOTONIC, TimerFlags::empty()).unwrap();
+
+    let before = Instant::now();
+    timer.set(Expiration::IntervalDelayed(TimeSpec::seconds(1), TimeSpec::seconds(2)),
+              TimerSetTimeFlags::empty()).unwrap();
+
+    match timer.get().unwrap() {
+        Some(Expiration::IntervalDelayed(value, interval)) => {
+            assert!(value <= TimeSpec::seconds(1));
+            assert_eq!(interval, TimeSpec::seconds(2));
+        },
+        other => panic!("unexpected timer setting {:?}", other),
+    }
+
+    timer.wait().unwrap();
+    timer.wait().unwrap();
+
+    let millis = before.elapsed().as_secs() * 1000 +
+                 before.elapsed().subsec_nanos() as u64 / 1_000_000;
+    assert!(millis > 2900);
+}
+
+#[test]
+pub fn test_timerfd_unset() {
+    let timer = TimerFd::new(ClockId::CLOCK_MONOTONIC, TimerFlags::TFD_NONBLOCK).unwrap();
+
+    timer.set(Expiration::OneShot(TimeSpec::seconds(1)), TimerSetTimeFlags::empty()).unwrap();
+    timer.unset().unwrap();
+
+    assert_eq!(timer.get().unwrap(), None);
+    assert_eq!(timer.wait().unwrap_err(), Error::Sys(Errno::EAGAIN));
+}
@@ -29,6 +29,8 @@ mod test_pty;
           target_os = "linux",
           target_os = "macos"))]
 mod test_sendfile;
+#[cfg(target_os = "linux")]
+mod test_sched;
 mod test_stat;
 mod test_unistd;
 
@@ -1,6 +1,7 @@
-use nix::fcntl::{openat, open, OFlag, readlink, readlinkat, rename, renameat};
+use nix::fcntl::{openat, open, OFlag, FdFlag, readlink, readlinkat, rename, renameat};
+use nix::fcntl::{fcntl, get_fd_flags, get_status_flags, FcntlArg, LockType, RecordLock};
 use nix::sys::stat::Mode;
-use nix::unistd::{close, read, symlink, symlinkat};
+use nix::unistd::{close, read, symlink, symlinkat, Whence};
 use tempdir::TempDir;
 use tempfile::NamedTempFile;
 use std::fs::File;
@@ -75,6 +76,42 @@ fn test_rename_renameat() {
     assert!(src.exists());
 }
 
+#[test]
+fn test_get_flags() {
+    let tmp = NamedTempFile::new().unwrap();
+    let fd = open(tmp.path(), OFlag::O_RDWR | OFlag::O_CLOEXEC, Mode::empty()).unwrap();
+
+    assert_eq!(get_fd_flags(fd).unwrap(), FdFlag::FD_CLOEXEC);
+    fcntl(fd, FcntlArg::F_SETFD(FdFlag::empty())).unwrap();
+    assert_eq!(get_fd_flags(fd).unwrap(), FdFlag::empty());
+
+    let flags = get_status_flags(fd).unwrap();
+    assert_eq!(flags & OFlag::O_ACCMODE, OFlag::O_RDWR);
+    assert!(!flags.contains(OFlag::O_NONBLOCK));
+    fcntl(fd, FcntlArg::F_SETFL(OFlag::O_NONBLOCK)).unwrap();
+    assert!(get_status_flags(fd).unwrap().contains(OFlag::O_NONBLOCK));
+
+    close(fd).unwrap();
+}
+
+#[test]
+fn test_record_lock() {
+    let tmp = NamedTempFile::new().unwrap();
+    let fd = open(tmp.path(), OFlag::O_RDWR, Mode::empty()).unwrap();
+
+    let lock = RecordLock::new(LockType::Write, Whence::SeekSet, 0, 10);
+    lock.set(fd).unwrap();
+    // Locks held by the calling process never conflict with each other.
+    assert!(lock.get(fd).unwrap().is_none());
+    RecordLock::new(LockType::Unlock, Whence::SeekSet, 0, 10).set(fd).unwrap();
+
+    let guard = RecordLock::new(LockType::Read, Whence::SeekSet, 0, 0).try_lock(fd).unwrap();
+    assert_eq!(guard.lock().lock_type(), LockType::Read);
+    guard.unlock().unwrap();
+
+    close(fd).unwrap();
+}
+
 #[cfg(any(target_os = "linux", target_os = "android"))]
 mod linux_android {
     use std::io::prelude::*;
@@ -83,8 +120,16 @@ mod linux_android {
     use libc::loff_t;
 
     use nix::fcntl::{SpliceFFlags, FallocateFlags, fallocate, splice, tee, vmsplice};
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::fcntl::{fcntl, get_lease, get_owner_ex, get_sig, FcntlArg, FdOwner, LeaseType};
+    use nix::fcntl::{open, LockType, OFlag, RecordLock};
+    use nix::sys::stat::Mode;
+    use nix::sys::signal::{RealtimeSignal, SigNum, Signal};
+    #[cfg(target_os = "linux")]
+    use nix::fcntl::{copy_fd_to_fd, copy_file_range};
     use nix::sys::uio::IoVec;
-    use nix::unistd::{close, pipe, read, write};
+    use nix::unistd::{close, getpid, lseek, pipe, read, write, Whence};
 
     use tempfile::{tempfile, NamedTempFile};
 
@@ -170,4 +215,145 @@ mod linux_android {
         let mut buf = [0u8; 200];
         assert_eq!(100, read(fd, &mut buf).unwrap());
     }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_copy_file_range() {
+        const CONTENTS: &[u8] = b"foobarbaz";
+
+        let mut tmp1 = tempfile().unwrap();
+        let tmp2 = tempfile().unwrap();
+        tmp1.write_all(CONTENTS).unwrap();
+
+        let mut offset: loff_t = 3;
+        let res = copy_file_range(tmp1.as_raw_fd(), Some(&mut offset),
+                                  tmp2.as_raw_fd(), None, 3).unwrap();
+        assert_eq!(3, res);
+        assert_eq!(6, offset);
+
+        let mut buf = [0u8; 1024];
+        lseek(tmp2.as_raw_fd(), 0, Whence::SeekSet).unwrap();
+        assert_eq!(3, read(tmp2.as_raw_fd(), &mut buf).unwrap());
+        assert_eq!(b"bar", &buf[0..3]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_copy_fd_to_fd() {
+        const CONTENTS: &[u8] = b"abcdef123456";
+
+        let mut tmp1 = tempfile().unwrap();
+        let tmp2 = tempfile().unwrap();
+        tmp1.write_all(CONTENTS).unwrap();
+        lseek(tmp1.as_raw_fd(), 6, Whence::SeekSet).unwrap();
+
+        assert_eq!(6, copy_fd_to_fd(tmp1.as_raw_fd(), tmp2.as_raw_fd()).unwrap());
+        assert_eq!(12, lseek(tmp1.as_raw_fd(), 0, Whence::SeekCur).unwrap());
+        assert_eq!(6, lseek(tmp2.as_raw_fd(), 0, Whence::SeekCur).unwrap());
+
+        // Copying into a pipe can't use any of the in-kernel file to file methods.
+        let (rd, wr) = pipe().unwrap();
+        lseek(tmp2.as_raw_fd(), 0, Whence::SeekSet).unwrap();
+        assert_eq!(6, copy_fd_to_fd(tmp2.as_raw_fd(), wr).unwrap());
+
+        let mut buf = [0u8; 1024];
+        assert_eq!(6, read(rd, &mut buf).unwrap());
+        assert_eq!(b"123456", &buf[0..6]);
+
+        close(rd).unwrap();
+        close(wr).unwrap();
+    }
+
+    #[test]
+    fn test_ofd_lock() {
+        let tmp = NamedTempFile::new().unwrap();
+        let fd = tmp.as_raw_fd();
+
+        let mut flock: libc::flock = unsafe { ::std::mem::zeroed() };
+        flock.l_type = libc::F_WRLCK as libc::c_short;
+        flock.l_whence = libc::SEEK_SET as libc::c_short;
+        fcntl(fd, FcntlArg::F_OFD_SETLK(&flock)).unwrap();
+
+        // A lock held through the same open file description never conflicts.
+        let mut query: libc::flock = unsafe { ::std::mem::zeroed() };
+        query.l_type = libc::F_WRLCK as libc::c_short;
+        query.l_whence = libc::SEEK_SET as libc::c_short;
+        fcntl(fd, FcntlArg::F_OFD_GETLK(&mut query)).unwrap();
+        assert_eq!(query.l_type, libc::F_UNLCK as libc::c_short);
+    }
+
+    #[test]
+    fn test_ofd_record_lock() {
+        let tmp = NamedTempFile::new().unwrap();
+        let fd1 = open(tmp.path(), OFlag::O_RDWR, Mode::empty()).unwrap();
+        let fd2 = open(tmp.path(), OFlag::O_RDWR, Mode::empty()).unwrap();
+
+        let lock = RecordLock::new(LockType::Write, Whence::SeekSet, 0, 10);
+        let guard = lock.try_lock_ofd(fd1).unwrap();
+
+        // Separate open file descriptions conflict even within one process.
+        assert_eq!(lock.try_lock_ofd(fd2).unwrap_err(), Error::Sys(Errno::EAGAIN));
+        let conflict = lock.get_ofd(fd2).unwrap().unwrap();
+        assert_eq!(conflict.lock_type(), LockType::Write);
+        assert_eq!(conflict.start(), 0);
+        assert_eq!(conflict.length(), 10);
+        assert_eq!(conflict.pid(), None);
+
+        drop(guard);
+        assert!(lock.get_ofd(fd2).unwrap().is_none());
+        lock.set_ofd(fd2).unwrap();
+
+        close(fd1).unwrap();
+        close(fd2).unwrap();
+    }
+
+    #[test]
+    fn test_record_lock_guard_relative_range() {
+        let tmp = NamedTempFile::new().unwrap();
+        let fd1 = open(tmp.path(), OFlag::O_RDWR, Mode::empty()).unwrap();
+        let fd2 = open(tmp.path(), OFlag::O_RDWR, Mode::empty()).unwrap();
+
+        lseek(fd1, 5, Whence::SeekSet).unwrap();
+        let guard = RecordLock::new(LockType::Write, Whence::SeekCur, 0, 5)
+            .try_lock_ofd(fd1).unwrap();
+        assert_eq!(guard.lock().whence() as libc::c_int, libc::SEEK_SET);
+        assert_eq!(guard.lock().start(), 5);
+
+        // Moving the file offset must not change the range that is released.
+        lseek(fd1, 100, Whence::SeekSet).unwrap();
+        drop(guard);
+        let lock = RecordLock::new(LockType::Write, Whence::SeekSet, 5, 5);
+        assert!(lock.get_ofd(fd2).unwrap().is_none());
+
+        close(fd1).unwrap();
+        close(fd2).unwrap();
+    }
+
+    #[test]
+    fn test_owner_and_sig() {
+        let (rd, wr) = pipe().unwrap();
+
+        fcntl(rd, FcntlArg::F_SETOWN_EX(FdOwner::Process(getpid()))).unwrap();
+        assert_eq!(get_owner_ex(rd).unwrap(), FdOwner::Process(getpid()));
+
+        assert_eq!(get_sig(rd).unwrap(), None);
+        fcntl(rd, FcntlArg::F_SETSIG(Some(Signal::SIGUSR1.into()))).unwrap();
+        assert_eq!(get_sig(rd).unwrap(), Some(SigNum::from(Signal::SIGUSR1)));
+
+        let rt = RealtimeSignal::new(1).unwrap();
+        fcntl(rd, FcntlArg::F_SETSIG(Some(rt.into()))).unwrap();
+        assert_eq!(get_sig(rd).unwrap(), Some(SigNum::Realtime(rt)));
+
+        fcntl(rd, FcntlArg::F_SETSIG(None)).unwrap();
+        assert_eq!(get_sig(rd).unwrap(), None);
+
+        close(rd).unwrap();
+        close(wr).unwrap();
+    }
+
+    #[test]
+    fn test_get_lease() {
+        let tmp = NamedTempFile::new().unwrap();
+        assert_eq!(get_lease(tmp.as_raw_fd()).unwrap(), LeaseType::Unlock);
+    }
 }
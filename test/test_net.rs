@@ -10,3 +10,247 @@ const LOOPBACK: &[u8] = b"lo0";
 fn test_if_nametoindex() {
     assert!(if_nametoindex(&LOOPBACK[..]).is_ok());
 }
+
+// Runs `f` in a child process inside a fresh network namespace.  The test
+// is skipped with a notice when namespaces can't be created.
+#[cfg(target_os = "linux")]
+fn in_new_netns<F: FnOnce() + ::std::panic::UnwindSafe>(f: F) {
+    use libc::_exit;
+    use nix::sched::{unshare, CloneFlags};
+    use nix::sys::wait::{waitpid, WaitStatus};
+    use nix::unistd::{fork, ForkResult};
+    use std::io::{self, Write};
+
+    const SKIPPED: i32 = 2;
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    match fork().expect("Error: Fork Failed") {
+        ForkResult::Child => {
+            if unshare(CloneFlags::CLONE_NEWNET).is_err() {
+                unsafe { _exit(SKIPPED) };
+            }
+            let ok = ::std::panic::catch_unwind(f).is_ok();
+            unsafe { _exit(if ok { 0 } else { 1 }) };
+        },
+        ForkResult::Parent { child } => {
+            match waitpid(child, None) {
+                Ok(WaitStatus::Exited(_, SKIPPED)) => {
+                    let stderr = io::stderr();
+                    let mut handle = stderr.lock();
+                    writeln!(handle, "Creating a network namespace requires CAP_SYS_ADMIN. \
+                                      Skipping test.").unwrap();
+                },
+                status => assert_eq!(status, Ok(WaitStatus::Exited(child, 0))),
+            }
+        },
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_if_ioctls() {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::net::rtnetlink::RtNetlink;
+    use nix::sys::socket::{socket, AddressFamily, InetAddr, IpAddr, SockAddr, SockFlag,
+                           SockType};
+
+    in_new_netns(|| {
+        let sock = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(),
+                          None).unwrap();
+        assert!(!if_flags(sock, "lo").unwrap().contains(InterfaceFlags::IFF_UP));
+        if_set_up(sock, "lo", true).unwrap();
+        let flags = if_flags(sock, "lo").unwrap();
+        assert!(flags.contains(InterfaceFlags::IFF_UP | InterfaceFlags::IFF_LOOPBACK));
+        assert_eq!(if_index(sock, "lo"), Ok(1));
+        assert_eq!(if_indextoname(1).unwrap(), "lo");
+        assert_eq!(if_addr(sock, "lo"),
+                   Ok(SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 0))));
+
+        RtNetlink::new().unwrap().add_veth("nixveth0", "nixveth1").unwrap();
+        if_set_mtu(sock, "nixveth0", 1300).unwrap();
+        assert_eq!(if_mtu(sock, "nixveth0"), Ok(1300));
+        let hwaddr = if_hwaddr(sock, "nixveth0").unwrap();
+        assert_eq!(hwaddr.hw_type, 1); // ARPHRD_ETHER
+        assert!(hwaddr.address[..6].iter().any(|&b| b != 0));
+        assert_eq!(if_addr(sock, "nixveth0"), Err(Error::Sys(Errno::EADDRNOTAVAIL)));
+
+        if_set_name(sock, "nixveth0", "nixveth2").unwrap();
+        let index = if_index(sock, "nixveth2").unwrap();
+        let names = if_nameindex().unwrap();
+        assert!(names.contains(&(1, "lo".to_owned())));
+        assert!(names.contains(&(index, "nixveth2".to_owned())));
+        assert_eq!(if_flags(sock, "nixveth0"), Err(Error::Sys(Errno::ENODEV)));
+        assert_eq!(if_flags(sock, "a-name-that-is-too-long"),
+                   Err(Error::Sys(Errno::ENAMETOOLONG)));
+    });
+}
+
+#[cfg(target_os = "linux")]
+mod rtnetlink {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::net::if_::InterfaceFlags;
+    use nix::net::rtnetlink::*;
+    use nix::sched::{unshare, CloneFlags};
+    use nix::sys::socket::IpAddr;
+    use std::os::unix::io::{AsRawFd, RawFd};
+    use super::in_new_netns;
+
+    struct Fd(RawFd);
+
+    impl AsRawFd for Fd {
+        fn as_raw_fd(&self) -> RawFd { self.0 }
+    }
+
+    #[test]
+    fn test_links_and_addresses() {
+        in_new_netns(|| {
+            let mut rtnl = RtNetlink::new().unwrap();
+            let lo = rtnl.link_by_name("lo").unwrap();
+            assert!(!lo.flags.contains(InterfaceFlags::IFF_UP));
+            rtnl.set_link_up(lo.index, true).unwrap();
+            assert!(rtnl.link_by_index(lo.index).unwrap().flags.contains(InterfaceFlags::IFF_UP));
+
+            rtnl.add_veth("nixveth0", "nixveth1").unwrap();
+            let veth = rtnl.link_by_name("nixveth0").unwrap();
+            assert_eq!(veth.kind, Some("veth".to_owned()));
+            rtnl.set_link_mtu(veth.index, 1400).unwrap();
+            rtnl.set_link_address(veth.index, &[0x02, 0, 0, 0, 0, 0x42]).unwrap();
+            rtnl.set_link_name(veth.index, "nixveth2").unwrap();
+            let veth = rtnl.link_by_index(veth.index).unwrap();
+            assert_eq!(veth.name, "nixveth2");
+            assert_eq!(veth.mtu, Some(1400));
+            assert_eq!(veth.address, Some(vec![0x02, 0, 0, 0, 0, 0x42]));
+            assert_eq!(rtnl.links().unwrap().len(), 3);
+
+            let addr = IpAddr::new_v4(10, 23, 0, 1);
+            rtnl.add_address(veth.index, addr, 24).unwrap();
+            assert_eq!(rtnl.add_address(veth.index, addr, 24), Err(Error::Sys(Errno::EEXIST)));
+            let addresses = rtnl.addresses().unwrap();
+            assert!(addresses.iter().any(|a| a.index == veth.index && a.address == addr &&
+                                            a.prefix_len == 24));
+            let addr6 = IpAddr::new_v6(0xfd00, 0, 0, 0, 0, 0, 0, 1);
+            rtnl.add_address(veth.index, addr6, 64).unwrap();
+            rtnl.delete_address(veth.index, addr6, 64).unwrap();
+
+            rtnl.delete_link(veth.index).unwrap();
+            assert_eq!(rtnl.link_by_name("nixveth1"), Err(Error::Sys(Errno::ENODEV)));
+        });
+    }
+
+    #[test]
+    fn test_routes() {
+        in_new_netns(|| {
+            let mut rtnl = RtNetlink::new().unwrap();
+            rtnl.add_veth("nixveth0", "nixveth1").unwrap();
+            let veth = rtnl.link_by_name("nixveth0").unwrap();
+            rtnl.set_link_up(veth.index, true).unwrap();
+            rtnl.add_address(veth.index, IpAddr::new_v4(10, 23, 0, 1), 24).unwrap();
+
+            let route = Route::new(IpAddr::new_v4(10, 42, 0, 0), 16)
+                .via(IpAddr::new_v4(10, 23, 0, 2))
+                .dev(veth.index);
+            rtnl.add_route(&route).unwrap();
+            let gateway = IpAddr::new_v4(10, 23, 0, 254);
+            rtnl.add_route(&Route::default_via(gateway)).unwrap();
+
+            let routes = rtnl.routes().unwrap();
+            assert!(routes.iter().any(|r| r.destination == route.destination &&
+                                         r.prefix_len == 16 &&
+                                         r.gateway == route.gateway &&
+                                         r.index == Some(veth.index) &&
+                                         r.table == u32::from(RT_TABLE_MAIN)));
+            assert!(routes.iter().any(|r| r.destination.is_none() && r.gateway == Some(gateway)));
+
+            rtnl.delete_route(&route).unwrap();
+            assert_eq!(rtnl.delete_route(&route), Err(Error::Sys(Errno::ESRCH)));
+        });
+    }
+
+    #[test]
+    fn test_interfaces() {
+        use nix::ifaddrs::interfaces;
+        use nix::sys::socket::{sendto, socket, AddressFamily, InetAddr, MsgFlags, SockAddr,
+                               SockFlag, SockType};
+
+        in_new_netns(|| {
+            let mut rtnl = RtNetlink::new().unwrap();
+            rtnl.set_link_up(1, true).unwrap();
+            rtnl.add_veth("nixveth0", "nixveth1").unwrap();
+            let veth = rtnl.link_by_name("nixveth0").unwrap();
+            rtnl.set_link_mtu(veth.index, 1280).unwrap();
+            let addr6 = IpAddr::new_v6(0xfd00, 0, 0, 0, 0, 0, 0, 1);
+            rtnl.add_address(veth.index, IpAddr::new_v4(10, 23, 0, 1), 24).unwrap();
+            rtnl.add_address(veth.index, addr6, 64).unwrap();
+
+            // Generate some traffic on the loopback interface.
+            let sock = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(),
+                              None).unwrap();
+            let dst = SockAddr::new_inet(InetAddr::new(IpAddr::new_v4(127, 0, 0, 1), 9));
+            sendto(sock, b"hello", &dst, MsgFlags::empty()).unwrap();
+
+            let interfaces = interfaces().unwrap();
+            assert_eq!(interfaces.len(), 3);
+            let lo = &interfaces[0];
+            assert_eq!(lo.link.name, "lo");
+            assert!(lo.link.stats.unwrap().tx_packets > 0);
+            assert!(lo.addresses.iter().any(|a| a.address == IpAddr::new_v4(127, 0, 0, 1) &&
+                                               a.scope == RT_SCOPE_HOST));
+
+            let veth = interfaces.iter().find(|i| i.link.name == "nixveth0").unwrap();
+            assert_eq!(veth.link.mtu, Some(1280));
+            assert_eq!(veth.link.address.as_ref().map(|a| a.len()), Some(6));
+            let v6 = veth.addresses.iter().find(|a| a.address == addr6).unwrap();
+            assert_eq!(v6.prefix_len, 64);
+            assert_eq!(v6.scope, RT_SCOPE_UNIVERSE);
+        });
+    }
+
+    #[test]
+    fn test_interfaces_in_netns() {
+        use nix::fcntl::{open, OFlag};
+        use nix::ifaddrs::interfaces_in_netns;
+        use nix::sys::stat::Mode;
+        use nix::unistd::close;
+
+        in_new_netns(|| {
+            RtNetlink::new().unwrap().add_veth("nixveth0", "nixveth1").unwrap();
+            let ns = open("/proc/self/ns/net", OFlag::O_RDONLY | OFlag::O_CLOEXEC,
+                          Mode::empty()).unwrap();
+            unshare(CloneFlags::CLONE_NEWNET).unwrap();
+
+            let outer = interfaces_in_netns(&Fd(ns)).unwrap();
+            assert!(outer.iter().any(|i| i.link.name == "nixveth1"));
+            // The thread stays in its own namespace.
+            let inner = RtNetlink::new().unwrap().interfaces().unwrap();
+            assert_eq!(inner.len(), 1);
+            close(ns).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_move_link_to_netns() {
+        use nix::fcntl::{open, OFlag};
+        use nix::sys::stat::Mode;
+        use nix::unistd::close;
+
+        in_new_netns(|| {
+            let mut rtnl = RtNetlink::new().unwrap();
+            // Keep a handle on this namespace before creating another one.
+            let ns = open("/proc/self/ns/net", OFlag::O_RDONLY | OFlag::O_CLOEXEC,
+                          Mode::empty()).unwrap();
+            unshare(CloneFlags::CLONE_NEWNET).unwrap();
+            let mut inner = RtNetlink::new().unwrap();
+            inner.add_veth("nixveth0", "nixveth1").unwrap();
+            let peer = inner.link_by_name("nixveth1").unwrap();
+
+            inner.set_link_netns_fd(peer.index, &Fd(ns)).unwrap();
+            assert_eq!(inner.link_by_name("nixveth1"), Err(Error::Sys(Errno::ENODEV)));
+            assert!(rtnl.link_by_name("nixveth1").is_ok());
+            close(ns).unwrap();
+        });
+    }
+}
@@ -0,0 +1,27 @@
+use libc::_exit;
+use nix::sched::{unshare, CloneFlags, TimeNsOffsets};
+use nix::sys::time::{TimeSpec, TimeValLike};
+use nix::sys::wait::{waitpid, WaitStatus};
+use nix::unistd::{fork, ForkResult, Pid};
+
+#[test]
+fn test_timens_offsets() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let offsets = TimeNsOffsets::new(TimeSpec::seconds(3600), TimeSpec::milliseconds(-1500));
+    match fork().expect("Error: Fork Failed") {
+        ForkResult::Child => {
+            // Time namespaces need Linux 5.6 and CAP_SYS_ADMIN, skip the test without them.
+            if unshare(CloneFlags::CLONE_NEWTIME).is_err() {
+                unsafe { _exit(0) };
+            }
+            let ok = offsets.write(Pid::this()).is_ok() &&
+                     TimeNsOffsets::read(Pid::this()).ok() == Some(offsets);
+            unsafe { _exit(if ok { 0 } else { 1 }) };
+        },
+        ForkResult::Parent { child } => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+    }
+}